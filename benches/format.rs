@@ -0,0 +1,23 @@
+//! Formatting the remaining time runs for every timer on every tick, so it must not allocate.
+
+use std::hint::black_box;
+use criterion::{
+    criterion_group,
+    criterion_main,
+    Criterion
+};
+
+use endzeit::units::UnitFormat;
+
+fn format(c: &mut Criterion) {
+    let all = UnitFormat::default();
+    let capped = UnitFormat::new(Some("dhms"), Some(2)).unwrap();
+    let mut out = String::new();
+
+    c.bench_function("write_to all units", |b| b.iter(|| all.write_to(black_box(3_456_789), &mut out)));
+    c.bench_function("write_to dhms, 2 units", |b| b.iter(|| capped.write_to(black_box(3_456_789), &mut out)));
+    c.bench_function("format all units", |b| b.iter(|| all.format(black_box(3_456_789))));
+}
+
+criterion_group!(benches, format);
+criterion_main!(benches);
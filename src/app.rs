@@ -0,0 +1,1591 @@
+use std::collections::VecDeque;
+use std::io::{
+    self,
+    Write
+};
+use std::sync::mpsc::{
+    self,
+    Receiver,
+    Sender
+};
+use std::thread;
+use std::time::Duration;
+use chrono::TimeDelta;
+use color_eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{
+        self,
+        Event,
+        KeyCode,
+        MouseButton,
+        MouseEvent,
+        MouseEventKind
+    },
+    layout::{
+        Constraint,
+        Layout,
+        Position,
+        Rect
+    },
+    style::{
+        Modifier,
+        Style
+    },
+    text::Line,
+    widgets::{
+        Block,
+        Borders,
+        Clear,
+        Paragraph,
+        Tabs,
+        Widget
+    },
+    DefaultTerminal,
+};
+use serde_json::json;
+
+use crate::awake::KeepAwake;
+use crate::clock;
+use crate::control::{
+    Control,
+    TimerStatus,
+    TimerWait
+};
+use crate::countdown::{
+    Countdown,
+    HookResult
+};
+use crate::desktop;
+use crate::dnd::DoNotDisturb;
+use crate::history::{
+    self,
+    Entry
+};
+use crate::i18n::Language;
+use crate::media::MediaCue;
+use crate::milestone::{
+    BeforeEnd,
+    Milestone
+};
+use crate::pane::OutputPane;
+use crate::prompt::{
+    centered,
+    Confirm,
+    Prompt,
+    PromptAction
+};
+use crate::notify::{
+    self,
+    Escalation,
+    Sound,
+    Tick
+};
+use crate::keys::{
+    key_name,
+    parse_key,
+    KeyBindings
+};
+use crate::record::{
+    Recorded,
+    Recorder
+};
+use crate::session::Session;
+use crate::rpc;
+use crate::retry::{
+    Attempt,
+    Retry
+};
+use crate::metrics;
+use crate::mqtt::Mqtt;
+use crate::push::Push;
+use crate::settings::Settings;
+use crate::signals;
+use crate::source::TargetSource;
+use crate::spec::{
+    parse_datetime,
+    parse_target_spec
+};
+use crate::template::Template;
+use crate::theme::Theme;
+use crate::term::{
+    set_badge,
+    TermNotify
+};
+use crate::units::format_seconds;
+use crate::verify::{
+    Verdict,
+    Verification
+};
+
+/// How long to wait for input before redrawing, unless configured otherwise.
+pub const DEFAULT_TICK_RATE: Duration = Duration::from_millis(333);
+
+/// Below this many rows per timer the stacked layout switches to tabs.
+const MIN_GAUGE_HEIGHT: u16 = 3;
+
+/// How much the add/subtract time keys move the target.
+const SMALL_STEP: TimeDelta = TimeDelta::minutes(1);
+const BIG_STEP: TimeDelta = TimeDelta::minutes(5);
+
+/// How often the ticker line moves, independent of the tick rate.
+const TICKER_FRAME: Duration = Duration::from_millis(100);
+
+/// Characters per second the ticker line scrolls by.
+const TICKER_SPEED: f64 = 8.0;
+
+const TICKER_SEPARATOR: &str = " | ";
+
+/// How often `--flash` inverts the screen, and how long one blink of inverted and normal takes in seconds.
+const FLASH_COUNT: u32 = 3;
+const FLASH_PERIOD: f64 = 0.4;
+/// Redraws while flashing, often enough for the blinks.
+const FLASH_FRAME: Duration = Duration::from_millis(50);
+
+/// How long `--confirm-execute` waits for an answer before leaving the commands out.
+const CONFIRM_EXECUTE_SECONDS: f64 = 30.0;
+
+/// Shown on a timer while `--alarm` alerts about it.
+const ALARM_NOTE: &str = "alarm, press any key or z to snooze";
+
+/// Seconds between bells of `--bell-count`, terminals merge bells that come quicker.
+const BELL_INTERVAL: f64 = 0.3;
+
+/// Drawn in the top right corner when the mouse is enabled, clicking it quits.
+const CLOSE_BUTTON: &str = "[x]";
+
+/// What the open prompt's input is used for.
+enum PromptKind {
+    NewTimer,
+    EditTarget,
+    EditName,
+}
+
+pub struct App {
+    countdowns: Vec<Countdown>,
+    selected: usize,
+    prompt: Option<(PromptKind, Prompt)>,
+    confirm_quit: bool,
+    confirming_quit: bool,
+    show_help: bool,
+    /// What the commands of finished timers printed, until a key closes it
+    output_pane: Option<OutputPane>,
+    mouse: bool,
+    /// The whole terminal as of the last draw
+    area: Rect,
+    max_retarget: Option<TimeDelta>,
+    exit_after: TimeDelta,
+    finished_since: Option<f64>,
+    retarget_command: Option<String>,
+    /// `--on-cancel`
+    cancel_command: Option<String>,
+    term_notify: Option<TermNotify>,
+    escalation: Escalation,
+    /// `--ntfy`, `--pushover`, `--telegram` and the chats of `--notify`
+    push: Vec<Push>,
+    /// Raise a desktop notification for every finished timer
+    desktop_notify: bool,
+    /// Also at halfway
+    desktop_notify_halfway: bool,
+    /// Seconds left at which to notify
+    notify_at: Vec<u64>,
+    /// `--exec-at` commands
+    milestones: Vec<Milestone>,
+    /// `--execute-before` commands
+    before_end: Vec<BeforeEnd>,
+    /// Seconds left of `notify_at` and `before_end` together
+    thresholds: Vec<u64>,
+    bell_count: u32,
+    flash: bool,
+    /// When the current flash started, and whether the screen is inverted in this frame
+    flashing: Option<f64>,
+    inverted: bool,
+    alarm_every: Option<Duration>,
+    snooze: Duration,
+    /// The timer `--alarm` is alerting about and when it last did
+    ringing: Option<(usize, f64)>,
+    alarm: Option<Sound>,
+    volume: u8,
+    /// How often `--tick` ticks, and whether it does right now
+    tick: Tick,
+    ticking: bool,
+    /// The selected timer and its remaining time in ticks at the last check
+    last_tick: Option<(usize, u64)>,
+    verification: Option<Verification>,
+    /// Hooks being verified, by the index of their timer
+    verifying: Vec<(usize, Receiver<Verdict>)>,
+    /// `--confirm-execute`
+    confirm_execute: bool,
+    /// The timer whose commands are being asked about, and since when
+    confirming_execute: Option<(usize, f64)>,
+    /// `--keep-awake`
+    keep_awake: bool,
+    /// `--media`
+    media: Vec<MediaCue>,
+    /// Held while a timer runs
+    awake: Option<KeepAwake>,
+    /// Set once taking it failed, so it isn't tried again on every tick
+    awake_failed: bool,
+    /// `--dnd`
+    dnd: bool,
+    /// On while a timer runs
+    do_not_disturb: Option<DoNotDisturb>,
+    /// Set once turning it on failed
+    dnd_failed: bool,
+    /// `--execute-retries`
+    retry: Option<Retry>,
+    /// Failed completion commands being tried again, by timer
+    retrying: Vec<(usize, Receiver<Attempt>)>,
+    /// Finished timers whose history entry waits for their commands, so it tells whether they ran
+    unrecorded: Vec<usize>,
+    verdicts: Option<Sender<Verdict>>,
+    /// Bells still to ring and when the last one rang, spread out so each one is heard
+    bells: (u32, f64),
+    /// Indices of timers snoozed from their desktop notification, and the sender handed to them
+    snoozes: (Sender<usize>, Receiver<usize>),
+    /// Whether the terminal has focus, as far as it reports it
+    focused: bool,
+    language: Language,
+    term_badge: bool,
+    badge: String,
+    /// `--mqtt`, connected once on start
+    mqtt: Option<Mqtt>,
+    /// Reused for formatting on every tick
+    scratch: String,
+    recorder: Option<Recorder>,
+    replay: Option<VecDeque<Recorded>>,
+    settings_updates: Option<Receiver<Result<Settings, String>>>,
+    controls: Option<Receiver<Result<Control, String>>>,
+    status_requests: Option<Receiver<Sender<Vec<TimerStatus>>>>,
+    timer_waits: Option<Receiver<TimerWait>>,
+    /// Waits for timers that haven't finished yet
+    waiting: Vec<TimerWait>,
+    /// Whether completed timers go into the history
+    keep_history: bool,
+    /// Where the unfinished timers are saved for `endzeit resume`
+    session: Option<Session>,
+    /// Gets the pid of the background instance the timers were handed to with the detach key
+    detach: Option<Sender<u32>>,
+    handed_off: bool,
+    tick_rate: Duration,
+    ticker: bool,
+    /// `--format` of `--output line`
+    line_format: Template,
+    interactive: bool,
+    /// `--rpc`: what commands print goes out as events instead of into the output pane
+    rpc: bool,
+    /// stdout carries data that a program reads, so bells, escape sequences and the flash stay off it
+    stdout_is_data: bool,
+    keys: KeyBindings,
+    theme: Theme,
+}
+
+impl App {
+    /// Uses the theme's background for all gauges and its palette for timers added later.
+    pub fn new(mut countdowns: Vec<Countdown>, settings: Settings) -> Self {
+        for countdown in &mut countdowns {
+            countdown.background = settings.theme.background;
+        }
+        let thresholds = settings.thresholds();
+        Self {
+            countdowns,
+            selected: 0,
+            prompt: None,
+            confirm_quit: settings.confirm_quit,
+            confirming_quit: false,
+            show_help: false,
+            output_pane: None,
+            mouse: false,
+            area: Rect::default(),
+            max_retarget: settings.max_retarget,
+            exit_after: settings.exit_after,
+            finished_since: None,
+            retarget_command: settings.on_retarget,
+            cancel_command: settings.on_cancel,
+            term_notify: settings.term_notify,
+            escalation: settings.escalation,
+            push: settings.push,
+            desktop_notify: settings.notify,
+            desktop_notify_halfway: settings.notify_halfway,
+            notify_at: settings.notify_at.clone(),
+            milestones: settings.milestones.clone(),
+            before_end: settings.before_end.clone(),
+            thresholds,
+            bell_count: settings.bell_count,
+            flash: settings.flash,
+            flashing: None,
+            inverted: false,
+            alarm_every: settings.alarm_every,
+            snooze: settings.snooze,
+            ringing: None,
+            alarm: settings.alarm.clone(),
+            volume: settings.volume,
+            tick: settings.tick.unwrap_or(Tick::Second),
+            ticking: settings.tick.is_some(),
+            last_tick: None,
+            verification: settings.verification.clone(),
+            verifying: Vec::new(),
+            confirm_execute: settings.confirm_execute,
+            keep_awake: settings.keep_awake,
+            media: settings.media.clone(),
+            awake: None,
+            awake_failed: false,
+            dnd: settings.dnd,
+            do_not_disturb: None,
+            dnd_failed: false,
+            confirming_execute: None,
+            retry: settings.retry,
+            retrying: Vec::new(),
+            unrecorded: Vec::new(),
+            verdicts: None,
+            bells: (0, 0.0),
+            snoozes: mpsc::channel(),
+            focused: true,
+            language: settings.language,
+            term_badge: settings.term_badge,
+            mqtt: settings.mqtt.map(|(broker, topic)| Mqtt::connect(broker, topic)),
+            badge: String::new(),
+            scratch: String::new(),
+            recorder: None,
+            replay: None,
+            settings_updates: None,
+            controls: None,
+            status_requests: None,
+            timer_waits: None,
+            waiting: Vec::new(),
+            keep_history: false,
+            session: None,
+            detach: None,
+            handed_off: false,
+            tick_rate: settings.tick_rate,
+            ticker: settings.ticker,
+            line_format: settings.line_format,
+            interactive: true,
+            rpc: false,
+            stdout_is_data: false,
+            keys: settings.keys,
+            theme: settings.theme,
+        }
+    }
+
+    /// Handles mouse events, the terminal must have mouse capture enabled.
+    pub fn with_mouse(mut self) -> Self {
+        self.mouse = true;
+        self
+    }
+
+    /// Runs without reading input, for output modes that don't own the terminal.
+    pub fn without_input(mut self) -> Self {
+        self.interactive = false;
+        self
+    }
+
+    /// Applies settings arriving on `updates` while running, see `settings::watch`.
+    pub fn watch_settings(mut self, updates: Receiver<Result<Settings, String>>) -> Self {
+        self.settings_updates = Some(updates);
+        self
+    }
+
+    /// Applies commands from signals and the control FIFO to the selected timer.
+    pub fn control_with(mut self, controls: Receiver<Result<Control, String>>) -> Self {
+        self.controls = Some(controls);
+        self
+    }
+
+    /// Answers requests for the state of the timers, from `endzeit status`.
+    pub fn answer_status(mut self, requests: Receiver<Sender<Vec<TimerStatus>>>) -> Self {
+        self.status_requests = Some(requests);
+        self
+    }
+
+    /// Answers waits for a timer to finish, see `--after-timer`.
+    pub fn answer_waits(mut self, waits: Receiver<TimerWait>) -> Self {
+        self.timer_waits = Some(waits);
+        self
+    }
+
+    /// Adds every timer that completes to the history, see `endzeit history`.
+    pub fn keep_history(mut self) -> Self {
+        self.keep_history = true;
+        self
+    }
+
+    /// Keeps the unfinished timers in `session` while running, so they survive a crash.
+    pub fn persist_to(mut self, session: Session) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Enables the detach key, which hands the timers to a background instance and quits. Its pid is sent to `detached`.
+    pub fn detach_to(mut self, detached: Sender<u32>) -> Self {
+        self.detach = Some(detached);
+        self
+    }
+
+    /// Sends how the `--verify-cmd` checks went to `verdicts`, for the summary after the timers are gone.
+    pub fn report_verdicts_to(mut self, verdicts: Sender<Verdict>) -> Self {
+        self.verdicts = Some(verdicts);
+        self
+    }
+
+    pub fn countdowns(&self) -> &[Countdown] {
+        &self.countdowns
+    }
+
+    pub fn record_to(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Feeds recorded events into the app instead of live input, only `q` still works to stop the replay.
+    pub fn replay(mut self, events: Vec<Recorded>) -> Self {
+        self.replay = Some(events.into());
+        self
+    }
+
+    pub fn run(self, mut terminal: DefaultTerminal) -> Result<()> {
+        self.run_with(|app| {
+            let mut area = app.area;
+            terminal.draw(|frame| {
+                area = frame.area();
+                frame.render_widget(&*app, area);
+            })?;
+            app.area = area;
+            Ok(())
+        })
+    }
+
+    /// Prints the timers as a line of plain ASCII text whenever it changes instead of drawing them,
+    /// see `Output::Plain`.
+    pub fn run_plain(self) -> Result<()> {
+        let mut last_line = String::new();
+        self.run_with(|app| {
+            let line = app.plain_line();
+            if line != last_line {
+                println!("{}", line);
+                last_line = line;
+            }
+            Ok(())
+        })
+    }
+
+    /// Emits the timers as JSON-RPC notifications on stdout instead of showing them, see `rpc`: `tick`
+    /// with all timers whenever a second passes or one is paused or resumed, `finished` when one ends
+    /// or is cancelled, and `output` with what its commands printed.
+    pub fn run_rpc(mut self) -> Result<()> {
+        self.rpc = true;
+        self.keep_stdout_for_data();
+        let mut last_states = Vec::new();
+        let mut finished = Vec::new();
+        self.run_with(|app| {
+            let states: Vec<(u64, bool)> = app.countdowns.iter()
+                .map(|countdown| (countdown.get_remaining_time().seconds(), countdown.is_paused()))
+                .collect();
+            if states != last_states {
+                let timers: Vec<TimerStatus> = app.countdowns.iter().enumerate()
+                    .map(|(index, countdown)| TimerStatus::new(countdown, index, index == app.selected))
+                    .collect();
+                rpc::notify("tick", json!({ "timers": timers }));
+                last_states = states;
+            }
+            finished.resize(app.countdowns.len(), false);
+            for (index, countdown) in app.countdowns.iter().enumerate() {
+                if countdown.is_finished() && !finished[index] {
+                    rpc::notify("finished", json!({
+                        "name": countdown.display_name(index),
+                        "target": countdown.target(),
+                        "cancelled": countdown.is_cancelled(),
+                    }));
+                }
+                finished[index] = countdown.is_finished();
+            }
+            Ok(())
+        })
+    }
+
+    /// Prints the selected timer as the JSON object of a waybar custom module whenever it changes,
+    /// with all timers in the tooltip, see `Output::Waybar`.
+    pub fn run_waybar(mut self) -> Result<()> {
+        self.keep_stdout_for_data();
+        let mut last_line = String::new();
+        self.run_with(|app| {
+            let countdown = &app.countdowns[app.selected];
+            let (text, class) = if countdown.is_finished() || countdown.get_remaining_time().seconds() == 0 {
+                ("finished".to_string(), "finished")
+            } else if countdown.is_paused() {
+                (format!("{} paused", countdown.get_remaining_time().format()), "paused")
+            } else {
+                (countdown.get_remaining_time().format(), "running")
+            };
+            let tooltip = app.plain_line().replace(TICKER_SEPARATOR, "\n");
+            let line = json!({
+                "text": text,
+                "tooltip": tooltip,
+                "class": class,
+                "percentage": if class == "finished" { 100 } else { countdown.get_progress_percentage().clamp(0.0, 100.0) as u8 },
+            }).to_string();
+            if line != last_line {
+                println!("{}", line);
+                last_line = line;
+            }
+            Ok(())
+        })
+    }
+
+    /// `--output line`: prints the selected countdown after `--format` whenever the line changes.
+    pub fn run_line(mut self) -> Result<()> {
+        self.keep_stdout_for_data();
+        let mut last_line = String::new();
+        self.run_with(|app| {
+            let countdown = &app.countdowns[app.selected];
+            let remaining = countdown.get_remaining_time();
+            let finished = countdown.is_finished() || remaining.seconds() == 0;
+            let line = app.line_format.render_with(remaining.seconds(), &[
+                ("name", countdown.display_name(app.selected)),
+                ("remaining", if finished { "finished".to_string() } else { remaining.format() }),
+                ("percent", if finished { 100 } else { countdown.get_progress_percentage().clamp(0.0, 100.0) as u8 }.to_string()),
+                ("target", countdown.target().format("%Y-%m-%d %H:%M:%S").to_string()),
+            ]);
+            if line != last_line {
+                println!("{}", line);
+                last_line = line;
+            }
+            Ok(())
+        })
+    }
+
+    /// The run loop, `show` puts the timers on screen.
+    fn run_with(mut self, mut show: impl FnMut(&mut Self) -> Result<()>) -> Result<()> {
+        loop {
+            if self.replay.is_some() {
+                if self.apply_replay() {
+                    break;
+                }
+            } else {
+                self.poll_sources();
+            }
+            self.apply_settings_updates();
+            self.apply_controls();
+            self.apply_snoozes();
+            self.answer_status_requests();
+            self.update_flash();
+            show(&mut self)?;
+            self.check_milestones();
+            self.ask_to_execute();
+            self.hold_awake();
+            self.hold_dnd();
+            self.collect_verdicts();
+            self.collect_attempts();
+            self.repeat_alarm();
+            self.ring_bells();
+            self.play_tick();
+            self.answer_waits_for_timers();
+            if self.term_badge {
+                self.update_badge();
+            }
+            if let Some(mqtt) = &mut self.mqtt {
+                mqtt.publish(&self.countdowns[self.selected], &self.countdowns[self.selected].display_name(self.selected));
+            }
+            self.save_session();
+
+            if self.exit_due() || signals::terminated_by().is_some() {
+                break;
+            }
+
+            if !self.interactive {
+                thread::sleep(self.frame_interval());
+            } else if self.handle_events()? {
+                break;
+            }
+        }
+
+        if self.term_badge {
+            set_badge("", "");
+        }
+        if let Some(session) = self.session.take() {
+            session.remove();
+        }
+        // Timers handed to a background instance live on there
+        if !self.handed_off {
+            for index in 0..self.countdowns.len() {
+                if !self.countdowns[index].is_finished() {
+                    self.give_up(index);
+                }
+            }
+        }
+        // Commands still running would lose their output and retries, `--on-cancel` could be cut off
+        for countdown in &mut self.countdowns {
+            countdown.wait_for_hooks();
+        }
+        Ok(())
+    }
+
+    /// The ticker scrolls more smoothly than the countdowns tick.
+    fn frame_interval(&self) -> Duration {
+        if self.ticker {
+            self.tick_rate.min(TICKER_FRAME)
+        } else if self.flashing.is_some() {
+            self.tick_rate.min(FLASH_FRAME)
+        } else {
+            self.tick_rate
+        }
+    }
+
+    fn poll_sources(&mut self) {
+        for (index, countdown) in self.countdowns.iter_mut().enumerate() {
+            let Some(target) = countdown.source.poll() else {
+                continue;
+            };
+            let previous = countdown.target();
+            let Some(shift) = countdown.retarget(target, self.max_retarget) else {
+                continue;
+            };
+            if let Some(recorder) = &mut self.recorder {
+                recorder.retarget(index, target);
+            }
+            if let Some(command) = &self.retarget_command {
+                countdown.run_hook(command, HookResult::Retargeted { previous, shift });
+            }
+        }
+    }
+
+    fn apply_controls(&mut self) {
+        let Some(controls) = &self.controls else {
+            return;
+        };
+        let controls: Vec<Result<Control, String>> = controls.try_iter().collect();
+        for control in controls {
+            match control {
+                Ok(control) => {
+                    if let Some(recorder) = &mut self.recorder {
+                        recorder.control(control);
+                    }
+                    self.apply_control(control);
+                }
+                Err(err) => self.countdowns[self.selected].set_note(err),
+            }
+        }
+    }
+
+    /// Snoozes the timers whose notification button was clicked.
+    fn apply_snoozes(&mut self) {
+        let snoozed: Vec<usize> = self.snoozes.1.try_iter().collect();
+        for index in snoozed {
+            if index < self.countdowns.len() {
+                self.snooze(index);
+            }
+        }
+    }
+
+    /// Runs the timer `index` again for `--snooze`, which also ends an alarm about it.
+    fn snooze(&mut self, index: usize) {
+        if self.ringing.is_some_and(|(ringing, _)| ringing == index) {
+            self.ringing = None;
+        }
+        self.countdowns[index].snooze(TimeDelta::from_std(self.snooze).unwrap_or_default());
+    }
+
+    fn answer_status_requests(&self) {
+        let Some(requests) = &self.status_requests else {
+            return;
+        };
+        for reply in requests.try_iter() {
+            let timers = self.countdowns.iter().enumerate()
+                .map(|(index, countdown)| TimerStatus::new(countdown, index, index == self.selected))
+                .collect();
+            let _ = reply.send(timers);
+        }
+    }
+
+    fn apply_control(&mut self, control: Control) {
+        if control == Control::Dismiss {
+            self.dismiss();
+            return;
+        }
+        let countdown = &mut self.countdowns[self.selected];
+        match control {
+            Control::TogglePause => countdown.toggle_pause(),
+            Control::Pause if !countdown.is_paused() => countdown.toggle_pause(),
+            Control::Resume if countdown.is_paused() => countdown.toggle_pause(),
+            Control::Pause | Control::Resume => {}
+            Control::Adjust(delta) => countdown.adjust(delta),
+            Control::Cancel => {
+                let running = !countdown.is_finished();
+                countdown.cancel();
+                if running {
+                    self.give_up(self.selected);
+                }
+            }
+            Control::Dismiss => {}
+        }
+    }
+
+    /// Runs `--on-cancel` for an unfinished timer that is given up and adds it to the history.
+    fn give_up(&mut self, index: usize) {
+        metrics::count_cancellation();
+        if let Some(command) = &self.cancel_command {
+            self.countdowns[index].run_hook(command, HookResult::Cancelled);
+        }
+        if !self.keep_history {
+            return;
+        }
+        let countdown = &mut self.countdowns[index];
+        if let Err(err) = history::append(&Entry::new(countdown, true)) {
+            countdown.set_note(format!("not added to history: {}", err));
+        }
+    }
+
+    fn apply_settings_updates(&mut self) {
+        let Some(updates) = &self.settings_updates else {
+            return;
+        };
+        let Some(update) = updates.try_iter().last() else {
+            return;
+        };
+        let settings = match update {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.countdowns[self.selected].set_note(format!("config not reloaded: {}", err));
+                return;
+            }
+        };
+
+        for (index, countdown) in self.countdowns.iter_mut().enumerate() {
+            // Timers that took their color from the palette follow the new one, explicit colors stay
+            if countdown.color == self.theme.color(index) {
+                countdown.color = settings.theme.color(index);
+            }
+            countdown.background = settings.theme.background;
+        }
+        self.thresholds = settings.thresholds();
+        self.max_retarget = settings.max_retarget;
+        self.exit_after = settings.exit_after;
+        self.retarget_command = settings.on_retarget;
+        self.cancel_command = settings.on_cancel;
+        self.term_notify = settings.term_notify;
+        self.escalation = settings.escalation;
+        self.push = settings.push;
+        self.desktop_notify = settings.notify;
+        self.desktop_notify_halfway = settings.notify_halfway;
+        self.notify_at = settings.notify_at.clone();
+        self.milestones = settings.milestones.clone();
+        // Without input nobody could answer, see the check at startup
+        self.confirm_execute = settings.confirm_execute && self.interactive;
+        self.keep_awake = settings.keep_awake;
+        self.dnd = settings.dnd;
+        self.media = settings.media.clone();
+        self.retry = settings.retry;
+        self.before_end = settings.before_end.clone();
+        self.bell_count = settings.bell_count;
+        self.flash = settings.flash;
+        self.alarm_every = settings.alarm_every;
+        self.snooze = settings.snooze;
+        self.alarm = settings.alarm.clone();
+        self.volume = settings.volume;
+        self.tick = settings.tick.unwrap_or(self.tick);
+        self.ticking = settings.tick.is_some();
+        self.verification = settings.verification.clone();
+        self.language = settings.language;
+        if self.term_badge && !settings.term_badge {
+            set_badge("", "");
+            self.badge.clear();
+        }
+        self.term_badge = settings.term_badge;
+        self.tick_rate = settings.tick_rate;
+        self.confirm_quit = settings.confirm_quit;
+        self.ticker = settings.ticker;
+        self.line_format = settings.line_format;
+        self.keys = settings.keys;
+        self.theme = settings.theme;
+        if self.stdout_is_data {
+            self.keep_stdout_for_data();
+        }
+    }
+
+    /// Turns off what writes to the terminal through stdout: terminal notifications, the badge,
+    /// bells and the flash of the escalation ladder. For outputs whose stdout is read by a program.
+    fn keep_stdout_for_data(&mut self) {
+        self.stdout_is_data = true;
+        self.term_notify = None;
+        self.term_badge = false;
+        // No one sees a flash there, the ladder moves on to the next channel
+        self.focused = false;
+    }
+
+    /// Applies all recorded events that are due, returns true if the recording quit.
+    fn apply_replay(&mut self) -> bool {
+        let now = clock::seconds();
+        while let Some(event) = self.replay.as_mut().and_then(|events| events.pop_front_if(|event| event.time() <= now)) {
+            match event {
+                Recorded::Key { key, .. } => {
+                    if let Some(code) = parse_key(&key) {
+                        if self.handle_key(code) {
+                            return true;
+                        }
+                    }
+                }
+                Recorded::Retarget { index, target, .. } => {
+                    if let Some(countdown) = self.countdowns.get_mut(index) {
+                        countdown.retarget(target, None);
+                    }
+                }
+                Recorded::Control { command, .. } => {
+                    if let Ok(control) = Control::parse(&command) {
+                        self.apply_control(control);
+                    }
+                }
+                Recorded::Select { index, .. } => {
+                    if index < self.countdowns.len() {
+                        self.selected = index;
+                    }
+                }
+                Recorded::Start { .. } => {}
+            }
+        }
+        false
+    }
+
+    fn check_milestones(&mut self) {
+        for (index, countdown) in self.countdowns.iter_mut().enumerate() {
+            let passed = countdown.advance_progress();
+            let milestones = passed.map(|passed| countdown.pass_milestones(&self.milestones, passed)).unwrap_or_default();
+            if let Some(percent) = milestones.last() {
+                countdown.set_note(format!("{}% command ran", percent));
+            }
+            let halfway = countdown.reached_halfway();
+            let crossed = countdown.crossed_thresholds(&self.thresholds);
+            let ran = countdown.run_before_end(&self.before_end, &crossed);
+            if let Some(seconds) = ran.iter().min() {
+                countdown.set_note(format!("command {} before the end ran", format_seconds(*seconds)));
+            }
+            let threshold = crossed.iter().copied().filter(|threshold| self.notify_at.contains(threshold)).min();
+            countdown.confirm_execute = self.confirm_execute;
+            let finished = countdown.complete_if_finished();
+            let commands_done = countdown.collect_hooks();
+            for cue in self.media.iter().filter(|cue| cue.due(passed, finished)) {
+                if !cue.action.send() {
+                    countdown.set_note("--media needs playerctl".to_string());
+                }
+            }
+            for (command, output) in countdown.take_output() {
+                if self.rpc {
+                    rpc::notify("output", json!({ "name": countdown.display_name(index), "command": command, "output": output }));
+                } else {
+                    self.output_pane.get_or_insert_with(OutputPane::new).push(&countdown.display_name(index), &command, &output);
+                }
+            }
+            // The entry tells whether the commands ran, so it waits for them
+            let record = if finished && countdown.runs_completion() {
+                self.unrecorded.push(index);
+                false
+            } else if commands_done && self.unrecorded.contains(&index) {
+                self.unrecorded.retain(|unrecorded| *unrecorded != index);
+                true
+            } else {
+                finished
+            };
+            if record && self.keep_history {
+                if let Err(err) = history::append(&Entry::new(countdown, false)) {
+                    countdown.set_note(format!("not added to history: {}", err));
+                }
+            }
+            for hook in countdown.take_failed_hooks() {
+                if let Some(retry) = self.retry {
+                    self.retrying.push((index, retry.start(countdown.display_name(index), hook)));
+                }
+            }
+            if commands_done && countdown.hook_ran() {
+                if let Some(verification) = &self.verification {
+                    self.verifying.push((index, verification.start(countdown.display_name(index), countdown.launcher.clone())));
+                    countdown.set_note("verifying".to_string());
+                }
+            }
+
+            let message = if finished {
+                self.language.finished(&countdown.display_name(index))
+            } else if halfway {
+                let remaining = countdown.get_remaining_time().seconds();
+                self.language.halfway(&countdown.display_name(index), remaining)
+            } else if let Some(threshold) = threshold {
+                self.language.left(&countdown.display_name(index), threshold)
+            } else {
+                continue;
+            };
+            if let Some(term_notify) = self.term_notify {
+                term_notify.notify("endzeit", &message);
+            }
+            self.escalation.notify("endzeit", &message, self.focused);
+            if finished || threshold.is_some() {
+                for push in &self.push {
+                    push.send(countdown, &countdown.display_name(index), &message, finished);
+                }
+            }
+            if finished {
+                metrics::count_completion();
+                // An alarm needs to be heard, so it rings the bell at least once
+                self.bells.0 += if self.alarm_every.is_some() { self.bell_count.max(1) } else { self.bell_count };
+                if let Some(alarm) = &self.alarm {
+                    notify::play(alarm, self.volume);
+                }
+                if self.flash {
+                    self.flashing = Some(clock::seconds());
+                }
+                if self.alarm_every.is_some() {
+                    self.ringing = Some((index, clock::seconds()));
+                    countdown.set_note(ALARM_NOTE.to_string());
+                }
+            }
+            if finished && self.desktop_notify {
+                let overshoot = (clock::now() - countdown.target()).num_seconds();
+                desktop::finished(&countdown.display_name(index), overshoot, self.language, index, self.snooze, self.snoozes.0.clone());
+            } else if (halfway && self.desktop_notify_halfway) || (threshold.is_some() && self.desktop_notify) {
+                desktop::warning(&countdown.display_name(index), &message);
+            }
+        }
+    }
+
+    /// Alerts about the finished timer again once `--alarm-every` has passed, until it's dismissed.
+    fn repeat_alarm(&mut self) {
+        let (Some(every), Some((index, last))) = (self.alarm_every, self.ringing) else {
+            return;
+        };
+        if clock::seconds() - last < every.as_secs_f64() {
+            return;
+        }
+        let message = self.language.finished(&self.countdowns[index].display_name(index));
+        if let Some(term_notify) = self.term_notify {
+            term_notify.notify("endzeit", &message);
+        }
+        self.escalation.notify("endzeit", &message, self.focused);
+        self.bells.0 += self.bell_count.max(1);
+        if let Some(alarm) = &self.alarm {
+            notify::play(alarm, self.volume);
+        }
+        if self.flash {
+            self.flashing = Some(clock::seconds());
+        }
+        // Notes fade after a while, the alarm doesn't
+        self.countdowns[index].set_note(ALARM_NOTE.to_string());
+        self.ringing = Some((index, clock::seconds()));
+    }
+
+    /// Inverts the screen during every other half of a `--flash` period, and ends the flash once
+    /// it has blinked `FLASH_COUNT` times.
+    fn update_flash(&mut self) {
+        let Some(started) = self.flashing else {
+            self.inverted = false;
+            return;
+        };
+        let halves = ((clock::seconds() - started) / (FLASH_PERIOD / 2.0)) as u32;
+        if halves >= FLASH_COUNT * 2 {
+            self.flashing = None;
+        }
+        self.inverted = halves.is_multiple_of(2) && halves < FLASH_COUNT * 2;
+    }
+
+    /// Stops the alerts of `--alarm`.
+    fn dismiss(&mut self) {
+        if let Some((index, _)) = self.ringing.take() {
+            self.countdowns[index].clear_note();
+        }
+    }
+
+    /// Notes the verdicts of finished verifications on their timers and passes them on.
+    fn collect_verdicts(&mut self) {
+        let countdowns = &mut self.countdowns;
+        let verdicts = &self.verdicts;
+        self.verifying.retain(|(index, receiver)| {
+            let verdict = match receiver.try_recv() {
+                Ok(verdict) => verdict,
+                Err(mpsc::TryRecvError::Empty) => return true,
+                Err(mpsc::TryRecvError::Disconnected) => return false,
+            };
+            countdowns[*index].set_note(verdict.note());
+            if let Some(verdicts) = verdicts {
+                let _ = verdicts.send(verdict);
+            }
+            false
+        });
+    }
+
+    /// `--keep-awake`: holds the machine awake while a timer runs, and lets it go once all are done.
+    fn hold_awake(&mut self) {
+        if !self.keep_awake || self.is_finished() {
+            self.awake = None;
+            return;
+        }
+        if self.awake.is_some() || self.awake_failed {
+            return;
+        }
+        match KeepAwake::take() {
+            Ok(awake) => self.awake = Some(awake),
+            Err(err) => {
+                self.awake_failed = true;
+                self.countdowns[self.selected].set_note(err);
+            }
+        }
+    }
+
+    /// `--dnd`: Do Not Disturb while a timer runs, the way it was once all are finished or cancelled.
+    fn hold_dnd(&mut self) {
+        if !self.dnd || self.is_finished() {
+            self.do_not_disturb = None;
+            return;
+        }
+        if self.do_not_disturb.is_some() || self.dnd_failed {
+            return;
+        }
+        match DoNotDisturb::enable() {
+            Ok(do_not_disturb) => self.do_not_disturb = Some(do_not_disturb),
+            Err(err) => {
+                self.dnd_failed = true;
+                self.countdowns[self.selected].set_note(err);
+            }
+        }
+    }
+
+    /// `--confirm-execute`: asks about the commands of one finished timer at a time, and leaves them
+    /// out once nobody answered in time.
+    fn ask_to_execute(&mut self) {
+        match self.confirming_execute {
+            Some((index, _)) if !self.countdowns[index].awaits_confirmation() => self.confirming_execute = None,
+            Some((_, since)) if clock::seconds() - since >= CONFIRM_EXECUTE_SECONDS => self.answer_execute(false),
+            Some(_) => {}
+            None => {
+                if let Some(index) = self.countdowns.iter().position(Countdown::awaits_confirmation) {
+                    self.confirming_execute = Some((index, clock::seconds()));
+                    self.countdowns[index].set_note(format!("waiting for the commands to be confirmed, not run in {}", format_seconds(CONFIRM_EXECUTE_SECONDS as u64)));
+                }
+            }
+        }
+    }
+
+    /// Runs or drops the commands being asked about, the ones that ran get what a completion
+    /// without the question gets once they are done.
+    fn answer_execute(&mut self, run: bool) {
+        let Some((index, _)) = self.confirming_execute.take() else {
+            return;
+        };
+        self.countdowns[index].confirm_commands(run);
+    }
+
+    /// Notes the tries of failed commands on their gauges, as they come in.
+    fn collect_attempts(&mut self) {
+        let countdowns = &mut self.countdowns;
+        self.retrying.retain(|(index, receiver)| loop {
+            match receiver.try_recv() {
+                Ok(attempt) => countdowns[*index].set_note(attempt.note()),
+                Err(mpsc::TryRecvError::Empty) => break true,
+                Err(mpsc::TryRecvError::Disconnected) => break false,
+            }
+        });
+    }
+
+    /// Rings one of the pending bells if the last one was long enough ago. BEL goes out between frames
+    /// so it never lands inside an escape sequence, and isn't passed through tmux, which flags the window.
+    fn ring_bells(&mut self) {
+        let (pending, last) = &mut self.bells;
+        if self.stdout_is_data {
+            *pending = 0;
+        }
+        if *pending == 0 || clock::seconds() - *last < BELL_INTERVAL {
+            return;
+        }
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07");
+        let _ = stdout.flush();
+        *pending -= 1;
+        *last = clock::seconds();
+    }
+
+    /// Ticks whenever the selected timer's remaining time passes a full second or minute.
+    fn play_tick(&mut self) {
+        let countdown = &self.countdowns[self.selected];
+        if !self.ticking || countdown.is_paused() || countdown.is_finished() {
+            self.last_tick = None;
+            return;
+        }
+        let ticks = countdown.get_remaining_time().seconds() / self.tick.seconds();
+        // Not when another timer was just selected
+        if self.last_tick.is_some_and(|(index, last)| index == self.selected && last != ticks) {
+            notify::play(&Tick::SOUND, self.volume);
+        }
+        self.last_tick = Some((self.selected, ticks));
+    }
+
+    /// Replies to the waits whose timer has finished, and to those for timers that don't exist or were cancelled.
+    fn answer_waits_for_timers(&mut self) {
+        if let Some(waits) = &self.timer_waits {
+            self.waiting.extend(waits.try_iter());
+        }
+        let countdowns = &self.countdowns;
+        self.waiting.retain(|wait| {
+            let countdown = countdowns.iter().enumerate().find(|(index, countdown)| countdown.display_name(*index) == wait.name);
+            let reply = match countdown {
+                None => Err(format!("No timer named {}", wait.name)),
+                Some((_, countdown)) if countdown.is_cancelled() => Err(format!("{} was cancelled", wait.name)),
+                Some((_, countdown)) if countdown.is_finished() => Ok(()),
+                Some(_) => return true,
+            };
+            let _ = wait.reply.send(reply);
+            false
+        });
+    }
+
+    /// Stops saving after the first error, which is noted in the gauge.
+    fn save_session(&mut self) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+        if let Err(err) = session.save(&self.countdowns) {
+            self.countdowns[self.selected].set_note(format!("not saved for resume: {}", err));
+            self.session = None;
+        }
+    }
+
+    /// Mirrors the selected timer into the terminal badge, only writing when the text changes.
+    fn update_badge(&mut self) {
+        let countdown = &self.countdowns[self.selected];
+        countdown.get_remaining_time().write_to(&mut self.scratch);
+        if self.scratch != self.badge {
+            set_badge(&countdown.display_name(self.selected), &self.scratch);
+            std::mem::swap(&mut self.badge, &mut self.scratch);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.countdowns.iter().all(Countdown::is_finished)
+    }
+
+    /// True once everything has been finished for `exit_after`, a timer added meanwhile starts the wait over.
+    fn exit_due(&mut self) -> bool {
+        if !self.is_finished() {
+            self.finished_since = None;
+            return false;
+        }
+        let finished_since = *self.finished_since.get_or_insert_with(clock::seconds);
+        // The last bells, flashes, verdicts and retries would be lost on quitting, an alarm waits to be
+        // dismissed, the commands to be confirmed or to finish and their output to be read
+        if self.bells.0 > 0 || self.flashing.is_some() || !self.verifying.is_empty() || !self.retrying.is_empty() || self.ringing.is_some()
+            || self.output_pane.is_some() || self.countdowns.iter().any(|countdown| countdown.awaits_confirmation() || countdown.runs_hooks()) {
+            return false;
+        }
+        clock::seconds() - finished_since >= self.exit_after.num_milliseconds() as f64 / 1000.0
+    }
+
+    /// Waits up to one tick for input and handles everything pending, returns true if the user wants to quit.
+    fn handle_events(&mut self) -> Result<bool> {
+        let mut timeout = self.frame_interval();
+        while event::poll(timeout)? {
+            timeout = Duration::ZERO;
+            let quit = match event::read()? {
+                Event::Key(key) if self.replay.is_some() => key.code == self.keys.quit,
+                Event::Key(key) => self.handle_key(key.code),
+                Event::Mouse(mouse) if self.replay.is_none() => self.handle_mouse(mouse),
+                Event::FocusGained => {
+                    self.focused = true;
+                    false
+                }
+                Event::FocusLost => {
+                    self.focused = false;
+                    false
+                }
+                _ => false,
+            };
+            if quit {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Clicking a gauge pauses or resumes it, scrolling adds or subtracts a minute and the close button quits.
+    /// Mouse actions go through the key handler so they are recorded and replayed like keys.
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> bool {
+        if !self.mouse || self.prompt.is_some() || self.show_help || self.output_pane.is_some() || self.confirming_quit
+            || self.confirming_execute.is_some() {
+            return false;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let clicked = mouse.kind == MouseEventKind::Down(MouseButton::Left);
+        if clicked && Self::close_button(self.area).contains(position) {
+            return self.handle_key(self.keys.quit);
+        }
+
+        let Some(index) = self.gauge_areas(self.area).into_iter()
+            .find(|(_, area)| area.contains(position))
+            .map(|(index, _)| index) else {
+            return false;
+        };
+        let key = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.keys.pause,
+            MouseEventKind::ScrollUp => self.keys.add_time,
+            MouseEventKind::ScrollDown => self.keys.subtract_time,
+            _ => return false,
+        };
+        if index != self.selected {
+            self.selected = index;
+            if let Some(recorder) = &mut self.recorder {
+                recorder.select(index);
+            }
+        }
+        self.handle_key(key)
+    }
+
+    fn close_button(area: Rect) -> Rect {
+        let width = (CLOSE_BUTTON.len() as u16).min(area.width);
+        Rect::new(area.right() - width, area.y, width, 1.min(area.height))
+    }
+
+    /// Handles a single key press, returns true if it quits.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.key(code);
+        }
+        if self.prompt.is_some() {
+            self.handle_prompt_key(code);
+            return false;
+        }
+        // Only y runs them, like the capital N of [y/N] says
+        if self.confirming_execute.is_some() {
+            self.answer_execute(code == KeyCode::Char('y'));
+            return false;
+        }
+        if self.confirming_quit {
+            self.confirming_quit = false;
+            return code == KeyCode::Char('y');
+        }
+        // Any key but snooze stops the alarm
+        if let Some((index, _)) = self.ringing {
+            if code == self.keys.snooze {
+                self.snooze(index);
+            } else {
+                self.dismiss();
+            }
+            return false;
+        }
+        if let Some(pane) = &mut self.output_pane {
+            if !pane.handle_key(code) {
+                self.output_pane = None;
+            }
+            return false;
+        }
+        // Any key closes the help
+        if self.show_help {
+            self.show_help = false;
+            return false;
+        }
+        let keys = &self.keys;
+        if code == keys.quit {
+            // Only ask while there is still something to lose
+            if self.confirm_quit && !self.is_finished() {
+                self.confirming_quit = true;
+                return false;
+            }
+            return true;
+        } else if code == keys.next || code == KeyCode::Right || code == KeyCode::Down {
+            self.select_next();
+        } else if code == keys.previous || code == KeyCode::Left || code == KeyCode::Up {
+            self.select_previous();
+        } else if code == keys.new_timer {
+            self.prompt = Some((PromptKind::NewTimer, Prompt::new("New timer (WHEN[,name=..][,color=..][,execute=..])", "")));
+        } else if code == keys.edit_target {
+            let target = self.countdowns[self.selected].target().format("%Y-%m-%d %H:%M:%S").to_string();
+            self.prompt = Some((PromptKind::EditTarget, Prompt::new("Edit target (YYYY-MM-DD HH:MM:SS)", &target)));
+        } else if code == keys.pause {
+            self.countdowns[self.selected].toggle_pause();
+        } else if code == keys.help {
+            self.show_help = true;
+        } else if code == keys.skip {
+            self.countdowns[self.selected].skip_to_end();
+        } else if code == keys.restart {
+            self.countdowns[self.selected].restart();
+        } else if code == keys.add_time {
+            self.countdowns[self.selected].adjust(SMALL_STEP);
+        } else if code == keys.subtract_time {
+            self.countdowns[self.selected].adjust(-SMALL_STEP);
+        } else if code == keys.add_time_big {
+            self.countdowns[self.selected].adjust(BIG_STEP);
+        } else if code == keys.subtract_time_big {
+            self.countdowns[self.selected].adjust(-BIG_STEP);
+        } else if code == keys.snooze && self.countdowns[self.selected].is_finished() {
+            self.snooze(self.selected);
+        } else if code == keys.tick {
+            self.ticking = !self.ticking;
+        } else if code == keys.detach && self.detach.is_some() {
+            return self.hand_off();
+        } else if code == keys.edit_name {
+            let name = self.countdowns[self.selected].name.clone().unwrap_or_default();
+            self.prompt = Some((PromptKind::EditName, Prompt::new("Name (empty to remove)", &name)));
+        }
+        false
+    }
+
+    /// Starts a background instance with the unfinished timers, returns true if the UI can go.
+    fn hand_off(&mut self) -> bool {
+        if self.countdowns.iter().any(Countdown::is_paused) {
+            self.countdowns[self.selected].set_note("resume paused timers before detaching".to_string());
+            return false;
+        }
+        if self.confirm_execute {
+            self.countdowns[self.selected].set_note("--confirm-execute can only ask here, not in the background".to_string());
+            return false;
+        }
+        let unfinished: Vec<&Countdown> = self.countdowns.iter()
+            .filter(|countdown| !countdown.is_finished())
+            .collect();
+        if unfinished.is_empty() {
+            return true;
+        }
+        #[cfg(unix)]
+        let result = crate::daemon::hand_off(&unfinished);
+        #[cfg(not(unix))]
+        let result: Result<u32, String> = Err("Detaching needs Unix".to_string());
+        match result {
+            Ok(pid) => {
+                if let Some(detached) = &self.detach {
+                    let _ = detached.send(pid);
+                }
+                self.handed_off = true;
+                true
+            }
+            Err(err) => {
+                self.countdowns[self.selected].set_note(err);
+                false
+            }
+        }
+    }
+
+    fn handle_prompt_key(&mut self, code: KeyCode) {
+        let Some((kind, prompt)) = &mut self.prompt else {
+            return;
+        };
+        let result = match (prompt.handle_key(code), kind) {
+            (PromptAction::None, _) => return,
+            (PromptAction::Cancel, _) => Ok(()),
+            (PromptAction::Submit(spec), PromptKind::NewTimer) => self.add_timer(&spec),
+            (PromptAction::Submit(target), PromptKind::EditTarget) => self.edit_target(&target),
+            (PromptAction::Submit(name), PromptKind::EditName) => {
+                let name = name.trim();
+                self.countdowns[self.selected].name = (!name.is_empty()).then(|| name.to_string());
+                Ok(())
+            }
+        };
+        match result {
+            Ok(()) => self.prompt = None,
+            Err(err) => {
+                if let Some((_, prompt)) = &mut self.prompt {
+                    prompt.set_error(err);
+                }
+            }
+        }
+    }
+
+    fn add_timer(&mut self, spec: &str) -> Result<(), String> {
+        let target_spec = parse_target_spec(spec)?;
+        if target_spec.target <= clock::now() {
+            return Err("Target date/time must be in the future".to_string());
+        }
+        let mut countdown = Countdown::from_spec(target_spec, self.theme.color(self.countdowns.len()));
+        countdown.background = self.theme.background;
+        self.countdowns.push(countdown);
+        self.selected = self.countdowns.len() - 1;
+        Ok(())
+    }
+
+    fn edit_target(&mut self, input: &str) -> Result<(), String> {
+        let target = parse_datetime(input)?;
+        if target <= clock::now() {
+            return Err("Target date/time must be in the future".to_string());
+        }
+        self.countdowns[self.selected].retarget(target, None);
+        Ok(())
+    }
+
+    fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.countdowns.len();
+    }
+
+    fn select_previous(&mut self) {
+        self.selected = (self.selected + self.countdowns.len() - 1) % self.countdowns.len();
+    }
+
+    fn is_stacked(&self, area: Rect) -> bool {
+        self.countdowns.len() as u16 * MIN_GAUGE_HEIGHT <= area.height
+    }
+
+    /// Where each visible timer is drawn, for rendering and for finding the timer under the mouse.
+    fn gauge_areas(&self, area: Rect) -> Vec<(usize, Rect)> {
+        if self.ticker {
+            return Vec::new();
+        }
+        if self.is_stacked(area) {
+            let constraints = self.countdowns.iter().map(|_| Constraint::Fill(1));
+            return Layout::vertical(constraints).split(area).iter().copied().enumerate().collect();
+        }
+
+        // Tabbed: the selected timer large below the tab bar, the others as one-line gauges underneath
+        let others = (0..self.countdowns.len()).filter(|index| *index != self.selected);
+        let compact_rows = (others.clone().count() as u16).min(area.height.saturating_sub(1) / 2);
+        let [_, selected_area, compact_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(compact_rows),
+        ]).areas(area);
+        let rows = Layout::vertical(vec![Constraint::Length(1); compact_rows as usize]).split(compact_area);
+
+        let mut areas = vec![(self.selected, selected_area)];
+        areas.extend(others.zip(rows.iter().copied()));
+        areas
+    }
+
+    fn render_stacked(&self, area: Rect, buf: &mut Buffer) {
+        for (index, area) in self.gauge_areas(area) {
+            self.countdowns[index].render(area, buf);
+            // Mark the timer that keys like `e` act on
+            if index == self.selected && self.countdowns.len() > 1 {
+                buf.set_string(area.x, area.y, "▶", Style::default().add_modifier(Modifier::BOLD));
+            }
+        }
+    }
+
+    /// Shows the selected timer large below a tab bar, with the others as one-line gauges underneath.
+    fn render_tabbed(&self, area: Rect, buf: &mut Buffer) {
+        let titles = self.countdowns.iter().enumerate().map(|(index, countdown)| countdown.display_name(index));
+        Tabs::new(titles)
+            .select(self.selected)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .render(Rect { height: 1.min(area.height), ..area }, buf);
+
+        for (index, area) in self.gauge_areas(area) {
+            self.countdowns[index].render(area, buf);
+        }
+    }
+
+    /// Lists the key bindings and the details of the selected timer in a popup.
+    fn render_help(&self, area: Rect, buf: &mut Buffer) {
+        let heading = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![Line::styled("Keys", heading)];
+        for (code, action) in self.keys.describe() {
+            let key = key_name(code).unwrap_or_default();
+            lines.push(Line::from(format!("  {:<10}{}", key, action)));
+        }
+
+        let countdown = &self.countdowns[self.selected];
+        let state = if countdown.is_finished() {
+            "finished"
+        } else if countdown.is_paused() {
+            "paused"
+        } else {
+            "running"
+        };
+        let source = match &countdown.source {
+            TargetSource::Fixed => "fixed".to_string(),
+            TargetSource::File { path, .. } => format!("file {}", path.display()),
+        };
+        let commands = countdown.command_lines();
+        let details = [
+            ("Name", countdown.display_name(self.selected)),
+            ("Target", countdown.target().format("%Y-%m-%d %H:%M:%S").to_string()),
+            ("Remaining", countdown.get_remaining_time().format()),
+            ("Progress", format!("{:.0}%", countdown.get_progress_percentage())),
+            ("State", state.to_string()),
+            ("Source", source),
+            ("Execute", if commands.is_empty() { "-".to_string() } else { commands.join("; ") }),
+        ];
+        lines.push(Line::default());
+        lines.push(Line::styled("Selected timer", heading));
+        for (label, value) in details {
+            lines.push(Line::from(format!("  {:<10}{}", label, value)));
+        }
+
+        let popup = centered(area, 60, lines.len() as u16 + 2);
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Help"))
+            .render(popup, buf);
+    }
+
+    /// All timers on one line without colors or symbols, like `Tea: 2m 10s | Focus: 20m 0s PAUSED`.
+    fn plain_line(&self) -> String {
+        let timers: Vec<String> = self.countdowns.iter().enumerate()
+            .map(|(index, countdown)| {
+                let state = if countdown.is_finished() || countdown.get_remaining_time().seconds() == 0 {
+                    "finished".to_string()
+                } else if countdown.is_paused() {
+                    format!("{} PAUSED", countdown.get_remaining_time().format())
+                } else {
+                    countdown.get_remaining_time().format()
+                };
+                format!("{}: {}", countdown.display_name(index), state)
+            })
+            .collect();
+        timers.join(TICKER_SEPARATOR)
+    }
+
+    /// Shows the running timers as `name: remaining | name: remaining` on one line,
+    /// scrolling around in a loop when the line is wider than the area.
+    fn render_ticker(&self, area: Rect, buf: &mut Buffer) {
+        let separator = Style::default().add_modifier(Modifier::DIM);
+        let mut line: Vec<(char, Style)> = Vec::new();
+        for (index, countdown) in self.countdowns.iter().enumerate().filter(|(_, countdown)| !countdown.is_finished()) {
+            if !line.is_empty() {
+                line.extend(TICKER_SEPARATOR.chars().map(|c| (c, separator)));
+            }
+            let style = Style::default().fg(countdown.color);
+            let mut text = format!("{}: {}", countdown.display_name(index), countdown.get_remaining_time().format());
+            if countdown.is_paused() {
+                text.push_str(" PAUSED");
+            }
+            line.extend(text.chars().map(|c| (c, style)));
+        }
+
+        let width = area.width as usize;
+        let offset = if line.len() > width {
+            line.extend(TICKER_SEPARATOR.chars().map(|c| (c, separator)));
+            (clock::seconds() * TICKER_SPEED) as usize % line.len()
+        } else {
+            0
+        };
+        let visible = line.iter().cycle().skip(offset).take(width.min(line.len()));
+        for (x, (c, style)) in (area.x..).zip(visible) {
+            buf[(x, area.y)].set_char(*c).set_style(*style);
+        }
+    }
+}
+
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.ticker {
+            self.render_ticker(area, buf);
+        } else if self.is_stacked(area) {
+            self.render_stacked(area, buf);
+        } else {
+            self.render_tabbed(area, buf);
+        }
+        if let Some((_, prompt)) = &self.prompt {
+            prompt.render(area, buf);
+        }
+        if self.mouse {
+            let close_button = App::close_button(area);
+            buf.set_stringn(close_button.x, close_button.y, CLOSE_BUTTON, close_button.width as usize, Style::default().add_modifier(Modifier::BOLD));
+        }
+        if let Some(pane) = &self.output_pane {
+            pane.render(area, buf);
+        }
+        if self.show_help {
+            self.render_help(area, buf);
+        }
+        if self.confirming_quit {
+            Confirm("Really abort? y/n").render(area, buf);
+        }
+        if let Some((index, since)) = self.confirming_execute {
+            let left = (CONFIRM_EXECUTE_SECONDS - (clock::seconds() - since)).ceil().max(0.0);
+            let question = format!("Run `{}`? [y/N] {}s", self.countdowns[index].command_lines().join("; "), left);
+            Confirm(&question).render(area, buf);
+        }
+        if self.inverted {
+            buf.set_style(area, Style::default().add_modifier(Modifier::REVERSED));
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+use chrono::TimeDelta;
+use clap::ValueEnum;
+use color_eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{
+        self,
+        Event,
+        KeyCode,
+        KeyModifiers
+    },
+    layout::{
+        Constraint,
+        Layout,
+        Rect
+    },
+    text::Line,
+    widgets::Widget,
+    TerminalOptions,
+    Viewport,
+};
+
+use crate::clock;
+use crate::countdown::Countdown;
+use crate::settings::Settings;
+
+/// Rows of the prompt: the question, the gauge and the key hint.
+const HEIGHT: u16 = 4;
+/// How often the gauge is redrawn while waiting for an answer.
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+/// Exit code when the time runs out without a default answer.
+const EXIT_TIMEOUT: i32 = 2;
+/// Exit code for Ctrl+C, like a shell reports SIGINT.
+const EXIT_INTERRUPTED: i32 = 130;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Answer {
+    Yes,
+    No,
+}
+
+impl Answer {
+    fn name(self) -> &'static str {
+        match self {
+            Answer::Yes => "yes",
+            Answer::No => "no",
+        }
+    }
+
+    fn exit_code(self) -> i32 {
+        match self {
+            Answer::Yes => 0,
+            Answer::No => 1,
+        }
+    }
+}
+
+enum Outcome {
+    Answered(Answer),
+    /// The time ran out, the default is taken if there is one
+    TimedOut(Option<Answer>),
+    Interrupted,
+}
+
+impl Outcome {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Outcome::Answered(answer) | Outcome::TimedOut(Some(answer)) => answer.exit_code(),
+            Outcome::TimedOut(None) => EXIT_TIMEOUT,
+            Outcome::Interrupted => EXIT_INTERRUPTED,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Outcome::Answered(answer) => answer.name().to_string(),
+            Outcome::TimedOut(Some(answer)) => format!("no answer in time, {}", answer.name()),
+            Outcome::TimedOut(None) => "no answer in time".to_string(),
+            Outcome::Interrupted => "interrupted".to_string(),
+        }
+    }
+}
+
+/// The question with its countdown and what answering does.
+struct Ask<'a> {
+    countdown: &'a Countdown,
+    hint: String,
+}
+
+impl Widget for &Ask<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [countdown_area, hint_area] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        self.countdown.render(countdown_area, buf);
+        Line::from(self.hint.as_str()).centered().render(hint_area, buf);
+    }
+}
+
+/// Asks a yes/no question below a countdown of `timeout` and exits with 0 for yes, 1 for no and
+/// 2 if the time ran out without a `default`, which is otherwise taken as the answer.
+pub fn run(question: &str, timeout: TimeDelta, default: Option<Answer>, settings: Settings) -> Result<()> {
+    let countdown = Countdown::new(clock::now() + timeout, Some(question.to_string()), settings.theme.color(0), Vec::new());
+    let choices = match default {
+        Some(Answer::Yes) => "[Y/n], Enter for yes",
+        Some(Answer::No) => "[y/N], Enter for no",
+        None => "[y/n]",
+    };
+    let mut ask = Ask { countdown: &countdown, hint: choices.to_string() };
+
+    let mut terminal = ratatui::init_with_options(TerminalOptions { viewport: Viewport::Inline(HEIGHT) });
+    let outcome = loop {
+        terminal.draw(|frame| frame.render_widget(&ask, frame.area()))?;
+        if countdown.is_finished() {
+            break Outcome::TimedOut(default);
+        }
+        if !event::poll(FRAME_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        let answer = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break Outcome::Interrupted,
+            KeyCode::Char('y' | 'Y') => Answer::Yes,
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => Answer::No,
+            KeyCode::Enter => match default {
+                Some(default) => default,
+                None => continue,
+            },
+            _ => continue,
+        };
+        break Outcome::Answered(answer);
+    };
+
+    // Leave the outcome on screen
+    ask.hint = outcome.describe();
+    terminal.draw(|frame| frame.render_widget(&ask, frame.area()))?;
+    ratatui::restore();
+    println!();
+    std::process::exit(outcome.exit_code());
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+use color_eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{
+        self,
+        Event,
+        KeyCode
+    },
+    layout::{
+        Constraint,
+        Layout,
+        Rect
+    },
+    style::{
+        Modifier,
+        Style
+    },
+    text::Line,
+    widgets::Widget,
+};
+
+use crate::control::TimerStatus;
+use crate::countdown::Countdown;
+use crate::exit_with;
+use crate::keys::{
+    key_name,
+    KeyBindings
+};
+use crate::settings::Settings;
+use crate::socket;
+use crate::theme::Theme;
+
+/// How often the timers are fetched from the instance and redrawn.
+const FRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The timers of another instance, as of the last status answer.
+struct Attached {
+    countdowns: Vec<Countdown>,
+    selected: usize,
+    hint: String,
+}
+
+impl Attached {
+    fn update(&mut self, timers: &[TimerStatus], theme: &Theme) {
+        self.countdowns = timers.iter().enumerate()
+            .map(|(index, timer)| Countdown::from_status(timer, theme.color(index)))
+            .collect();
+        self.selected = timers.iter().position(|timer| timer.selected).unwrap_or(0);
+    }
+}
+
+impl Widget for &Attached {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [gauges_area, hint_area] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let areas = Layout::vertical(self.countdowns.iter().map(|_| Constraint::Length(3))).split(gauges_area);
+        for (index, (countdown, area)) in self.countdowns.iter().zip(areas.iter()).enumerate() {
+            countdown.render(*area, buf);
+            // Commands act on the selected timer of the instance
+            if index == self.selected && self.countdowns.len() > 1 {
+                buf.set_string(area.x, area.y, "▶", Style::default().add_modifier(Modifier::BOLD));
+            }
+        }
+        Line::from(self.hint.as_str()).centered().render(hint_area, buf);
+    }
+}
+
+/// `endzeit attach`: shows the timers of the background instance running the timer `name`, or the
+/// one with that pid. Pause and the add/subtract keys are sent to it, the detach key leaves it running.
+pub fn run(name: &str, settings: Settings) -> Result<()> {
+    let pid = socket::find(name).unwrap_or_else(|| exit_with(&format!("No running timer named {}", name)));
+    let keys = &settings.keys;
+    let hint = format!(
+        "{} pause   {} {} add/subtract a minute   {} detach",
+        key_name(keys.pause).unwrap_or_default(),
+        key_name(keys.add_time).unwrap_or_default(),
+        key_name(keys.subtract_time).unwrap_or_default(),
+        key_name(keys.detach).unwrap_or_default(),
+    );
+    let mut attached = Attached { countdowns: Vec::new(), selected: 0, hint };
+
+    let mut terminal = ratatui::init();
+    let farewell = loop {
+        match socket::timers(pid) {
+            Ok(timers) => attached.update(&timers, &settings.theme),
+            // The instance exits once its timers are done
+            Err(_) => break format!("endzeit {} has finished", pid),
+        }
+        terminal.draw(|frame| frame.render_widget(&attached, frame.area()))?;
+        if !event::poll(FRAME_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.code == keys.detach || key.code == keys.quit {
+            break format!("Detached from endzeit {}, attach again with `endzeit attach {}`", pid, pid);
+        }
+        let Some(command) = command(keys, key.code) else {
+            continue;
+        };
+        if let Err(err) = socket::request(pid, command) {
+            attached.hint = err;
+        }
+    };
+    ratatui::restore();
+    println!("{}", farewell);
+    Ok(())
+}
+
+/// The control command for a key, see `Control::parse`.
+fn command(keys: &KeyBindings, code: KeyCode) -> Option<&'static str> {
+    if code == keys.pause {
+        Some("toggle")
+    } else if code == keys.add_time {
+        Some("add 1m")
+    } else if code == keys.subtract_time {
+        Some("subtract 1m")
+    } else if code == keys.add_time_big {
+        Some("add 5m")
+    } else if code == keys.subtract_time_big {
+        Some("subtract 5m")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs::File;
+use std::io::{
+    BufReader,
+    Cursor
+};
+use std::path::Path;
+use std::sync::mpsc::{
+    self,
+    Sender
+};
+use std::sync::OnceLock;
+use std::thread;
+use rodio::{
+    Decoder,
+    OutputStream,
+    OutputStreamHandle,
+    Sink,
+    Source
+};
+
+use crate::notify::{
+    self,
+    Sound
+};
+
+type Decoded = Box<dyn Source<Item = i16> + Send>;
+
+/// A sound for the output thread, at a volume in percent, with where to say whether it started.
+type Request = (Decoded, u8, Sender<Result<(), String>>);
+
+/// The output thread shared by the alarm, the escalation and the ticks.
+static OUTPUT: OnceLock<Sender<Request>> = OnceLock::new();
+
+/// Plays `sound` on the default output device at `volume` percent. Returns once it started
+/// playing, or with the reason it can't.
+pub fn play(sound: &Sound, volume: u8) -> Result<(), String> {
+    let source = decode(sound)?;
+    let (started, result) = mpsc::channel();
+    output().send((source, volume, started)).map_err(|_| "Audio output stopped".to_string())?;
+    result.recv().map_err(|_| "Audio output stopped".to_string())?
+}
+
+/// Starts the thread that owns the output stream on first use. The stream can't move between
+/// threads on every platform and goes silent when dropped, so it stays there. Sounds overlap,
+/// each gets its own sink.
+fn output() -> &'static Sender<Request> {
+    OUTPUT.get_or_init(|| {
+        let (sender, requests) = mpsc::channel::<Request>();
+        thread::spawn(move || {
+            let mut stream: Option<(OutputStream, OutputStreamHandle)> = None;
+            for (source, volume, started) in requests {
+                // Tried again for every sound, a headset may have been plugged in since
+                if stream.is_none() {
+                    stream = OutputStream::try_default().ok();
+                }
+                let result = match &stream {
+                    Some((_, handle)) => Sink::try_new(handle).map_err(|e| format!("No audio output: {}", e)).map(|sink| {
+                        sink.set_volume(volume as f32 / 100.0);
+                        sink.append(source);
+                        sink.detach();
+                    }),
+                    None => Err("No audio output".to_string()),
+                };
+                let _ = started.send(result);
+            }
+        });
+        sender
+    })
+}
+
+fn decode(sound: &Sound) -> Result<Decoded, String> {
+    match sound {
+        // The system sound may be in a format rodio can't read, like AIFF on macOS
+        Sound::System => match notify::system_sounds().find_map(|file| decode_file(file).ok()) {
+            Some(source) => Ok(source),
+            None => decode(&Sound::Builtin(notify::DEFAULT_SOUND)),
+        },
+        Sound::Builtin(name) => {
+            let bytes = notify::builtin_sound(name).ok_or_else(|| format!("Unknown sound builtin:{}", name))?;
+            Ok(Box::new(Decoder::new(Cursor::new(bytes)).map_err(|e| format!("Cannot play builtin:{}: {}", name, e))?))
+        }
+        Sound::File(file) => decode_file(file),
+    }
+}
+
+fn decode_file(file: &Path) -> Result<Decoded, String> {
+    let reader = File::open(file).map_err(|e| format!("Failed to open {}: {}", file.display(), e))?;
+    Ok(Box::new(Decoder::new(BufReader::new(reader)).map_err(|e| format!("Cannot play {}: {}", file.display(), e))?))
+}
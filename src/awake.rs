@@ -0,0 +1,116 @@
+#[cfg(unix)]
+use std::process::{
+    Child,
+    Command,
+    Stdio
+};
+#[cfg(unix)]
+use std::thread;
+#[cfg(unix)]
+use std::time::Duration;
+
+#[cfg(unix)]
+use crate::exec::find_program;
+
+/// Why the machine stays awake, as listed by `systemd-inhibit --list`.
+#[cfg(unix)]
+const WHY: &str = "Counting down";
+/// How long a helper gets to fail before its lock counts as taken.
+#[cfg(unix)]
+const STARTUP: Duration = Duration::from_millis(100);
+
+#[cfg(windows)]
+const ES_CONTINUOUS: u32 = 0x8000_0000;
+#[cfg(windows)]
+const ES_SYSTEM_REQUIRED: u32 = 0x0000_0001;
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn SetThreadExecutionState(flags: u32) -> u32;
+}
+
+/// `--keep-awake`: keeps the machine from going to sleep while it's held, so the completion
+/// commands get to run. Released on drop.
+pub struct KeepAwake {
+    _lock: Lock,
+}
+
+enum Lock {
+    /// A lock of logind, straight from D-Bus
+    #[cfg(all(feature = "dbus", target_os = "linux"))]
+    Logind { _inhibitor: crate::dbus::Inhibitor },
+    /// systemd-inhibit or caffeinate, which hold the lock until they are killed
+    #[cfg(unix)]
+    Helper(Child),
+    /// The execution state of the thread, which has to be the one that drops it
+    #[cfg(windows)]
+    ExecutionState,
+}
+
+impl KeepAwake {
+    /// Takes the lock the platform offers: with the dbus feature from logind directly, else through
+    /// systemd-inhibit on Linux and the BSDs, caffeinate on macOS and SetThreadExecutionState on Windows.
+    pub fn take() -> Result<Self, String> {
+        #[cfg(all(feature = "dbus", target_os = "linux"))]
+        if let Ok(inhibitor) = crate::dbus::Inhibitor::take(WHY) {
+            return Ok(Self { _lock: Lock::Logind { _inhibitor: inhibitor } });
+        }
+        #[cfg(unix)]
+        let pid = std::process::id().to_string();
+        #[cfg(target_os = "macos")]
+        return helper(Command::new("caffeinate").args(["-i", "-w", &pid]));
+        // The helper quits along with endzeit, even when endzeit is killed and can't stop it
+        #[cfg(all(unix, not(target_os = "macos")))]
+        return helper(Command::new("systemd-inhibit").args([
+            "--what=sleep:idle",
+            "--who=endzeit",
+            &format!("--why={}", WHY),
+            "--mode=block",
+            "sh",
+            "-c",
+            &format!("while kill -0 {} 2>/dev/null; do sleep 5; done", pid),
+        ]));
+        #[cfg(windows)]
+        {
+            if unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) } == 0 {
+                return Err("Windows refused to keep the machine awake".to_string());
+            }
+            Ok(Self { _lock: Lock::ExecutionState })
+        }
+    }
+}
+
+/// Starts the helper holding the lock, and makes sure it didn't fail right away.
+#[cfg(unix)]
+fn helper(command: &mut Command) -> Result<KeepAwake, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    if !find_program(&program) {
+        return Err(format!("--keep-awake needs {}", program));
+    }
+    let mut child = command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+    thread::sleep(STARTUP);
+    if let Ok(Some(status)) = child.try_wait() {
+        return Err(format!("{} couldn't keep the machine awake ({})", program, status));
+    }
+    Ok(KeepAwake { _lock: Lock::Helper(child) })
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        match self {
+            #[cfg(all(feature = "dbus", target_os = "linux"))]
+            Lock::Logind { .. } => {}
+            #[cfg(unix)]
+            Lock::Helper(child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            #[cfg(windows)]
+            Lock::ExecutionState => unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS);
+            },
+        }
+    }
+}
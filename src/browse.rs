@@ -0,0 +1,189 @@
+use color_eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{
+        self,
+        Event,
+        KeyCode
+    },
+    layout::{
+        Constraint,
+        Layout,
+        Rect
+    },
+    style::{
+        Color,
+        Modifier,
+        Style
+    },
+    text::Line,
+    widgets::{
+        Block,
+        Row,
+        StatefulWidget,
+        Table,
+        TableState,
+        Widget
+    },
+};
+
+use crate::history::{
+    self,
+    Entry,
+    HistoryFilter
+};
+use crate::prompt::{
+    Prompt,
+    PromptAction
+};
+use crate::units::format_seconds;
+
+/// Rows moved by PageUp and PageDown.
+const PAGE: u16 = 10;
+
+enum Editing {
+    Name,
+    Dates,
+}
+
+/// The table of past timers with its filter.
+struct Browser {
+    entries: Vec<Entry>,
+    filter: HistoryFilter,
+    /// Indices into `entries` that pass the filter, newest first
+    visible: Vec<usize>,
+    state: TableState,
+    prompt: Option<(Editing, Prompt)>,
+    status: Option<String>,
+}
+
+impl Browser {
+    fn new(entries: Vec<Entry>, filter: HistoryFilter) -> Self {
+        let mut browser = Self { entries, filter, visible: Vec::new(), state: TableState::default(), prompt: None, status: None };
+        browser.apply_filter();
+        browser
+    }
+
+    fn apply_filter(&mut self) {
+        self.visible = (0..self.entries.len()).rev().filter(|index| self.filter.matches(&self.entries[*index])).collect();
+        self.state.select((!self.visible.is_empty()).then_some(0));
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.state.selected().and_then(|selected| self.visible.get(selected)).map(|index| &self.entries[*index])
+    }
+
+    /// Returns Some with the spec to start once the user picked a timer to run again, or Some(None) to quit.
+    fn handle_key(&mut self, code: KeyCode) -> Option<Option<String>> {
+        if let Some((editing, prompt)) = &mut self.prompt {
+            let input = match prompt.handle_key(code) {
+                PromptAction::None => return None,
+                PromptAction::Cancel => {
+                    self.prompt = None;
+                    return None;
+                }
+                PromptAction::Submit(input) => input,
+            };
+            let result = match editing {
+                Editing::Name => {
+                    self.filter.set_name(&input);
+                    Ok(())
+                }
+                Editing::Dates => self.filter.set_dates(&input),
+            };
+            match result {
+                Ok(()) => {
+                    self.prompt = None;
+                    self.apply_filter();
+                }
+                Err(err) => prompt.set_error(err),
+            }
+            return None;
+        }
+
+        self.status = None;
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(None),
+            KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+            KeyCode::PageDown => self.state.scroll_down_by(PAGE),
+            KeyCode::PageUp => self.state.scroll_up_by(PAGE),
+            KeyCode::Home => self.state.select_first(),
+            KeyCode::End => self.state.select_last(),
+            KeyCode::Char('/') => self.prompt = Some((Editing::Name, Prompt::new("Name contains", self.filter.name()))),
+            KeyCode::Char('d') => self.prompt = Some((Editing::Dates, Prompt::new("Ended FROM..TO (YYYY-MM-DD, either may be empty)", &self.filter.dates()))),
+            KeyCode::Enter | KeyCode::Char('r') => match self.selected().map(Entry::rerun_spec) {
+                Some(Some(spec)) => return Some(Some(spec)),
+                Some(None) => self.status = Some("this timer has no duration to run again".to_string()),
+                None => {}
+            },
+            _ => {}
+        }
+        None
+    }
+}
+
+impl Widget for &mut Browser {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [table_area, status_area] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let rows = self.visible.iter().map(|index| {
+            let entry = &self.entries[*index];
+            let outcome = if entry.aborted {
+                "aborted".to_string()
+            } else if entry.overshoot > 0 {
+                format!("{}s late", entry.overshoot)
+            } else {
+                "done".to_string()
+            };
+            Row::new([
+                entry.finished.format("%Y-%m-%d %H:%M").to_string(),
+                entry.name.clone().unwrap_or_else(|| "-".to_string()),
+                entry.duration.map(format_seconds).unwrap_or_default(),
+                outcome,
+                if entry.hook_ran { "ran" } else { "" }.to_string(),
+            ])
+        });
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(7),
+        ];
+        let title = format!(" {} of {} timers{} ", self.visible.len(), self.entries.len(), self.filter.describe());
+        let table = Table::new(rows, widths)
+            .header(Row::new(["Ended", "Name", "Duration", "Result", "Command"]).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        StatefulWidget::render(table, table_area, buf, &mut self.state);
+
+        let status = match &self.status {
+            Some(status) => Line::styled(status.as_str(), Style::default().fg(Color::Red)),
+            None => Line::from("↑↓ scroll  / name  d dates  Enter run again  q quit"),
+        };
+        status.centered().render(status_area, buf);
+
+        if let Some((_, prompt)) = &self.prompt {
+            prompt.render(area, buf);
+        }
+    }
+}
+
+/// `endzeit history browse`: a scrollable table of past timers, newest first, filtered by `filter`
+/// and by what is typed after `/` and `d`. Returns the spec of a timer picked to run again.
+pub fn run(filter: HistoryFilter) -> Result<Option<String>> {
+    let entries = history::load().unwrap_or_else(|err| crate::exit_with(&err));
+    let mut browser = Browser::new(entries, filter);
+    let mut terminal = ratatui::init();
+    let picked = loop {
+        terminal.draw(|frame| frame.render_widget(&mut browser, frame.area()))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if let Some(picked) = browser.handle_key(key.code) {
+            break picked;
+        }
+    };
+    ratatui::restore();
+    Ok(picked)
+}
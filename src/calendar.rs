@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::Path;
+use chrono::{
+    Local,
+    NaiveDate,
+    NaiveDateTime,
+    TimeDelta,
+    TimeZone,
+    Utc
+};
+
+use crate::clock;
+use crate::countdown::TimeRemaining;
+use crate::spec::parse_iso_duration;
+
+/// An event of an ICS calendar, in local time.
+pub struct Event {
+    pub summary: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+/// The properties of a VEVENT read so far.
+#[derive(Default)]
+struct PartialEvent {
+    summary: String,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    duration: Option<u64>,
+}
+
+/// Reads the events of an ICS file. Times with a TZID are taken as local time.
+pub fn load(path: &Path) -> Result<Vec<Event>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse(&contents).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+fn parse(contents: &str) -> Result<Vec<Event>, String> {
+    // Long lines are folded onto continuation lines starting with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<PartialEvent> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let property = name.split(';').next().unwrap_or_default();
+        match (property, &mut current) {
+            ("BEGIN", None) if value == "VEVENT" => current = Some(PartialEvent::default()),
+            ("END", Some(_)) if value == "VEVENT" => {
+                let Some(PartialEvent { summary, start: Some(start), end, duration }) = current.take() else {
+                    continue;
+                };
+                let end = end.unwrap_or_else(|| start + TimeDelta::seconds(duration.unwrap_or(0) as i64));
+                events.push(Event { summary, start, end });
+            }
+            ("SUMMARY", Some(event)) => event.summary = value.replace("\\,", ",").replace("\\;", ";"),
+            ("DTSTART", Some(event)) => event.start = Some(parse_ics_datetime(value)?),
+            ("DTEND", Some(event)) => event.end = Some(parse_ics_datetime(value)?),
+            ("DURATION", Some(event)) => event.duration = Some(parse_iso_duration(value)?),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+/// Parses `20250101T090000Z` (UTC), `20250101T090000` (local) or `20250101` (all day).
+fn parse_ics_datetime(value: &str) -> Result<NaiveDateTime, String> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| format!("Invalid date: {}", value))?;
+        return Ok(Utc.from_utc_datetime(&naive).with_timezone(&Local).naive_local());
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d").map(|date| date.and_time(Default::default())))
+        .map_err(|_| format!("Invalid date: {}", value))
+}
+
+/// Warns about the first event overlapping the time until `target`, like
+/// "heads up: 'Dentist' starts 20m before this deadline".
+pub fn conflict_warning(events: &[Event], target: NaiveDateTime) -> Option<String> {
+    let now = clock::now();
+    let mut conflicts: Vec<&Event> = events.iter()
+        .filter(|event| event.start < target && event.end > now)
+        .collect();
+    conflicts.sort_by_key(|event| event.start);
+
+    let event = conflicts.first()?;
+    let mut warning = if event.start <= now {
+        format!("heads up: '{}' is going on", event.summary)
+    } else {
+        let before = TimeRemaining::from_seconds((target - event.start).num_seconds() as u64).format();
+        format!("heads up: '{}' starts {} before this deadline", event.summary, before.trim_end_matches(" 0s"))
+    };
+    if conflicts.len() > 1 {
+        warning.push_str(&format!(" (+{} more)", conflicts.len() - 1));
+    }
+    Some(warning)
+}
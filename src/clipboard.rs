@@ -0,0 +1,51 @@
+use std::env;
+use std::process::{
+    Command,
+    Stdio
+};
+
+/// Programs that print the clipboard, tried in order until one succeeds.
+fn paste_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbpaste", &[])]
+    } else if cfg!(target_os = "windows") {
+        vec![("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])]
+    } else {
+        let mut commands: Vec<(&str, &[&str])> = vec![
+            ("xclip", &["-selection", "clipboard", "-out"]),
+            ("xsel", &["--clipboard", "--output"]),
+        ];
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            commands.insert(0, ("wl-paste", &["--no-newline"]));
+        }
+        commands
+    }
+}
+
+/// Reads the clipboard text through the platform's paste command.
+pub fn read() -> Result<String, String> {
+    let commands = paste_commands();
+    for (program, args) in &commands {
+        let Ok(output) = Command::new(program).args(*args).stdin(Stdio::null()).stderr(Stdio::null()).output() else {
+            continue;
+        };
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+    let tried: Vec<&str> = commands.iter().map(|(program, _)| *program).collect();
+    Err(format!("Failed to read the clipboard, tried {}", tried.join(", ")))
+}
+
+/// Reads a `--target` spec from the first non-empty line of the clipboard.
+/// Commands are refused, copied text shouldn't be able to run anything.
+pub fn target_spec() -> Result<String, String> {
+    let contents = read()?;
+    let Some(spec) = contents.lines().map(str::trim).find(|line| !line.is_empty()) else {
+        return Err("The clipboard is empty".to_string());
+    };
+    if spec.contains("execute=") {
+        return Err(format!("{}: Refusing to run a command from the clipboard", spec));
+    }
+    Ok(spec.to_string())
+}
@@ -0,0 +1,53 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+use chrono::{
+    Local,
+    NaiveDateTime,
+    TimeDelta
+};
+
+/// The time base of all countdowns. Normally this follows the system clock,
+/// replays run it from a recorded start time and at a different speed.
+struct Clock {
+    origin: Instant,
+    simulated: Option<(NaiveDateTime, f64)>,
+}
+
+static CLOCK: OnceLock<Clock> = OnceLock::new();
+
+fn clock() -> &'static Clock {
+    CLOCK.get_or_init(|| Clock {
+        origin: Instant::now(),
+        simulated: None,
+    })
+}
+
+/// Runs the clock from `start`, `speed` times as fast as real time.
+/// Has to be called before anything reads the clock.
+pub fn simulate(start: NaiveDateTime, speed: f64) {
+    let clock = Clock {
+        origin: Instant::now(),
+        simulated: Some((start, speed)),
+    };
+    if CLOCK.set(clock).is_err() {
+        panic!("clock::simulate called after the clock was read");
+    }
+}
+
+/// Seconds since the clock started, this is monotonic.
+pub fn seconds() -> f64 {
+    let clock = clock();
+    let elapsed = clock.origin.elapsed().as_secs_f64();
+    match clock.simulated {
+        Some((_, speed)) => elapsed * speed,
+        None => elapsed,
+    }
+}
+
+/// The current local date/time.
+pub fn now() -> NaiveDateTime {
+    match clock().simulated {
+        Some((start, _)) => start + TimeDelta::milliseconds((seconds() * 1000.0) as i64),
+        None => Local::now().naive_local(),
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf
+};
+use serde::Deserialize;
+
+use crate::contrast::Contrast;
+use crate::exec::{
+    HookValidation,
+    PowerAction,
+    Shell
+};
+use crate::i18n::Language;
+use crate::keys::KeyConfig;
+use crate::notify::{
+    Channel,
+    Tick
+};
+use crate::output::Output;
+use crate::preset::Preset;
+use crate::push::Email;
+use crate::term::TermNotify;
+
+/// `notify = true`, or the channels of `[notify]` and its subtables like `[notify.email]`.
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub enum NotifyConfig {
+    Desktop(bool),
+    Channels(NotifyChannels),
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyChannels {
+    /// The desktop notification, what `notify = true` turns on
+    pub desktop: Option<bool>,
+    pub email: Option<Email>,
+}
+
+impl NotifyConfig {
+    pub fn desktop(&self) -> bool {
+        match self {
+            NotifyConfig::Desktop(desktop) => *desktop,
+            NotifyConfig::Channels(channels) => channels.desktop.unwrap_or(false),
+        }
+    }
+
+    pub fn email(&self) -> Option<&Email> {
+        match self {
+            NotifyConfig::Desktop(_) => None,
+            NotifyConfig::Channels(channels) => channels.email.as_ref(),
+        }
+    }
+}
+
+/// Declares the settings shared by the top level of the config file and its profiles once:
+/// `Config` holds them as they are, `Profile` holds each as an override, and `overlay` applies
+/// a profile's overrides. Lists are replaced as a whole, not extended.
+macro_rules! config_tables {
+    (
+        options { $( $(#[$option_meta:meta])* $option:ident: $option_type:ty, )* }
+        lists { $( $(#[$list_meta:meta])* $list:ident: $list_type:ty, )* }
+    ) => {
+        /// Settings from `~/.config/endzeit/config.toml`, a missing file means defaults.
+        #[derive(Deserialize, Default)]
+        #[serde(default, deny_unknown_fields)]
+        pub struct Config {
+            $( $(#[$option_meta])* pub $option: Option<$option_type>, )*
+            $( $(#[$list_meta])* pub $list: Vec<$list_type>, )*
+            pub keys: KeyConfig,
+            pub presets: HashMap<String, Preset>,
+            /// Profile used when none is given with --profile
+            pub profile: Option<String>,
+            pub profiles: HashMap<String, Profile>,
+        }
+
+        /// A `[profiles.NAME]` table. Everything it sets replaces the top level value while the profile is selected.
+        #[derive(Deserialize, Default)]
+        #[serde(default, deny_unknown_fields)]
+        pub struct Profile {
+            $( $(#[$option_meta])* $option: Option<$option_type>, )*
+            $( $(#[$list_meta])* $list: Option<Vec<$list_type>>, )*
+            keys: KeyConfig,
+            presets: HashMap<String, Preset>,
+        }
+
+        impl Config {
+            /// Replaces what `profile` sets.
+            fn overlay(&mut self, profile: Profile) {
+                $( self.$option = profile.$option.or(self.$option.take()); )*
+                $( if let Some($list) = profile.$list {
+                    self.$list = $list;
+                } )*
+                self.keys.overlay(profile.keys);
+                self.presets.extend(profile.presets);
+            }
+        }
+    };
+}
+
+config_tables! {
+    options {
+        /// Milliseconds between redraws
+        refresh_rate: u64,
+        /// Command to execute when a countdown finishes, if none is given on the command line
+        execute: String,
+        /// A program and its arguments like `["rm", "--", "my file"]`, started without a shell
+        exec_direct: Vec<String>,
+        /// Shut down, suspend, hibernate or lock the machine after the commands
+        then: PowerAction,
+        /// Directory and shell commands run with, unless their timer sets its own
+        execute_cwd: PathBuf,
+        #[serde(alias = "shell")]
+        execute_shell: Shell,
+        execute_detach: bool,
+        execute_timeout: String,
+        confirm_execute: bool,
+        keep_awake: bool,
+        dnd: bool,
+        execute_retries: u32,
+        /// Duration like `"30s"`
+        execute_backoff: String,
+        max_retarget: String,
+        exit_after: String,
+        verify_cmd: String,
+        /// A duration like `30s`
+        verify_timeout: String,
+        on_retarget: String,
+        on_cancel: String,
+        /// `off`, `warn` or `strict`
+        validate_hooks: HookValidation,
+        term_notify: TermNotify,
+        /// `true` for the desktop notification with a snooze button, or a table with more channels
+        notify: NotifyConfig,
+        notify_halfway: bool,
+        bell: bool,
+        bell_count: u32,
+        flash: bool,
+        alarm: bool,
+        /// A duration like `30s`
+        alarm_every: String,
+        /// A duration like `9m`
+        snooze: String,
+        webhook: String,
+        /// An ntfy.sh topic or the URL of one
+        ntfy: String,
+        /// `TOKEN/USER`
+        pushover: String,
+        /// `TOKEN:CHAT`
+        telegram: String,
+        /// Webhook URLs the finish is posted to
+        slack: String,
+        discord: String,
+        /// `builtin:NAME` or a sound file for the sound channel
+        sound: String,
+        /// Percent
+        volume: u8,
+        /// `second` or `minute`
+        tick: Tick,
+        /// Language of notifications, `en` or `de`
+        lang: Language,
+        term_badge: bool,
+        /// `tcp://HOST:PORT` of an MQTT broker
+        mqtt: String,
+        mqtt_topic: String,
+        metrics_port: u16,
+        /// `HOST:PORT` the page of the countdown is served on
+        serve: String,
+        serve_token: String,
+        output: Output,
+        /// Template of the lines of `--output line`
+        format: String,
+        /// Plain output without any of the optional features, see --minimal
+        minimal: bool,
+        ticker: bool,
+        /// Ask before quitting while a countdown is still running
+        confirm_quit: bool,
+        /// Click and scroll on the gauges, false leaves the mouse to the terminal
+        mouse: bool,
+        /// ICS file checked for events overlapping the countdowns
+        calendar: PathBuf,
+        /// `builtin:NAME` or a TOML theme file, `colors` and `background` override it
+        theme: String,
+        /// Gauge colors handed out to timers in order
+        colors: Vec<String>,
+        background: String,
+        /// `auto`, `high` or `off`
+        contrast: Contrast,
+        /// Units remaining times are shown in, like `dhms`
+        units: String,
+        max_units: usize,
+    }
+    lists {
+        /// `--exec-at` milestones like `["50%:notify-send half"]`
+        exec_at: String,
+        /// `--execute-before` commands like `["5m:notify-send 'wrap up'"]`
+        execute_before: String,
+        /// Durations like `["30m", "10m", "1m"]`
+        notify_at: String,
+        /// Channels tried in order for each milestone, like `["flash", "desktop", "sound", "webhook"]`
+        escalate: Channel,
+        /// `--media` cues like `["pause", "90%:play"]`
+        media: String,
+        /// Extra chrono formats accepted for dates, tried before YYYY-MM-DD
+        date_formats: String,
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("endzeit").join("config.toml"))
+    }
+
+    /// Loads the config from `path`, or from the default location if none is given.
+    /// Only a missing default config is fine, a missing explicit one is an error.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let explicit = path.is_some();
+        let Some(path) = path.map(Path::to_path_buf).or_else(Self::path) else {
+            return Ok(Self::default());
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        toml::from_str(&contents).map_err(|e| format!("Invalid config {}: {}", path.display(), e))
+    }
+
+    /// Applies the named profile, or the config's default profile if `name` is None.
+    pub fn with_profile(mut self, name: Option<&str>) -> Result<Self, String> {
+        let Some(name) = name.map(str::to_string).or_else(|| self.profile.clone()) else {
+            return Ok(self);
+        };
+        let Some(profile) = self.profiles.remove(&name) else {
+            return Err(format!("Unknown profile: {}", name));
+        };
+
+        self.overlay(profile);
+        Ok(self)
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::OnceLock;
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::theme::Theme;
+
+/// Contrast ratio the gauge colors are brought to in auto mode, enough to tell them from the background.
+const AUTO_RATIO: f64 = 3.0;
+/// WCAG's minimum for normal text, forced by `--contrast high`.
+const HIGH_RATIO: f64 = 4.5;
+/// How far a color moves towards black or white per step while looking for enough contrast.
+const MIX_STEP: f64 = 0.05;
+
+/// Background of the terminal as answered to OSC 11, None if it didn't answer or wasn't asked.
+static TERMINAL_BACKGROUND: OnceLock<Option<Rgb>> = OnceLock::new();
+
+type Rgb = (u8, u8, u8);
+
+/// How the gauge colors are adjusted to the terminal background.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Contrast {
+    /// Fix up the default colors when the terminal tells its background
+    Auto,
+    /// Bring every color, also configured ones, to a WCAG-like contrast ratio
+    High,
+    /// Use the colors as they are
+    Off,
+}
+
+/// Asks the terminal for its background color once, before the TUI takes over the input.
+pub fn detect_background() {
+    TERMINAL_BACKGROUND.get_or_init(query);
+}
+
+/// Adjusts `theme` for the detected terminal background. Colors from the command line or config
+/// (`explicit_palette`, `explicit_background`) are only touched with `Contrast::High`.
+pub fn adapt(theme: &mut Theme, contrast: Contrast, explicit_palette: bool, explicit_background: bool) {
+    let terminal = TERMINAL_BACKGROUND.get().copied().flatten();
+    let (reference, ratio) = match (contrast, terminal) {
+        (Contrast::Off, _) | (Contrast::Auto, None) => return,
+        (Contrast::Auto, Some(terminal)) => (terminal, AUTO_RATIO),
+        (Contrast::High, Some(terminal)) => (terminal, HIGH_RATIO),
+        // Without an answer the gauge background is the best guess
+        (Contrast::High, None) => match rgb(theme.background) {
+            Some(background) => (background, HIGH_RATIO),
+            None => return,
+        },
+    };
+
+    // The default black gauge background would be a dark hole in a light terminal
+    if terminal.is_some() && !explicit_background && luminance(reference) > 0.5 {
+        theme.background = Color::Rgb(reference.0, reference.1, reference.2);
+    }
+    if explicit_palette && contrast != Contrast::High {
+        return;
+    }
+    for color in &mut theme.palette {
+        *color = readable(*color, reference, ratio);
+    }
+}
+
+/// `color`, or the first mix of it towards black or white that reaches `ratio` against `background`.
+fn readable(color: Color, background: Rgb, ratio: f64) -> Color {
+    let Some(rgb) = rgb(color) else {
+        return color;
+    };
+    if contrast_ratio(rgb, background) >= ratio {
+        return color;
+    }
+    let towards = if luminance(background) > 0.5 { 0.0 } else { 255.0 };
+    let mix = |channel: u8, amount: f64| (channel as f64 + (towards - channel as f64) * amount).round() as u8;
+    let mut mixed = rgb;
+    for step in 1..=(1.0 / MIX_STEP) as u32 {
+        let amount = step as f64 * MIX_STEP;
+        mixed = (mix(rgb.0, amount), mix(rgb.1, amount), mix(rgb.2, amount));
+        if contrast_ratio(mixed, background) >= ratio {
+            break;
+        }
+    }
+    Color::Rgb(mixed.0, mixed.1, mixed.2)
+}
+
+/// Relative luminance as defined by WCAG, 0 for black to 1 for white.
+fn luminance((r, g, b): Rgb) -> f64 {
+    let linear = |channel: u8| {
+        let channel = channel as f64 / 255.0;
+        if channel <= 0.03928 { channel / 12.92 } else { ((channel + 0.055) / 1.055).powf(2.4) }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// WCAG contrast ratio, from 1 for equal colors to 21 for black on white.
+fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// The RGB value of a color, the 16 named ones as xterm shows them by default.
+/// None for the terminal's own default color.
+fn rgb(color: Color) -> Option<Rgb> {
+    const BASE: [Rgb; 16] = [
+        (0x00, 0x00, 0x00), (0xcd, 0x00, 0x00), (0x00, 0xcd, 0x00), (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee), (0xcd, 0x00, 0xcd), (0x00, 0xcd, 0xcd), (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f), (0xff, 0x00, 0x00), (0x00, 0xff, 0x00), (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff), (0xff, 0x00, 0xff), (0x00, 0xff, 0xff), (0xff, 0xff, 0xff),
+    ];
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+    Some(match index {
+        0..=15 => BASE[index as usize],
+        // The 6x6x6 color cube
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        // The gray ramp
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    })
+}
+
+/// Reads a reply like `rgb:ffff/ffff/dddd` with one to four hex digits per channel.
+fn parse_reply(reply: &str) -> Option<Rgb> {
+    let (_, rgb) = reply.split_once("rgb:")?;
+    let mut channels = rgb.splitn(3, '/').map(|channel| {
+        let digits: String = channel.chars().take_while(char::is_ascii_hexdigit).collect();
+        if digits.is_empty() || digits.len() > 4 {
+            return None;
+        }
+        let max = 16u32.pow(digits.len() as u32) - 1;
+        let value = u32::from_str_radix(&digits, 16).ok()?;
+        Some((value * 255 / max) as u8)
+    });
+    Some((channels.next()??, channels.next()??, channels.next()??))
+}
+
+#[cfg(unix)]
+fn query() -> Option<Rgb> {
+    use std::io::{
+        self,
+        IsTerminal,
+        Write
+    };
+    use std::time::{
+        Duration,
+        Instant
+    };
+    use ratatui::crossterm::terminal;
+
+    /// How long a terminal gets to answer, remote ones included.
+    const QUERY_TIMEOUT: Duration = Duration::from_millis(300);
+
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return None;
+    }
+    terminal::enable_raw_mode().ok()?;
+    // Every terminal answers the device attributes request, so its reply ends the wait
+    // whether or not OSC 11 is supported and no late answer ends up as key presses
+    let mut stdout = io::stdout();
+    let written = stdout.write_all(b"\x1b]11;?\x1b\\\x1b[c").and_then(|_| stdout.flush());
+    let mut reply = Vec::new();
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    while written.is_ok() {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let mut poll = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        if left.is_zero() || unsafe { libc::poll(&mut poll, 1, left.as_millis() as i32) } <= 0 {
+            break;
+        }
+        let mut buffer = [0u8; 64];
+        let read = unsafe { libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len()) };
+        if read <= 0 {
+            break;
+        }
+        reply.extend_from_slice(&buffer[..read as usize]);
+        let attributes = reply.windows(3).position(|window| window == b"\x1b[?");
+        if attributes.is_some_and(|start| reply[start..].ends_with(b"c")) {
+            break;
+        }
+    }
+    let _ = terminal::disable_raw_mode();
+    parse_reply(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(not(unix))]
+fn query() -> Option<Rgb> {
+    None
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::fs::{
+    self,
+    File
+};
+use std::io::{
+    BufRead,
+    BufReader
+};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+use chrono::{
+    NaiveDateTime,
+    TimeDelta
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::countdown::Countdown;
+use crate::spec::parse_duration;
+
+/// A command from outside the terminal, sent by signals or written to the control FIFO.
+/// It acts on the selected timer.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Control {
+    TogglePause,
+    Pause,
+    Resume,
+    /// Moves the target, a negative delta brings it closer
+    Adjust(TimeDelta),
+    /// Ends the timer without running its command
+    Cancel,
+    /// Stops the alerts of `--alarm`
+    Dismiss,
+}
+
+impl Control {
+    /// Parses `pause`, `resume`, `toggle`, `add DURATION`, `subtract DURATION`, `cancel` or `dismiss`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let (command, argument) = input.split_once(' ').unwrap_or((input, ""));
+        match (command, argument.trim()) {
+            ("pause", "") => Ok(Control::Pause),
+            ("resume", "") => Ok(Control::Resume),
+            ("toggle", "") => Ok(Control::TogglePause),
+            ("cancel", "") => Ok(Control::Cancel),
+            ("dismiss", "") => Ok(Control::Dismiss),
+            ("add", duration) => Ok(Control::Adjust(parse_duration(duration)?)),
+            ("subtract", duration) => Ok(Control::Adjust(-parse_duration(duration)?)),
+            _ => Err(format!("Unknown command: {}", input)),
+        }
+    }
+}
+
+/// Writes the command the way `parse` reads it, so recordings can replay it.
+impl fmt::Display for Control {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Control::TogglePause => write!(f, "toggle"),
+            Control::Pause => write!(f, "pause"),
+            Control::Resume => write!(f, "resume"),
+            Control::Adjust(delta) if *delta < TimeDelta::zero() => write!(f, "subtract {}", -delta.num_seconds()),
+            Control::Adjust(delta) => write!(f, "add {}", delta.num_seconds()),
+            Control::Cancel => write!(f, "cancel"),
+            Control::Dismiss => write!(f, "dismiss"),
+        }
+    }
+}
+
+/// A timer as reported to `endzeit status`.
+#[derive(Serialize, Deserialize)]
+pub struct TimerStatus {
+    pub name: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    pub target: NaiveDateTime,
+    /// Whole seconds left
+    pub remaining: u64,
+    /// Whole seconds from start to target, including adjustments
+    #[serde(default)]
+    pub duration: u64,
+    pub paused: bool,
+    pub finished: bool,
+    pub selected: bool,
+}
+
+impl TimerStatus {
+    pub fn new(countdown: &Countdown, index: usize, selected: bool) -> Self {
+        Self {
+            name: countdown.display_name(index),
+            project: countdown.project.clone(),
+            target: countdown.target(),
+            remaining: countdown.get_remaining_time().seconds(),
+            duration: countdown.total_duration(),
+            paused: countdown.is_paused(),
+            finished: countdown.is_finished(),
+            selected,
+        }
+    }
+}
+
+/// A client of `--after-timer` waiting for the timer `name` to finish. It gets Ok once it has,
+/// or why it never will.
+pub struct TimerWait {
+    pub name: String,
+    pub reply: Sender<Result<(), String>>,
+}
+
+/// A named pipe read one command per line, like `echo "add 5m" > /tmp/endzeit.ctl`.
+/// It is removed again on drop if endzeit created it.
+pub struct Fifo {
+    path: PathBuf,
+    created: bool,
+}
+
+impl Fifo {
+    /// Creates the FIFO unless it already exists and sends every command written to it from a background thread.
+    /// Lines that don't parse are sent as errors.
+    #[cfg(unix)]
+    pub fn open(path: PathBuf, sender: Sender<Result<Control, String>>) -> Result<Self, String> {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::fs::FileTypeExt;
+
+        let created = match fs::metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_fifo() => false,
+            Ok(_) => return Err(format!("{} exists and is not a FIFO", path.display())),
+            Err(_) => {
+                let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| format!("Invalid path: {}", path.display()))?;
+                if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+                    return Err(format!("Failed to create {}: {}", path.display(), std::io::Error::last_os_error()));
+                }
+                true
+            }
+        };
+
+        let reader_path = path.clone();
+        thread::spawn(move || loop {
+            // Opening blocks until a writer shows up, reading ends when the last one closes it
+            let Ok(file) = File::open(&reader_path) else {
+                return;
+            };
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if sender.send(Control::parse(&line)).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Self { path, created })
+    }
+}
+
+impl Drop for Fifo {
+    fn drop(&mut self) {
+        if self.created {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
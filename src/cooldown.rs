@@ -0,0 +1,42 @@
+use std::fs;
+use chrono::{
+    DateTime,
+    Local,
+    TimeDelta
+};
+use color_eyre::Result;
+
+use crate::countdown::TimeRemaining;
+use crate::state::{
+    file_name,
+    state_dir
+};
+
+/// Succeeds and starts a new cooldown if the last one for `name` is older than `duration`,
+/// otherwise prints the remaining cooldown and exits with status 1.
+pub fn run(name: &str, duration: TimeDelta) -> Result<()> {
+    let path = match state_dir("cooldowns") {
+        Ok(dir) => dir.join(file_name(name)),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let now = Local::now();
+    // A missing or unreadable timestamp counts as an expired cooldown
+    let last = fs::read_to_string(&path).ok()
+        .and_then(|contents| DateTime::parse_from_rfc3339(contents.trim()).ok());
+
+    if let Some(last) = last {
+        let remaining = last + duration - now.fixed_offset();
+        if remaining > TimeDelta::zero() {
+            let seconds = (remaining.num_milliseconds() as u64).div_ceil(1000);
+            eprintln!("{}: {} remaining", name, TimeRemaining::from_seconds(seconds).format());
+            std::process::exit(1);
+        }
+    }
+
+    fs::write(&path, now.to_rfc3339())?;
+    Ok(())
+}
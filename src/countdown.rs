@@ -0,0 +1,796 @@
+use std::fmt;
+use std::io;
+use std::process::Command;
+use std::sync::mpsc::{
+    self,
+    Receiver,
+    Sender
+};
+use std::thread;
+use chrono::{
+    NaiveDateTime,
+    TimeDelta
+};
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    layout::{
+        Alignment,
+        Constraint,
+        Layout,
+        Rect
+    },
+    style::{
+        Color,
+        Modifier,
+        Style
+    },
+    widgets::{
+        Gauge,
+        Paragraph,
+        Widget
+    },
+};
+
+use crate::clock;
+use crate::control::TimerStatus;
+use crate::exec::{
+    is_process_alive,
+    Launcher,
+    PowerAction,
+    Ran
+};
+use crate::milestone::{
+    BeforeEnd,
+    Milestone
+};
+use crate::source::TargetSource;
+use crate::spec::{
+    escape_option,
+    parse_when,
+    TargetSpec
+};
+use crate::units::{
+    format_seconds,
+    write_seconds
+};
+
+/// How many seconds a note like "target moved" stays visible in the gauge label.
+const NOTE_SECONDS: f64 = 60.0;
+
+/// A single timer counting down towards its target.
+pub struct Countdown {
+    pub name: Option<String>,
+    /// Groups timers in `endzeit ctl tree`
+    pub project: Option<String>,
+    pub color: Color,
+    pub background: Color,
+    /// Run one after the other when the countdown completes
+    pub execute_commands: Vec<String>,
+    /// A program and its arguments to start without a shell after them, from `--exec-direct`
+    pub exec_direct: Option<Vec<String>>,
+    /// `--then`: shuts down, suspends or locks the machine after all of them
+    pub then: Option<PowerAction>,
+    /// Where and through which shell `execute_commands` run
+    pub launcher: Launcher,
+    /// `--confirm-execute`: at the end the commands wait for `confirm_commands`
+    pub confirm_execute: bool,
+    pub source: TargetSource,
+    when: Option<String>,
+    watched_pid: Option<u32>,
+    target: NaiveDateTime,
+    start_seconds: f64,
+    total_seconds: f64,
+    initial_seconds: f64,
+    completed: bool,
+    cancelled: bool,
+    hook_ran: bool,
+    /// Finished with commands that wait for `confirm_commands`
+    awaiting_confirmation: bool,
+    halfway_reached: bool,
+    /// The smallest threshold in seconds the time left was at or below at the last check,
+    /// the outer None before the first one
+    threshold: Option<Option<u64>>,
+    /// Progress in percent at the last milestone check, None before the first
+    progress: Option<f64>,
+    failed_hooks: Vec<FailedHook>,
+    /// Where the commands running in the background report back, see `collect_hooks`
+    hook_sender: Sender<HookDone>,
+    hook_results: Receiver<HookDone>,
+    running_hooks: usize,
+    /// The completion commands that haven't reported back yet, and how the others went
+    pending_completion: usize,
+    completion_outcomes: Vec<String>,
+    completion_failed: bool,
+    /// Captured output of commands, see `take_output`
+    output: Vec<(String, String)>,
+    note: Option<(String, f64)>,
+    paused_at: Option<f64>,
+}
+
+/// A completion command that failed, ready to run again with the same environment.
+pub struct FailedHook {
+    /// The command line, or the program and arguments of `--exec-direct`
+    pub label: String,
+    pub command: Command,
+    pub launcher: Launcher,
+    /// How the first try went
+    pub outcome: String,
+}
+
+/// What a command of a timer that ran in the background reports back.
+struct HookDone {
+    /// The command line, or the program and arguments of `--exec-direct`
+    label: String,
+    /// The command, kept to try it again, None if it couldn't be built
+    command: Option<Command>,
+    ran: io::Result<Option<Ran>>,
+    /// One of the completion commands, which are noted together once all are done
+    completion: bool,
+}
+
+/// Why a command of a timer runs, `ENDZEIT_RESULT` in its environment.
+#[derive(Clone, Copy)]
+pub enum HookResult {
+    /// A milestone on the way
+    Running,
+    Completed,
+    /// Given up before it finished, see `--on-cancel`
+    Cancelled,
+    /// A file source moved the target away from `previous`, see `--on-retarget`
+    Retargeted { previous: NaiveDateTime, shift: TimeDelta },
+}
+
+impl fmt::Display for HookResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HookResult::Running => write!(f, "running"),
+            HookResult::Completed => write!(f, "completed"),
+            HookResult::Cancelled => write!(f, "cancelled"),
+            HookResult::Retargeted { .. } => write!(f, "retargeted"),
+        }
+    }
+}
+
+/// Whole seconds left on a countdown.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeRemaining {
+    seconds: u64,
+}
+
+impl Countdown {
+    pub fn new(target_datetime: NaiveDateTime, name: Option<String>, color: Color, execute_commands: Vec<String>) -> Self {
+        let now = clock::now();
+        let total_seconds = (target_datetime - now).num_milliseconds() as f64 / 1000.0;
+        let (hook_sender, hook_results) = mpsc::channel();
+
+        Self {
+            name,
+            project: None,
+            color,
+            background: Color::Black,
+            execute_commands,
+            exec_direct: None,
+            then: None,
+            launcher: Launcher::default(),
+            confirm_execute: false,
+            source: TargetSource::Fixed,
+            when: None,
+            watched_pid: None,
+            target: target_datetime,
+            start_seconds: clock::seconds(),
+            total_seconds,
+            initial_seconds: total_seconds,
+            completed: false,
+            cancelled: false,
+            hook_ran: false,
+            awaiting_confirmation: false,
+            halfway_reached: false,
+            threshold: None,
+            progress: None,
+            failed_hooks: Vec::new(),
+            hook_sender,
+            hook_results,
+            running_hooks: 0,
+            pending_completion: 0,
+            completion_outcomes: Vec::new(),
+            completion_failed: false,
+            output: Vec::new(),
+            note: None,
+            paused_at: None,
+        }
+    }
+
+    /// Creates the countdown for a `--target` spec, `fallback_color` is used if the spec has none.
+    pub fn from_spec(target_spec: TargetSpec, fallback_color: Color) -> Self {
+        let color = target_spec.color.unwrap_or(fallback_color);
+        let mut countdown = Countdown::new(target_spec.target, target_spec.name, color, target_spec.execute)
+            .with_source(target_spec.source);
+        countdown.when = Some(target_spec.when);
+        countdown.project = target_spec.project;
+        countdown.launcher = target_spec.launcher;
+        countdown
+    }
+
+    /// A copy of a timer running in another instance, for `endzeit attach`. It has no command to run.
+    pub fn from_status(status: &TimerStatus, color: Color) -> Self {
+        let now = clock::seconds();
+        let mut countdown = Countdown::new(status.target, Some(status.name.clone()), color, Vec::new());
+        countdown.total_seconds = status.duration as f64;
+        countdown.start_seconds = now - status.duration.saturating_sub(status.remaining) as f64;
+        countdown.paused_at = status.paused.then_some(now);
+        countdown.completed = status.finished;
+        countdown
+    }
+
+    pub fn with_source(mut self, source: TargetSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Finishes the countdown early, without running the completion handler, once `pid` exits.
+    pub fn with_watched_pid(mut self, pid: u32) -> Self {
+        self.watched_pid = Some(pid);
+        self
+    }
+
+    /// The WHEN part the countdown was started with, if it came from a spec.
+    pub fn when(&self) -> Option<&str> {
+        self.when.as_deref()
+    }
+
+    /// How long the countdown was set to run when it started, in whole seconds.
+    pub fn initial_duration(&self) -> u64 {
+        self.initial_seconds.max(0.0).round() as u64
+    }
+
+    /// How long the countdown runs from start to target, in whole seconds.
+    pub fn total_duration(&self) -> u64 {
+        self.total_seconds.max(0.0).round() as u64
+    }
+
+    /// A `--target` spec that starts the same countdown elsewhere: same target, name, color and command.
+    pub fn to_spec(&self) -> String {
+        let mut spec = match self.when.as_deref() {
+            Some(when) if when.starts_with("file:") => escape_option(when),
+            _ => self.target.format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+        if let Some(name) = &self.name {
+            spec.push_str(&format!(",name={}", escape_option(name)));
+        }
+        if let Some(project) = &self.project {
+            spec.push_str(&format!(",project={}", escape_option(project)));
+        }
+        spec.push_str(&format!(",color={}", self.color));
+        if let Some(cwd) = &self.launcher.cwd {
+            spec.push_str(&format!(",cwd={}", escape_option(&cwd.to_string_lossy())));
+        }
+        if let Some(shell) = self.launcher.shell.and_then(|shell| shell.to_possible_value()) {
+            spec.push_str(&format!(",shell={}", shell.get_name()));
+        }
+        for execute in &self.execute_commands {
+            spec.push_str(&format!(",execute={}", execute));
+        }
+        spec
+    }
+
+    pub fn target(&self) -> NaiveDateTime {
+        self.target
+    }
+
+    /// Moves the target, refusing jumps larger than `max_retarget`.
+    /// Returns the applied shift, or None if the target is unchanged or the move was refused.
+    pub fn retarget(&mut self, target_datetime: NaiveDateTime, max_retarget: Option<TimeDelta>) -> Option<TimeDelta> {
+        let delta = target_datetime - self.target;
+        if delta.is_zero() {
+            return None;
+        }
+
+        if max_retarget.is_some_and(|max| delta.abs() > max) {
+            self.set_note(format!("target move {} refused", format_delta(delta)));
+            return None;
+        }
+
+        self.target = target_datetime;
+        self.total_seconds += delta.num_milliseconds() as f64 / 1000.0;
+        self.completed = self.completed && self.is_finished();
+        self.set_note(format!("target moved {}", format_delta(delta)));
+        Some(delta)
+    }
+
+    /// Moves the target by `delta`, but not into the past, so subtracting more than is left ends the countdown now.
+    pub fn adjust(&mut self, delta: TimeDelta) {
+        // A target beyond what a date can hold stays where it is
+        let Some(target) = self.target.checked_add_signed(delta) else {
+            return;
+        };
+        // A paused target only moves on when resuming, so while paused it ends as of the pause
+        let now = match self.paused_at {
+            Some(paused_at) => clock::now() - TimeDelta::milliseconds(((clock::seconds() - paused_at) * 1000.0) as i64),
+            None => clock::now(),
+        };
+        self.retarget(target.max(now), None);
+    }
+
+    /// Runs again for `duration` from now, also when it has already finished.
+    pub fn snooze(&mut self, duration: TimeDelta) {
+        self.paused_at = None;
+        self.retarget(clock::now() + duration, None);
+        self.cancelled = false;
+        self.hook_ran = false;
+        self.awaiting_confirmation = false;
+        self.set_note(format!("snoozed {}", format_delta(duration)));
+    }
+
+    /// Starts over. The original WHEN is resolved again, so `+25m` runs for 25 minutes and `17:00`
+    /// moves to its next occurrence; if it lies in the past the original duration runs again.
+    pub fn restart(&mut self) {
+        let now = clock::now();
+        let target = self.when.as_deref()
+            .and_then(|when| parse_when(when).ok())
+            .map(|(_, target)| target)
+            .filter(|target| *target > now)
+            .unwrap_or_else(|| now + TimeDelta::milliseconds((self.initial_seconds * 1000.0) as i64));
+
+        self.target = target;
+        self.start_seconds = clock::seconds();
+        self.total_seconds = (target - now).num_milliseconds() as f64 / 1000.0;
+        self.completed = false;
+        self.cancelled = false;
+        self.hook_ran = false;
+        self.awaiting_confirmation = false;
+        self.halfway_reached = false;
+        self.progress = None;
+        self.paused_at = None;
+        self.note = None;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Freezes the remaining time, or resumes and pushes the target back by the time spent paused.
+    pub fn toggle_pause(&mut self) {
+        if self.completed {
+            return;
+        }
+        match self.paused_at.take() {
+            Some(paused_at) => {
+                let paused = clock::seconds() - paused_at;
+                self.start_seconds += paused;
+                self.target += TimeDelta::milliseconds((paused * 1000.0) as i64);
+            }
+            None => self.paused_at = Some(clock::seconds()),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        let elapsed = self.elapsed_seconds();
+        elapsed >= self.total_seconds
+    }
+
+    /// Runs the completion handler once the countdown has finished, or with `confirm_execute`
+    /// leaves its commands waiting for `confirm_commands`.
+    /// Returns true only on the tick the handler actually ran.
+    pub fn complete_if_finished(&mut self) -> bool {
+        if self.completed {
+            return false;
+        }
+        if self.watched_pid.is_some_and(|pid| !is_process_alive(pid)) {
+            self.stop();
+            return false;
+        }
+        if !self.is_finished() {
+            return false;
+        }
+        self.completed = true;
+        if self.confirm_execute && !self.command_lines().is_empty() {
+            self.awaiting_confirmation = true;
+        } else {
+            self.handle_completion();
+        }
+        true
+    }
+
+    /// Whether the commands wait for `confirm_commands`.
+    pub fn awaits_confirmation(&self) -> bool {
+        self.awaiting_confirmation
+    }
+
+    /// Runs the commands that wait since the end, or drops them.
+    pub fn confirm_commands(&mut self, run: bool) {
+        if !std::mem::take(&mut self.awaiting_confirmation) {
+            return;
+        }
+        if run {
+            self.handle_completion();
+        } else {
+            self.set_note("commands not run".to_string());
+        }
+    }
+
+    /// The completion commands, `--exec-direct` with its arguments joined by spaces.
+    pub fn command_lines(&self) -> Vec<String> {
+        self.execute_commands.iter().cloned()
+            .chain(self.exec_direct.as_ref().map(|argv| argv.join(" ")))
+            .chain(self.then.and_then(PowerAction::argv).map(|argv| argv.join(" ")))
+            .collect()
+    }
+
+    /// Moves the target to now, so the completion handler runs on the next check.
+    pub fn skip_to_end(&mut self) {
+        if self.completed {
+            return;
+        }
+        self.paused_at = None;
+        self.target = clock::now();
+        self.total_seconds = self.elapsed_seconds();
+        // Milestones that were skipped over don't run
+        self.progress = Some(100.0);
+    }
+
+    /// Ends the countdown right now without running its command.
+    pub fn cancel(&mut self) {
+        if self.completed {
+            return;
+        }
+        self.paused_at = None;
+        self.stop();
+        self.cancelled = true;
+        self.set_note("cancelled".to_string());
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Ends the countdown right now without running the completion handler.
+    fn stop(&mut self) {
+        self.total_seconds = self.elapsed_seconds();
+        self.completed = true;
+    }
+
+    /// Returns true on the first tick at or past half of the countdown.
+    pub fn reached_halfway(&mut self) -> bool {
+        if self.halfway_reached || self.get_progress_percentage() < 50.0 {
+            return false;
+        }
+        self.halfway_reached = true;
+        true
+    }
+
+    /// Returns the thresholds in seconds left that the timer fell to since the last check, usually
+    /// one, more after a jump. Thresholds already passed at the first check don't count, ones it moves
+    /// back above count again. `thresholds` must be the same on every check.
+    pub fn crossed_thresholds(&mut self, thresholds: &[u64]) -> Vec<u64> {
+        let remaining = self.get_remaining_time().seconds();
+        let level = thresholds.iter().copied().filter(|threshold| remaining <= *threshold).min();
+        let Some(previous) = self.threshold.replace(level) else {
+            return Vec::new();
+        };
+        let Some(level) = level.filter(|_| !self.is_finished()) else {
+            return Vec::new();
+        };
+        thresholds.iter().copied()
+            .filter(|threshold| *threshold >= level && previous.is_none_or(|previous| *threshold < previous))
+            .collect()
+    }
+
+    /// Starts the commands of `hooks` whose time left is among the `crossed` thresholds and returns
+    /// their seconds left.
+    pub fn run_before_end(&mut self, hooks: &[BeforeEnd], crossed: &[u64]) -> Vec<u64> {
+        let due: Vec<&BeforeEnd> = hooks.iter().filter(|hook| crossed.contains(&hook.seconds)).collect();
+        for hook in &due {
+            self.run_hook(&hook.command, HookResult::Running);
+        }
+        due.iter().map(|hook| hook.seconds).collect()
+    }
+
+    /// The progress in percent at the last check and now, for what lies on the way. The first check
+    /// only notes where the timer stands, so a resumed timer doesn't repeat what already ran before.
+    pub fn advance_progress(&mut self) -> Option<(f64, f64)> {
+        let progress = self.get_progress_percentage();
+        let previous = self.progress.replace(progress)?;
+        if self.completed {
+            return None;
+        }
+        Some((previous, progress))
+    }
+
+    /// Starts the commands of the `milestones` within the stretch of progress `passed` and returns
+    /// their percentages.
+    pub fn pass_milestones(&mut self, milestones: &[Milestone], passed: (f64, f64)) -> Vec<f64> {
+        let (previous, progress) = passed;
+        let due: Vec<&Milestone> = milestones.iter().filter(|milestone| milestone.crossed(previous, progress)).collect();
+        for milestone in &due {
+            self.run_hook(&milestone.command, HookResult::Running);
+        }
+        due.iter().map(|milestone| milestone.percent).collect()
+    }
+
+    /// The name, or a numbered fallback for unnamed timers.
+    pub fn display_name(&self, index: usize) -> String {
+        self.name.clone().unwrap_or_else(|| format!("Timer {}", index + 1))
+    }
+
+    pub fn get_remaining_time(&self) -> TimeRemaining {
+        let remaining_seconds = (self.total_seconds as u64).saturating_sub(
+            self.elapsed_seconds() as u64
+        );
+        TimeRemaining::from_seconds(remaining_seconds)
+    }
+
+    fn elapsed_seconds(&self) -> f64 {
+        self.paused_at.unwrap_or_else(clock::seconds) - self.start_seconds
+    }
+
+    pub fn get_progress_percentage(&self) -> f64 {
+        let elapsed = self.elapsed_seconds();
+        if self.total_seconds > 0.0 {
+            (elapsed / self.total_seconds).min(1.0) * 100.0
+        } else {
+            100.0
+        }
+    }
+
+    /// The milestone at 100%. Starts the commands one after the other in the background,
+    /// `collect_hooks` notes how they went.
+    fn handle_completion(&mut self) {
+        let mut commands: Vec<(String, io::Result<Command>)> = self.execute_commands.iter()
+            .map(|command_line| (command_line.clone(), self.launcher.command(command_line)))
+            .collect();
+        if let Some(argv) = &self.exec_direct {
+            commands.push((argv.join(" "), self.launcher.direct(argv)));
+        }
+        if let Some(argv) = self.then.and_then(PowerAction::argv) {
+            commands.push((argv.join(" "), self.launcher.direct(&argv)));
+        }
+        self.pending_completion += commands.len();
+        self.start_hooks(commands, HookResult::Completed);
+    }
+
+    /// Runs `commands` one after the other on a thread of their own, so the countdowns go on
+    /// meanwhile. Each reports back to `collect_hooks` once it's done.
+    fn start_hooks(&mut self, commands: Vec<(String, io::Result<Command>)>, result: HookResult) {
+        if commands.is_empty() {
+            return;
+        }
+        self.running_hooks += commands.len();
+        let env = self.hook_env(result);
+        let launcher = self.launcher.clone();
+        let sender = self.hook_sender.clone();
+        let completion = matches!(result, HookResult::Completed);
+        thread::spawn(move || {
+            for (label, command) in commands {
+                let done = match command {
+                    Ok(mut command) => {
+                        command.envs(env.clone());
+                        let ran = launcher.start(&mut command);
+                        HookDone { label, command: Some(command), ran, completion }
+                    }
+                    Err(e) => HookDone { label, command: None, ran: Err(e), completion },
+                };
+                if sender.send(done).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Takes in what the commands that ran in the background reported since the last call: their
+    /// output is kept for `take_output`, completion commands that failed for `take_failed_hooks`,
+    /// and how the completion commands went is noted unless a single one succeeded.
+    /// Returns true once the last of the completion commands is done.
+    pub fn collect_hooks(&mut self) -> bool {
+        let mut completed = false;
+        while let Ok(done) = self.hook_results.try_recv() {
+            completed |= self.take_in(done);
+        }
+        completed
+    }
+
+    /// Waits for the commands still running in the background, before quitting.
+    pub fn wait_for_hooks(&mut self) {
+        while self.running_hooks > 0 {
+            let Ok(done) = self.hook_results.recv() else {
+                return;
+            };
+            self.take_in(done);
+        }
+    }
+
+    /// Whether commands of this timer are still running in the background.
+    pub fn runs_hooks(&self) -> bool {
+        self.running_hooks > 0
+    }
+
+    /// Whether the completion commands are still running.
+    pub fn runs_completion(&self) -> bool {
+        self.pending_completion > 0
+    }
+
+    /// Returns true if it was the last of the completion commands.
+    fn take_in(&mut self, done: HookDone) -> bool {
+        let HookDone { label, command, ran, completion } = done;
+        self.running_hooks -= 1;
+        if let Ok(Some(ran)) = &ran {
+            self.keep_output(&label, ran);
+        }
+        if !completion {
+            if let Err(e) = ran {
+                self.set_note(format!("`{}` didn't start: {}", label, e));
+            }
+            return false;
+        }
+
+        let started = ran.is_ok();
+        let (outcome, failed) = match ran {
+            Ok(Some(ran)) => match ran.failure() {
+                Some(failure) => (failure, true),
+                None => ("ok".to_string(), false),
+            },
+            Ok(None) => ("started".to_string(), false),
+            Err(e) => (format!("didn't start: {}", e), true),
+        };
+        self.hook_ran |= started;
+        self.completion_failed |= failed;
+        self.completion_outcomes.push(format!("`{}` {}", label, outcome));
+        if let Some(command) = command.filter(|_| started && failed) {
+            self.failed_hooks.push(FailedHook { label, command, launcher: self.launcher.clone(), outcome });
+        }
+
+        self.pending_completion -= 1;
+        if self.pending_completion > 0 {
+            return false;
+        }
+        let outcomes = std::mem::take(&mut self.completion_outcomes);
+        if std::mem::take(&mut self.completion_failed) || outcomes.len() > 1 {
+            self.set_note(outcomes.join(", "));
+        }
+        true
+    }
+
+    /// The completion commands that failed since the last call, to be tried again.
+    pub fn take_failed_hooks(&mut self) -> Vec<FailedHook> {
+        std::mem::take(&mut self.failed_hooks)
+    }
+
+    /// The output of the commands that ran since the last call, with their command lines.
+    pub fn take_output(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.output)
+    }
+
+    fn keep_output(&mut self, label: &str, ran: &Ran) {
+        if let Some(output) = ran.output.as_deref().filter(|output| !output.trim().is_empty()) {
+            self.output.push((label.to_string(), output.to_string()));
+        }
+    }
+
+    /// Starts a command of this timer with its launcher in the background, see `collect_hooks`.
+    pub fn run_hook(&mut self, command_line: &str, result: HookResult) {
+        let command = self.launcher.command(command_line);
+        self.start_hooks(vec![(command_line.to_string(), command)], result);
+    }
+
+    /// What a command learns about its timer from the environment.
+    fn hook_env(&self, result: HookResult) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("ENDZEIT_NAME", self.name.clone().unwrap_or_default()),
+            ("ENDZEIT_PROJECT", self.project.clone().unwrap_or_default()),
+            ("ENDZEIT_TARGET", self.target.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            ("ENDZEIT_DURATION_SECONDS", self.total_duration().to_string()),
+            ("ENDZEIT_OVERSHOOT_SECONDS", (clock::now() - self.target).num_seconds().to_string()),
+            ("ENDZEIT_RESULT", result.to_string()),
+        ];
+        if let HookResult::Retargeted { previous, shift } = result {
+            env.push(("ENDZEIT_PREVIOUS_TARGET", previous.format("%Y-%m-%dT%H:%M:%S").to_string()));
+            env.push(("ENDZEIT_SHIFT_SECONDS", shift.num_seconds().to_string()));
+        }
+        env
+    }
+
+    /// Whether the command ran when the countdown completed.
+    pub fn hook_ran(&self) -> bool {
+        self.hook_ran
+    }
+
+    /// Shows `note` next to the remaining time for a while.
+    pub fn set_note(&mut self, note: String) {
+        self.note = Some((note, clock::seconds()));
+    }
+
+    pub fn clear_note(&mut self) {
+        self.note = None;
+    }
+
+    fn note(&self) -> Option<&str> {
+        match &self.note {
+            Some((note, since)) if clock::seconds() - since < NOTE_SECONDS => Some(note),
+            _ => None,
+        }
+    }
+}
+
+impl TimeRemaining {
+    pub fn from_seconds(seconds: u64) -> Self {
+        TimeRemaining { seconds }
+    }
+
+    pub fn seconds(&self) -> u64 {
+        self.seconds
+    }
+
+    /// Formats with the configured units, by default like `1d 2h 5s`.
+    pub fn format(&self) -> String {
+        format_seconds(self.seconds)
+    }
+
+    /// Like `format`, but into a reusable buffer.
+    pub fn write_to(&self, out: &mut String) {
+        write_seconds(self.seconds, out);
+    }
+}
+
+/// Formats a signed shift like `+15m` or `-1h 30m 10s`.
+fn format_delta(delta: TimeDelta) -> String {
+    let sign = if delta < TimeDelta::zero() { '-' } else { '+' };
+    let time_string = TimeRemaining::from_seconds(delta.num_seconds().unsigned_abs()).format();
+    format!("{}{}", sign, time_string.trim_end_matches(" 0s"))
+}
+
+impl Widget for &Countdown {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut label = self.get_remaining_time().format();
+        let mut area = area;
+
+        // The name goes on its own line above the gauge if there is room, otherwise into the gauge label
+        if let Some(name) = &self.name {
+            if area.height >= 2 {
+                let [title_area, gauge_area] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+                Paragraph::new(name.as_str())
+                    .style(Style::default().fg(self.color).add_modifier(Modifier::BOLD))
+                    .alignment(Alignment::Center)
+                    .render(title_area, buf);
+                area = gauge_area;
+            } else {
+                label = format!("{}: {}", name, label);
+            }
+        }
+        if self.is_paused() {
+            label.push_str(" PAUSED");
+        }
+        if let Some(note) = self.note() {
+            label.push_str(&format!(" ({})", note));
+        }
+
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(self.color).bg(self.background))
+            .percent(self.get_progress_percentage() as u16)
+            .label(label);
+
+        gauge.render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use ratatui::style::Color;
+
+    use super::Countdown;
+    use crate::spec::parse_target_spec;
+
+    #[test]
+    fn to_spec_round_trips_commas() {
+        let spec = "2099-01-01 12:00:00,name=Deploy,, final,project=a,,b,cwd=/tmp/x,,y,execute=echo a, b";
+        let countdown = Countdown::from_spec(parse_target_spec(spec).unwrap(), Color::Red);
+        assert_eq!(countdown.name.as_deref(), Some("Deploy, final"));
+
+        let restored = parse_target_spec(&countdown.to_spec()).unwrap();
+        assert_eq!(restored.name.as_deref(), Some("Deploy, final"));
+        assert_eq!(restored.project.as_deref(), Some("a,b"));
+        assert_eq!(restored.launcher.cwd, Some(PathBuf::from("/tmp/x,y")));
+        assert_eq!(restored.execute, vec!["echo a, b".to_string()]);
+        assert_eq!(restored.target, countdown.target());
+    }
+}
@@ -0,0 +1,99 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::os::fd::AsRawFd;
+use std::process::{
+    Command,
+    Stdio
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::countdown::Countdown;
+use crate::exec::PowerAction;
+use crate::spec::parse_target_spec;
+use crate::theme::Theme;
+
+/// Hands the timers to the instance started by `hand_off`, as JSON.
+const HAND_OFF_VAR: &str = "ENDZEIT_HAND_OFF";
+
+/// A timer as handed to the background instance, with the commands its spec can't hold.
+#[derive(Serialize, Deserialize)]
+struct HandedOff {
+    spec: String,
+    exec_direct: Option<Vec<String>>,
+    then: Option<PowerAction>,
+}
+
+/// Forks into the background for `start --detach`: the parent prints the pid and exits, the child
+/// continues in a new session with stdin, stdout and stderr on `/dev/null`. It registers its
+/// socket like every instance, so `endzeit list`, `status`, `pause` and `cancel` find it.
+///
+/// Must be called before any thread is spawned, only the calling thread survives a fork.
+pub fn detach() -> Result<(), String> {
+    match unsafe { libc::fork() } {
+        -1 => return Err(format!("Failed to fork: {}", std::io::Error::last_os_error())),
+        0 => {}
+        pid => {
+            println!("endzeit running in the background with pid {}", pid);
+            std::process::exit(0);
+        }
+    }
+
+    // Leave the terminal's session so closing it doesn't hang up the timer
+    if unsafe { libc::setsid() } == -1 {
+        return Err(format!("Failed to start a new session: {}", std::io::Error::last_os_error()));
+    }
+    let null = OpenOptions::new().read(true).write(true).open("/dev/null")
+        .map_err(|e| format!("Failed to open /dev/null: {}", e))?;
+    for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        if unsafe { libc::dup2(null.as_raw_fd(), fd) } == -1 {
+            return Err(format!("Failed to redirect output: {}", std::io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
+
+/// Starts a detached instance running `countdowns`, for the detach key of the TUI. It gets the command
+/// line of this one, so the options and the commands shared by all timers stay the same, and takes
+/// the countdowns from `handed_off` instead of the ones the command line names. Returns its pid.
+pub fn hand_off(countdowns: &[&Countdown]) -> Result<u32, String> {
+    let timers: Vec<HandedOff> = countdowns.iter()
+        .map(|countdown| HandedOff {
+            spec: countdown.to_spec(),
+            exec_direct: countdown.exec_direct.clone(),
+            then: countdown.then,
+        })
+        .collect();
+    let timers = serde_json::to_string(&timers).map_err(|e| e.to_string())?;
+    let exe = env::current_exe().map_err(|e| format!("Failed to find the endzeit binary: {}", e))?;
+    let output = Command::new(exe).args(env::args_os().skip(1)).env(HAND_OFF_VAR, timers).stdin(Stdio::null()).output()
+        .map_err(|e| format!("Failed to start endzeit in the background: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    // The last word of the message printed by `detach`
+    String::from_utf8_lossy(&output.stdout).split_whitespace().last()
+        .and_then(|pid| pid.parse().ok())
+        .ok_or_else(|| "endzeit in the background didn't report its pid".to_string())
+}
+
+/// The countdowns handed over by `hand_off` if this instance was started by it. The variable is
+/// removed so the commands of the timers don't see it.
+pub fn handed_off(theme: &Theme) -> Result<Option<Vec<Countdown>>, String> {
+    let Some(timers) = env::var_os(HAND_OFF_VAR) else {
+        return Ok(None);
+    };
+    env::remove_var(HAND_OFF_VAR);
+    let timers: Vec<HandedOff> = serde_json::from_str(&timers.to_string_lossy()).map_err(|e| format!("{}: {}", HAND_OFF_VAR, e))?;
+    let mut countdowns = Vec::new();
+    for timer in timers {
+        let target_spec = parse_target_spec(&timer.spec).map_err(|err| format!("{}: {}", timer.spec, err))?;
+        let mut countdown = Countdown::from_spec(target_spec, theme.color(countdowns.len()));
+        countdown.exec_direct = timer.exec_direct;
+        countdown.then = timer.then;
+        countdowns.push(countdown);
+    }
+    Ok(Some(countdowns))
+}
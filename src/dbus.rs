@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use zbus::blocking::Connection;
+use zbus::zvariant::{
+    OwnedFd,
+    Value
+};
+
+/// Shows a notification through the freedesktop notification service on the session bus,
+/// returns false if there is none.
+pub fn notify(summary: &str, body: &str) -> bool {
+    let Ok(connection) = Connection::session() else {
+        return false;
+    };
+    // App name, id to replace, icon, summary, body, actions, hints and the default timeout
+    let arguments = ("endzeit", 0u32, "", summary, body, Vec::<&str>::new(), HashMap::<&str, Value>::new(), -1i32);
+    connection.call_method(
+        Some("org.freedesktop.Notifications"),
+        "/org/freedesktop/Notifications",
+        Some("org.freedesktop.Notifications"),
+        "Notify",
+        &arguments,
+    ).is_ok()
+}
+
+/// Calls `method` of the MPRIS player interface, like `Pause`, on every media player of the session
+/// bus. Returns false if there is no session bus.
+pub fn control_players(method: &str) -> bool {
+    let Ok(connection) = Connection::session() else {
+        return false;
+    };
+    let Ok(reply) = connection.call_method(
+        Some("org.freedesktop.DBus"),
+        "/org/freedesktop/DBus",
+        Some("org.freedesktop.DBus"),
+        "ListNames",
+        &(),
+    ) else {
+        return false;
+    };
+    let names: Vec<String> = reply.body().deserialize().unwrap_or_default();
+    for name in names.iter().filter(|name| name.starts_with("org.mpris.MediaPlayer2.")) {
+        // A player that can't do it, like pausing what is stopped, doesn't stop the others
+        let _ = connection.call_method(
+            Some(name.as_str()),
+            "/org/mpris/MediaPlayer2",
+            Some("org.mpris.MediaPlayer2.Player"),
+            method,
+            &(),
+        );
+    }
+    true
+}
+
+/// A lock of logind that blocks sleep and idle actions for as long as it's held, released on drop.
+pub struct Inhibitor {
+    _lock: OwnedFd,
+}
+
+impl Inhibitor {
+    /// Asks logind on the system bus for the lock, `why` is shown by `systemd-inhibit --list`.
+    pub fn take(why: &str) -> Result<Self, String> {
+        let connection = Connection::system().map_err(|e| format!("No system bus: {}", e))?;
+        let reply = connection.call_method(
+            Some("org.freedesktop.login1"),
+            "/org/freedesktop/login1",
+            Some("org.freedesktop.login1.Manager"),
+            "Inhibit",
+            &("sleep:idle", "endzeit", why, "block"),
+        ).map_err(|e| format!("logind refused to inhibit sleep: {}", e))?;
+        let lock = reply.body().deserialize().map_err(|e| format!("Invalid answer from logind: {}", e))?;
+        Ok(Self { _lock: lock })
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::mpsc::{
+    self,
+    Sender
+};
+use std::thread;
+use std::time::Duration;
+use chrono::{
+    Local,
+    TimeDelta
+};
+use color_eyre::Result;
+
+use crate::app::App;
+use crate::clock;
+use crate::countdown::Countdown;
+use crate::notify::{
+    Channel,
+    Escalation,
+    Sound
+};
+use crate::settings::Settings;
+use crate::theme::Theme;
+
+/// How much faster than real time the demo runs, a minute per second.
+const SPEED: f64 = 60.0;
+/// The demo timers, each finishes after as many seconds as it has minutes.
+const TIMERS: [(&str, i64); 4] = [
+    ("Tea", 3),
+    ("Focus", 12),
+    ("Standup", 20),
+    ("Deploy", 28),
+];
+/// How long the finished timers stay on screen, in simulated time.
+const EXIT_AFTER: TimeDelta = TimeDelta::minutes(2);
+
+/// The steps of the tour: after how many real seconds, and what changes.
+#[derive(Clone, Copy)]
+enum Stage {
+    Ocean,
+    Ticker,
+    Sunset,
+}
+
+const STAGES: [(u64, Stage); 3] = [
+    (7, Stage::Ocean),
+    (14, Stage::Ticker),
+    (21, Stage::Sunset),
+];
+
+impl Stage {
+    fn settings(self) -> Settings {
+        let theme = match self {
+            Stage::Ocean => Theme::builtin("ocean").unwrap_or_default(),
+            Stage::Ticker => Theme::default(),
+            Stage::Sunset => Theme::builtin("sunset").unwrap_or_default(),
+        };
+        Settings {
+            ticker: matches!(self, Stage::Ticker),
+            theme,
+            ..demo_settings()
+        }
+    }
+}
+
+/// Everything the demo has in common: the terminal flashes at halfway and on finish, and it quits
+/// shortly after the last timer.
+fn demo_settings() -> Settings {
+    Settings {
+        escalation: Escalation::new(vec![Channel::Flash], None, None, Sound::System, 100),
+        exit_after: EXIT_AFTER,
+        ..Settings::default()
+    }
+}
+
+/// `endzeit demo`: a tour of about 30 seconds through the stacked gauges, themes, the ticker,
+/// the halfway and finish flashes and finished timers, on a clock running a minute per second.
+/// Nothing is executed or added to the history. `q` ends it early.
+pub fn run(settings: Settings) -> Result<()> {
+    clock::simulate(Local::now().naive_local(), SPEED);
+    let now = clock::now();
+    let settings = Settings { keys: settings.keys, ..demo_settings() };
+    let countdowns = TIMERS.iter().enumerate()
+        .map(|(index, (name, minutes))| {
+            Countdown::new(now + TimeDelta::minutes(*minutes), Some(name.to_string()), settings.theme.color(index), Vec::new())
+        })
+        .collect();
+
+    let (updates, receiver) = mpsc::channel();
+    thread::spawn(move || tour(updates));
+    let terminal = ratatui::init();
+    let app_result = App::new(countdowns, settings).watch_settings(receiver).run(terminal);
+    ratatui::restore();
+
+    println!("That was endzeit with stacked gauges, the flash at halfway and on finish, custom themes and the one line ticker.");
+    println!("See `endzeit --help` for the options behind them.");
+    app_result
+}
+
+/// Sends the settings of every stage when it is due.
+fn tour(updates: Sender<Result<Settings, String>>) {
+    let mut elapsed = 0;
+    for (at, stage) in STAGES {
+        thread::sleep(Duration::from_secs(at - elapsed));
+        elapsed = at;
+        if updates.send(Ok(stage.settings())).is_err() {
+            return;
+        }
+    }
+}
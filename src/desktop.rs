@@ -0,0 +1,52 @@
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+use notify_rust::Notification;
+
+use crate::i18n::Language;
+
+/// Played with completion notifications in the macOS Notification Center, one of the system sounds.
+#[cfg(target_os = "macos")]
+const MACOS_SOUND: &str = "Glass";
+
+/// Raises a desktop notification for the finished timer `index` called `name`, with how late it is
+/// if it ended more than a second after its target. Where notifications have buttons (Linux and the
+/// BSDs) it offers to snooze the timer for `snooze`, which sends `index` to `snoozes`. On macOS it goes
+/// to the Notification Center with a sound.
+pub fn finished(name: &str, overshoot: i64, language: Language, index: usize, snooze: Duration, snoozes: Sender<usize>) {
+    let mut body = language.finished(name);
+    if overshoot > 1 {
+        body.push_str(&format!(", {}", language.late(overshoot as u64)));
+    }
+    let mut notification = Notification::new();
+    notification.appname("endzeit").summary(name).body(&body);
+
+    // Showing talks to the notification daemon and waiting for a button blocks, neither may hold up the countdown
+    thread::spawn(move || {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            notification.action("snooze", &language.snooze(snooze.as_secs())).action("dismiss", language.dismiss());
+            if let Ok(handle) = notification.show() {
+                handle.wait_for_action(|action| {
+                    if action == "snooze" {
+                        let _ = snoozes.send(index);
+                    }
+                });
+            }
+        }
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        {
+            let _ = (index, snooze, snoozes);
+            #[cfg(target_os = "macos")]
+            notification.sound_name(MACOS_SOUND);
+            let _ = notification.show();
+        }
+    });
+}
+
+/// Raises a plain desktop notification titled with the timer's `name`, for warnings before it finishes.
+pub fn warning(name: &str, message: &str) {
+    let mut notification = Notification::new();
+    notification.appname("endzeit").summary(name).body(message);
+    thread::spawn(move || notification.show().map(|_| ()));
+}
@@ -0,0 +1,172 @@
+#[cfg(unix)]
+use std::process::{
+    Command,
+    Stdio
+};
+
+#[cfg(unix)]
+use crate::exec::find_program;
+
+/// The GNOME setting that shows notifications as banners, off is Do Not Disturb.
+#[cfg(all(unix, not(target_os = "macos")))]
+const GNOME_BANNERS: [&str; 2] = ["org.gnome.desktop.notifications", "show-banners"];
+/// The shortcuts that turn a Focus on and off, macOS has no other way to script it.
+#[cfg(target_os = "macos")]
+const SHORTCUT_ON: &str = "endzeit DND on";
+#[cfg(target_os = "macos")]
+const SHORTCUT_OFF: &str = "endzeit DND off";
+
+/// The state of Focus Assist, a profile number: 0 off, 1 priority only, 2 alarms only.
+#[cfg(windows)]
+const WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED: u64 = 0x0d83_063e_a3bf_1c75;
+#[cfg(windows)]
+const ALARMS_ONLY: u32 = 2;
+
+#[cfg(windows)]
+#[link(name = "ntdll")]
+extern "system" {
+    fn NtQueryWnfStateData(
+        state_name: *const u64,
+        type_id: *const u8,
+        explicit_scope: *const u8,
+        change_stamp: *mut u32,
+        buffer: *mut u32,
+        buffer_size: *mut u32,
+    ) -> i32;
+    fn NtUpdateWnfStateData(
+        state_name: *const u64,
+        buffer: *const u32,
+        length: u32,
+        type_id: *const u8,
+        explicit_scope: *const u8,
+        matching_change_stamp: u32,
+        check_stamp: u32,
+    ) -> i32;
+}
+
+/// `--dnd`: Do Not Disturb while it's held, so a focus session goes without notifications. The
+/// setting from before comes back on drop.
+pub struct DoNotDisturb {
+    restore: Restore,
+}
+
+enum Restore {
+    /// Whether GNOME showed banners before
+    #[cfg(all(unix, not(target_os = "macos")))]
+    Banners(String),
+    /// The shortcut that turns the Focus off again
+    #[cfg(target_os = "macos")]
+    Shortcut,
+    /// The profile of Focus Assist before
+    #[cfg(windows)]
+    FocusAssist(u32),
+}
+
+impl DoNotDisturb {
+    /// Turns Do Not Disturb on in the way of the platform: the banners of GNOME, a shortcut for
+    /// Focus on macOS and Focus Assist on Windows.
+    pub fn enable() -> Result<Self, String> {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            let Some(banners) = gsettings(&["get", GNOME_BANNERS[0], GNOME_BANNERS[1]]) else {
+                return Err("--dnd needs GNOME's gsettings".to_string());
+            };
+            if gsettings(&["set", GNOME_BANNERS[0], GNOME_BANNERS[1], "false"]).is_none() {
+                return Err("GNOME refused to turn on Do Not Disturb".to_string());
+            }
+            Ok(Self { restore: Restore::Banners(banners) })
+        }
+        #[cfg(target_os = "macos")]
+        {
+            if !find_program("shortcuts") {
+                return Err("--dnd needs the Shortcuts of macOS 12 or later".to_string());
+            }
+            if !shortcut(SHORTCUT_ON) {
+                return Err(format!("--dnd needs the shortcuts \"{}\" and \"{}\"", SHORTCUT_ON, SHORTCUT_OFF));
+            }
+            Ok(Self { restore: Restore::Shortcut })
+        }
+        #[cfg(windows)]
+        {
+            let Some(profile) = focus_assist() else {
+                return Err("Focus Assist isn't available".to_string());
+            };
+            if !set_focus_assist(ALARMS_ONLY) {
+                return Err("Windows refused to turn on Focus Assist".to_string());
+            }
+            Ok(Self { restore: Restore::FocusAssist(profile) })
+        }
+    }
+}
+
+impl Drop for DoNotDisturb {
+    fn drop(&mut self) {
+        match &self.restore {
+            #[cfg(all(unix, not(target_os = "macos")))]
+            Restore::Banners(banners) => {
+                gsettings(&["set", GNOME_BANNERS[0], GNOME_BANNERS[1], banners]);
+            }
+            #[cfg(target_os = "macos")]
+            Restore::Shortcut => {
+                shortcut(SHORTCUT_OFF);
+            }
+            #[cfg(windows)]
+            Restore::FocusAssist(profile) => {
+                set_focus_assist(*profile);
+            }
+        }
+    }
+}
+
+/// Runs gsettings and returns what it printed, None if it failed.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn gsettings(args: &[&str]) -> Option<String> {
+    if !find_program("gsettings") {
+        return None;
+    }
+    let output = Command::new("gsettings").args(args).stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Runs a shortcut of the Shortcuts app and waits for it, false if it failed or doesn't exist.
+#[cfg(target_os = "macos")]
+fn shortcut(name: &str) -> bool {
+    Command::new("shortcuts").args(["run", name])
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .status().is_ok_and(|status| status.success())
+}
+
+/// The profile of Focus Assist, 0 if it was never set.
+#[cfg(windows)]
+fn focus_assist() -> Option<u32> {
+    let mut change_stamp = 0;
+    let mut profile = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        NtQueryWnfStateData(
+            &WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED,
+            std::ptr::null(),
+            std::ptr::null(),
+            &mut change_stamp,
+            &mut profile,
+            &mut size,
+        )
+    };
+    (status >= 0).then_some(profile)
+}
+
+#[cfg(windows)]
+fn set_focus_assist(profile: u32) -> bool {
+    let status = unsafe {
+        NtUpdateWnfStateData(
+            &WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED,
+            &profile,
+            std::mem::size_of::<u32>() as u32,
+            std::ptr::null(),
+            std::ptr::null(),
+            0,
+            0,
+        )
+    };
+    status >= 0
+}
@@ -0,0 +1,231 @@
+use chrono::{
+    NaiveDateTime,
+    TimeDelta
+};
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{
+        Event,
+        KeyEventKind,
+        MouseEventKind
+    },
+    layout::{
+        Constraint,
+        Layout,
+        Rect
+    },
+    style::{
+        Modifier,
+        Style
+    },
+    widgets::Widget,
+};
+
+use crate::countdown::Countdown;
+use crate::keys::KeyBindings;
+use crate::spec::parse_target_spec;
+use crate::theme::Theme;
+
+/// How far the add/subtract keys and the mouse wheel move a target.
+const SMALL_STEP: TimeDelta = TimeDelta::minutes(1);
+const BIG_STEP: TimeDelta = TimeDelta::minutes(5);
+/// Rows of a gauge with its name above it.
+const GAUGE_HEIGHT: u16 = 3;
+
+/// Countdowns driven by the event loop of a host application:
+///
+/// ```ignore
+/// let mut endzeit = Endzeit::from_specs(["+25m,name=Focus", "17:00,name=Standup"])?;
+/// loop {
+///     terminal.draw(|frame| endzeit.render(area, frame.buffer_mut()))?;
+///     for index in endzeit.tick() {
+///         // countdown `index` just finished
+///     }
+///     if event::poll(timeout)? {
+///         let event = event::read()?;
+///         if !endzeit.handle_event(&event) {
+///             // not meant for the countdowns
+///         }
+///     }
+/// }
+/// ```
+///
+/// The keys are those of the endzeit TUI, see [`KeyBindings`]: pause, add and subtract time, and
+/// switching between countdowns. Quitting is left to the host.
+pub struct Endzeit {
+    countdowns: Vec<Countdown>,
+    selected: usize,
+    keys: KeyBindings,
+    theme: Theme,
+    /// Where the countdowns were rendered last, for the mouse
+    area: Rect,
+}
+
+impl Endzeit {
+    pub fn new() -> Self {
+        Self {
+            countdowns: Vec::new(),
+            selected: 0,
+            keys: KeyBindings::default(),
+            theme: Theme::default(),
+            area: Rect::default(),
+        }
+    }
+
+    /// Countdowns in the format of `endzeit --target`, like `+25m,name=Focus` or `2025-12-31 23:59`.
+    pub fn from_specs<'a>(specs: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut endzeit = Self::new();
+        for spec in specs {
+            endzeit.add_spec(spec)?;
+        }
+        Ok(endzeit)
+    }
+
+    pub fn with_keys(mut self, keys: KeyBindings) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Adds a countdown to `target`, it is not selected.
+    pub fn add(&mut self, target: NaiveDateTime, name: Option<String>) {
+        let color = self.theme.color(self.countdowns.len());
+        self.countdowns.push(Countdown::new(target, name, color, Vec::new()));
+    }
+
+    /// Adds a countdown in the format of `endzeit --target`. An `execute=` command runs when it finishes.
+    pub fn add_spec(&mut self, spec: &str) -> Result<(), String> {
+        let target_spec = parse_target_spec(spec)?;
+        let color = self.theme.color(self.countdowns.len());
+        self.countdowns.push(Countdown::from_spec(target_spec, color));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.countdowns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.countdowns.is_empty()
+    }
+
+    /// The countdown keys act on.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Whole seconds left on countdown `index`.
+    pub fn remaining(&self, index: usize) -> Option<u64> {
+        Some(self.countdowns.get(index)?.get_remaining_time().seconds())
+    }
+
+    pub fn is_paused(&self, index: usize) -> bool {
+        self.countdowns.get(index).is_some_and(Countdown::is_paused)
+    }
+
+    pub fn toggle_pause(&mut self, index: usize) {
+        if let Some(countdown) = self.countdowns.get_mut(index) {
+            countdown.toggle_pause();
+        }
+    }
+
+    /// Moves the target of countdown `index`, a negative `delta` brings it closer.
+    pub fn adjust(&mut self, index: usize, delta: TimeDelta) {
+        if let Some(countdown) = self.countdowns.get_mut(index) {
+            countdown.adjust(delta);
+        }
+    }
+
+    /// Completes the countdowns that reached their target, starting their commands in the background.
+    /// Call it once per frame, it returns the ones that finished since the last call.
+    pub fn tick(&mut self) -> Vec<usize> {
+        let mut finished = Vec::new();
+        for (index, countdown) in self.countdowns.iter_mut().enumerate() {
+            if countdown.complete_if_finished() {
+                finished.push(index);
+            }
+            // The host has no output pane or retries, what the commands left behind is dropped
+            countdown.collect_hooks();
+            countdown.take_output();
+            countdown.take_failed_hooks();
+        }
+        finished
+    }
+
+    /// Handles a key or mouse event, returns false if it wasn't for the countdowns so the host can use it.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        if self.countdowns.is_empty() {
+            return false;
+        }
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                let code = key.code;
+                let keys = &self.keys;
+                if code == keys.pause {
+                    self.toggle_pause(self.selected);
+                } else if code == keys.add_time {
+                    self.adjust(self.selected, SMALL_STEP);
+                } else if code == keys.subtract_time {
+                    self.adjust(self.selected, -SMALL_STEP);
+                } else if code == keys.add_time_big {
+                    self.adjust(self.selected, BIG_STEP);
+                } else if code == keys.subtract_time_big {
+                    self.adjust(self.selected, -BIG_STEP);
+                } else if code == keys.next {
+                    self.selected = (self.selected + 1) % self.countdowns.len();
+                } else if code == keys.previous {
+                    self.selected = (self.selected + self.countdowns.len() - 1) % self.countdowns.len();
+                } else {
+                    return false;
+                }
+                true
+            }
+            Event::Mouse(mouse) => {
+                let Some(index) = self.gauge_at(mouse.column, mouse.row) else {
+                    return false;
+                };
+                match mouse.kind {
+                    MouseEventKind::Down(_) => {
+                        self.selected = index;
+                        self.toggle_pause(index);
+                    }
+                    MouseEventKind::ScrollUp => self.adjust(index, SMALL_STEP),
+                    MouseEventKind::ScrollDown => self.adjust(index, -SMALL_STEP),
+                    _ => return false,
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draws the countdowns below each other into `area` of the host's buffer.
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.area = area;
+        for (index, area) in self.gauge_areas(area) {
+            self.countdowns[index].render(area, buf);
+            if index == self.selected && self.countdowns.len() > 1 {
+                buf.set_string(area.x, area.y, "▶", Style::default().add_modifier(Modifier::BOLD));
+            }
+        }
+    }
+
+    /// One area per countdown, as many as fit.
+    fn gauge_areas(&self, area: Rect) -> Vec<(usize, Rect)> {
+        let fitting = (area.height / GAUGE_HEIGHT).max(1) as usize;
+        let count = self.countdowns.len().min(fitting);
+        let areas = Layout::vertical((0..count).map(|_| Constraint::Length(GAUGE_HEIGHT))).split(area);
+        areas.iter().copied().enumerate().collect()
+    }
+
+    fn gauge_at(&self, column: u16, row: u16) -> Option<usize> {
+        self.gauge_areas(self.area).into_iter()
+            .find(|(_, area)| area.contains((column, row).into()))
+            .map(|(index, _)| index)
+    }
+}
+
+impl Default for Endzeit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
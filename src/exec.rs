@@ -0,0 +1,473 @@
+use std::env;
+use std::io::{
+    self,
+    Read
+};
+use std::path::{
+    Path,
+    PathBuf
+};
+use std::process::{
+    Child,
+    Command,
+    ExitStatus,
+    Stdio
+};
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering
+};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+use clap::ValueEnum;
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::countdown::Countdown;
+use crate::units::format_seconds;
+
+/// Shell builtins and keywords that won't be found on PATH.
+const BUILTINS: [&str; 21] = [
+    ":", ".", "[", "!", "{", "case", "cd", "echo", "eval", "exec", "exit", "export", "false",
+    "for", "if", "printf", "set", "test", "true", "until", "while",
+];
+
+/// Where and how commands run unless their timer says otherwise, from --execute-cwd and --execute-shell.
+static DEFAULT_LAUNCHER: OnceLock<Launcher> = OnceLock::new();
+
+/// How often a command with `--execute-timeout` is checked on.
+const WAIT_POLL: Duration = Duration::from_millis(50);
+
+/// Set while the TUI owns the terminal, see `capture_output`.
+static CAPTURE_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// The interpreter hook commands are run with.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    /// sh -c, the default on Unix
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+    /// powershell -NoProfile -Command, pwsh outside of Windows
+    Powershell,
+    /// cmd /C, the default on Windows
+    Cmd,
+    /// Split the command into words and start the program directly
+    None,
+}
+
+/// `--then`: what the machine does once a timer finished and its commands ran.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerAction {
+    Shutdown,
+    Suspend,
+    Hibernate,
+    Lock,
+}
+
+impl PowerAction {
+    /// The program and arguments that do it on this platform: systemd outside of macOS and Windows.
+    /// None where there is no way, macOS can't be sent to hibernate.
+    pub fn argv(self) -> Option<Vec<String>> {
+        let argv: &[&str] = if cfg!(target_os = "windows") {
+            match self {
+                PowerAction::Shutdown => &["shutdown", "/s", "/t", "0"],
+                PowerAction::Suspend => &["rundll32.exe", "powrprof.dll,SetSuspendState", "0,1,0"],
+                PowerAction::Hibernate => &["shutdown", "/h"],
+                PowerAction::Lock => &["rundll32.exe", "user32.dll,LockWorkStation"],
+            }
+        } else if cfg!(target_os = "macos") {
+            match self {
+                PowerAction::Shutdown => &["osascript", "-e", "tell application \"System Events\" to shut down"],
+                PowerAction::Suspend => &["pmset", "sleepnow"],
+                PowerAction::Hibernate => return None,
+                // Locks as long as the password is asked for right after the display sleeps
+                PowerAction::Lock => &["pmset", "displaysleepnow"],
+            }
+        } else {
+            match self {
+                PowerAction::Shutdown => &["systemctl", "poweroff"],
+                PowerAction::Suspend => &["systemctl", "suspend"],
+                PowerAction::Hibernate => &["systemctl", "hibernate"],
+                PowerAction::Lock => &["loginctl", "lock-session"],
+            }
+        };
+        Some(argv.iter().map(|word| word.to_string()).collect())
+    }
+}
+
+impl Shell {
+    fn platform_default() -> Self {
+        if cfg!(target_os = "windows") { Shell::Cmd } else { Shell::Sh }
+    }
+
+    /// The program and the flags that make it run a command line, None starts the program directly.
+    fn invocation(self) -> Option<(&'static str, &'static [&'static str])> {
+        match self {
+            Shell::Sh => Some(("sh", &["-c"])),
+            Shell::Bash => Some(("bash", &["-c"])),
+            Shell::Zsh => Some(("zsh", &["-c"])),
+            Shell::Fish => Some(("fish", &["-c"])),
+            // Only Windows ships Windows PowerShell, elsewhere it's PowerShell 7
+            Shell::Powershell if cfg!(target_os = "windows") => Some(("powershell", &["-NoProfile", "-Command"])),
+            Shell::Powershell => Some(("pwsh", &["-NoProfile", "-Command"])),
+            Shell::Cmd => Some(("cmd", &["/C"])),
+            Shell::None => None,
+        }
+    }
+
+    /// Whether `-n` checks the syntax without running anything.
+    fn checks_syntax(self) -> bool {
+        matches!(self, Shell::Sh | Shell::Bash | Shell::Zsh | Shell::Fish)
+    }
+}
+
+/// Where and through which shell a command is started. Unset fields fall back to the defaults
+/// given with `set_default_launcher`, then to the current directory and the platform's shell.
+#[derive(Clone, Default)]
+pub struct Launcher {
+    pub cwd: Option<PathBuf>,
+    pub shell: Option<Shell>,
+    /// Let commands go instead of waiting for them, see `start`
+    pub detach: bool,
+    /// Kill commands that are still running after this long, see `start`
+    pub timeout: Option<Duration>,
+}
+
+impl Launcher {
+    fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref().or_else(|| DEFAULT_LAUNCHER.get().and_then(|launcher| launcher.cwd.as_deref()))
+    }
+
+    fn shell(&self) -> Shell {
+        self.shell
+            .or_else(|| DEFAULT_LAUNCHER.get().and_then(|launcher| launcher.shell))
+            .unwrap_or_else(Shell::platform_default)
+    }
+
+    fn detaches(&self) -> bool {
+        self.detach || DEFAULT_LAUNCHER.get().is_some_and(|launcher| launcher.detach)
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout.or_else(|| DEFAULT_LAUNCHER.get().and_then(|launcher| launcher.timeout))
+    }
+
+    /// Runs `command` and waits for it, with `--execute-timeout` at most that long before it's
+    /// killed along with everything it started. With `--execute-detach` it's started in a session of
+    /// its own with its input and output going nowhere and left running, then there is no status.
+    pub fn start(&self, command: &mut Command) -> io::Result<Option<Ran>> {
+        if !self.detaches() {
+            return self.wait(command).map(Some);
+        }
+        command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        #[cfg(unix)]
+        unsafe {
+            use std::os::unix::process::CommandExt;
+            // Neither the terminal closing nor endzeit quitting reaches it then
+            command.pre_exec(|| {
+                libc::setsid();
+                Ok(())
+            });
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const DETACHED_PROCESS: u32 = 0x0000_0008;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+        }
+        let mut child = command.spawn()?;
+        // Reaped in the background, so it doesn't linger as a zombie while endzeit keeps running
+        thread::spawn(move || child.wait());
+        Ok(None)
+    }
+
+    fn wait(&self, command: &mut Command) -> io::Result<Ran> {
+        if capturing() {
+            // Input is the TUI's, it would take away key presses
+            command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+        let timeout = self.timeout();
+        #[cfg(unix)]
+        if timeout.is_some() {
+            use std::os::unix::process::CommandExt;
+            // A group of its own, so killing it reaches what the shell started as well
+            command.process_group(0);
+        }
+        let mut child = command.spawn()?;
+        let stdout = child.stdout.take().map(read_all);
+        let stderr = child.stderr.take().map(read_all);
+        let (status, timed_out) = match timeout {
+            Some(timeout) => wait_at_most(&mut child, timeout)?,
+            None => (child.wait()?, None),
+        };
+        let output = match (stdout, stderr) {
+            (Some(stdout), Some(stderr)) => Some(stdout.join().unwrap_or_default() + &stderr.join().unwrap_or_default()),
+            _ => None,
+        };
+        Ok(Ran { status, output, timed_out })
+    }
+
+    /// Builds the process for `command_line`, ready to be started.
+    pub fn command(&self, command_line: &str) -> io::Result<Command> {
+        let Some((shell, flags)) = self.shell().invocation() else {
+            return self.direct(&split_words(command_line));
+        };
+        let mut command = Command::new(shell);
+        command.args(flags).arg(command_line);
+        if let Some(cwd) = self.cwd() {
+            command.current_dir(cwd);
+        }
+        Ok(command)
+    }
+
+    /// Builds the process for a program and its arguments, started without a shell so none of
+    /// them is ever interpreted.
+    pub fn direct(&self, argv: &[String]) -> io::Result<Command> {
+        let Some((program, args)) = argv.split_first() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+        };
+        let mut command = Command::new(program);
+        command.args(args);
+        if let Some(cwd) = self.cwd() {
+            command.current_dir(cwd);
+        }
+        Ok(command)
+    }
+
+    /// `command_line` as a line of a POSIX shell script that runs it the way `command` would.
+    pub fn script_line(&self, command_line: &str) -> String {
+        match self.shell().invocation() {
+            Some((shell, flags)) => self.in_cwd(format!("{} {} {}", shell, flags.join(" "), shell_quote(command_line))),
+            None => self.direct_script_line(&split_words(command_line)),
+        }
+    }
+
+    /// A program and its arguments as a line of a POSIX shell script, every word quoted.
+    pub fn direct_script_line(&self, argv: &[String]) -> String {
+        self.in_cwd(argv.iter().map(|word| shell_quote(word)).collect::<Vec<_>>().join(" "))
+    }
+
+    fn in_cwd(&self, line: String) -> String {
+        match self.cwd() {
+            Some(cwd) => format!("(cd {} && {})", shell_quote(&cwd.to_string_lossy()), line),
+            None => line,
+        }
+    }
+
+    /// Runs `command_line` and waits for it to finish.
+    pub fn run(&self, command_line: &str) -> io::Result<ExitStatus> {
+        self.command(command_line)?.status()
+    }
+
+}
+
+/// A command that ran to its end.
+pub struct Ran {
+    pub status: ExitStatus,
+    /// What it wrote to stdout and then stderr, if it was captured
+    pub output: Option<String>,
+    /// The timeout it was killed after
+    pub timed_out: Option<Duration>,
+}
+
+impl Ran {
+    /// Why the command failed, None if it succeeded.
+    pub fn failure(&self) -> Option<String> {
+        match self.timed_out {
+            Some(timeout) => Some(format!("killed after {}", format_seconds(timeout.as_secs()))),
+            None if self.status.success() => None,
+            None => Some(format!("failed ({})", self.status)),
+        }
+    }
+}
+
+/// Reads `pipe` to its end on a thread of its own, so a full pipe can't block the child.
+fn read_all(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+/// Waits for `child` up to `timeout`, then kills it, on Unix with its whole process group. Returns
+/// its status and the timeout if it was killed.
+fn wait_at_most(child: &mut Child, timeout: Duration) -> io::Result<(ExitStatus, Option<Duration>)> {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, None));
+        }
+        thread::sleep(WAIT_POLL);
+    }
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = child.kill();
+    Ok((child.wait()?, Some(timeout)))
+}
+
+/// Captures the output of the commands that are waited for instead of letting it through to the
+/// terminal, where it would land in the middle of the TUI.
+pub fn capture_output(capture: bool) {
+    CAPTURE_OUTPUT.store(capture, Ordering::Relaxed);
+}
+
+/// Whether output is captured, see `capture_output`.
+pub fn capturing() -> bool {
+    CAPTURE_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Sets the defaults used by launchers that leave the directory or shell open, can only be set once.
+pub fn set_default_launcher(launcher: Launcher) {
+    let _ = DEFAULT_LAUNCHER.set(launcher);
+}
+
+/// Quotes `word` for a POSIX shell, single quotes with the ones inside escaped.
+pub fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Splits a command into words at whitespace, single and double quotes group words.
+fn split_words(command_line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in command_line.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// How hook commands are checked at startup.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookValidation {
+    /// Don't check
+    Off,
+    /// Note problems on the gauge
+    Warn,
+    /// Refuse to start
+    Strict,
+}
+
+impl HookValidation {
+    /// Checks the completion commands of `countdowns` and the `shared_hooks` that run for all of them.
+    /// Strict fails on the first problem, warn notes problems on the affected gauges.
+    pub fn check(self, countdowns: &mut [Countdown], shared_hooks: &[&str]) -> Result<(), String> {
+        if self == HookValidation::Off {
+            return Ok(());
+        }
+        let shared_error = shared_hooks.iter().find_map(|hook| validate_command(hook, &Launcher::default()).err());
+        for countdown in countdowns {
+            let error = countdown.execute_commands.iter()
+                .find_map(|command| validate_command(command, &countdown.launcher).err())
+                .or_else(|| countdown.exec_direct.as_deref().and_then(|argv| validate_direct(argv, &countdown.launcher).err()))
+                .or_else(|| countdown.then.and_then(PowerAction::argv).and_then(|argv| validate_direct(&argv, &countdown.launcher).err()))
+                .or_else(|| shared_error.clone());
+            let Some(error) = error else {
+                continue;
+            };
+            if self == HookValidation::Strict {
+                return Err(format!("Refusing to start, hook would fail: {}", error));
+            }
+            countdown.set_note(format!("hook would fail: {}", error));
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a command can run with `launcher`: its directory exists, its shell syntax parses and its program exists.
+pub fn validate_command(command: &str, launcher: &Launcher) -> Result<(), String> {
+    if let Some(cwd) = launcher.cwd().filter(|cwd| !cwd.is_dir()) {
+        return Err(format!("`{}` is not a directory", cwd.display()));
+    }
+    let shell = launcher.shell();
+    if let Some((program, _)) = shell.invocation().filter(|_| shell.checks_syntax()) {
+        let output = Command::new(program)
+            .args(["-n", "-c", command])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("`{}` doesn't parse: {}", command, error.trim()));
+        }
+    }
+
+    let words = split_words(command);
+    // Leading VAR=value assignments only set the environment of the program, which needs a shell
+    let Some(program) = words.iter().find(|word| shell == Shell::None || !word.contains('=') || word.starts_with('=')) else {
+        return Ok(());
+    };
+    // Subshells and variables can't be resolved without running them
+    if program.starts_with(['(', '$']) || BUILTINS.contains(&program.as_str()) || find_program(program) {
+        Ok(())
+    } else {
+        Err(format!("`{}` not found", program))
+    }
+}
+
+/// Checks that a program started without a shell can run with `launcher`: its directory exists and the program exists.
+pub fn validate_direct(argv: &[String], launcher: &Launcher) -> Result<(), String> {
+    if let Some(cwd) = launcher.cwd().filter(|cwd| !cwd.is_dir()) {
+        return Err(format!("`{}` is not a directory", cwd.display()));
+    }
+    match argv.first() {
+        Some(program) if find_program(program) => Ok(()),
+        Some(program) => Err(format!("`{}` not found", program)),
+        None => Err("empty command".to_string()),
+    }
+}
+
+/// Checks whether `program` is a file, or can be found on PATH if it is just a name.
+pub fn find_program(program: &str) -> bool {
+    let candidates = |path: &Path| [path.to_path_buf(), path.with_extension(env::consts::EXE_EXTENSION)];
+    if program.contains(['/', '\\']) {
+        return candidates(Path::new(program)).iter().any(|path| path.is_file());
+    }
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| candidates(&dir.join(program)).iter().any(|path| path.is_file()))
+    })
+}
+
+/// Checks whether a process with the given pid is still running.
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    // Signal 0 only checks for existence, EPERM means it exists but belongs to someone else
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Checks whether a process with the given pid is still running.
+#[cfg(windows)]
+pub fn is_process_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/NH", "/FI", &format!("PID eq {}", pid)])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+        .unwrap_or(false)
+}
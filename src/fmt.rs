@@ -0,0 +1,53 @@
+use std::io::{
+    self,
+    BufRead
+};
+use color_eyre::Result;
+
+use crate::countdown::TimeRemaining;
+use crate::spec::parse_iso_duration;
+use crate::template::Template;
+
+/// Reads one duration per line from stdin (seconds or ISO 8601 like `PT2H35M`)
+/// and prints it humanized, either like the gauge label or through `format`.
+pub fn run(format: Option<String>) -> Result<()> {
+    let template = match format.as_deref().map(Template::parse) {
+        Some(Ok(template)) => Some(template),
+        Some(Err(err)) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    let mut failed = false;
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+
+        let seconds = match input.parse::<u64>() {
+            Ok(seconds) => seconds,
+            Err(_) => match parse_iso_duration(input) {
+                Ok(seconds) => seconds,
+                Err(err) => {
+                    eprintln!("{}: {}", input, err);
+                    failed = true;
+                    continue;
+                }
+            },
+        };
+
+        match &template {
+            Some(template) => println!("{}", template.render(seconds)),
+            None => println!("{}", TimeRemaining::from_seconds(seconds).format()),
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
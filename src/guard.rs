@@ -0,0 +1,38 @@
+use chrono::NaiveDateTime;
+use color_eyre::Result;
+
+use crate::clock;
+use crate::countdown::TimeRemaining;
+use crate::spec::parse_when;
+
+/// For git hooks: fails while the freeze given by `until` is in effect. Without `deny_after`
+/// the freeze lasts until that point, with it the freeze starts there.
+pub fn run(until: &str, deny_after: bool) -> Result<()> {
+    let deadline = match parse_when(until) {
+        Ok((_, deadline)) => deadline,
+        Err(err) => {
+            eprintln!("{}: {}", until, err);
+            std::process::exit(1);
+        }
+    };
+
+    let now = clock::now();
+    let when = deadline.format("%Y-%m-%d %H:%M:%S");
+    match (deny_after, deadline > now) {
+        (false, true) => deny(&format!("Code freeze until {}, {} left", when, time_between(now, deadline))),
+        (true, false) => deny(&format!("Code freeze since {} ({} ago)", when, time_between(deadline, now))),
+        (true, true) => eprintln!("Code freeze starts {}, {} left", when, time_between(now, deadline)),
+        (false, false) => {}
+    }
+    Ok(())
+}
+
+fn deny(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+fn time_between(from: NaiveDateTime, to: NaiveDateTime) -> String {
+    let seconds = ((to - from).num_milliseconds() as u64).div_ceil(1000);
+    TimeRemaining::from_seconds(seconds).format()
+}
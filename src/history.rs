@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::fs::{
+    self,
+    OpenOptions
+};
+use std::io::Write;
+use std::path::PathBuf;
+use chrono::{
+    Datelike,
+    NaiveDate,
+    NaiveDateTime,
+    TimeDelta
+};
+use clap::{
+    Args,
+    ValueEnum
+};
+use color_eyre::Result;
+use ratatui::crossterm::{
+    style::{
+        Color,
+        Stylize
+    },
+    terminal
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::clock;
+use crate::countdown::Countdown;
+use crate::machine::{
+    HistoryEntry,
+    Machine
+};
+use crate::spec::parse_date;
+use crate::state::state_dir;
+
+/// Weeks shown by the heatmap when the terminal is wide enough, a year and a bit.
+const HEATMAP_WEEKS: usize = 53;
+/// Columns taken by the weekday labels in front of the heatmap.
+const LABEL_WIDTH: usize = 4;
+/// Colors for days without timers and the four levels of activity, from the 256 color palette.
+const LEVEL_COLORS: [u8; 5] = [238, 22, 28, 34, 46];
+
+/// A finished or aborted timer, one JSON line of `<state dir>/endzeit/history/history.jsonl`.
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    pub name: Option<String>,
+    pub target: NaiveDateTime,
+    /// When the timer actually ended
+    pub finished: NaiveDateTime,
+    /// The WHEN part it was started with, like `+25m`
+    #[serde(default)]
+    pub when: Option<String>,
+    /// How long it was set to run, in seconds
+    #[serde(default)]
+    pub duration: Option<u64>,
+    /// Cancelled, or endzeit quit before it finished
+    #[serde(default)]
+    pub aborted: bool,
+    /// Seconds from the target to the actual end, negative for timers aborted early
+    #[serde(default)]
+    pub overshoot: i64,
+    /// Whether its command ran
+    #[serde(default)]
+    pub hook_ran: bool,
+}
+
+impl Entry {
+    /// The entry for `countdown` ending now.
+    pub fn new(countdown: &Countdown, aborted: bool) -> Self {
+        let finished = clock::now();
+        Self {
+            name: countdown.name.clone(),
+            target: countdown.target(),
+            finished,
+            when: countdown.when().map(str::to_string),
+            duration: Some(countdown.initial_duration()),
+            aborted,
+            overshoot: (finished - countdown.target()).num_seconds(),
+            hook_ran: countdown.hook_ran(),
+        }
+    }
+}
+
+impl Entry {
+    /// A `--target` spec that runs the timer again for as long as it ran then, with its name.
+    pub fn rerun_spec(&self) -> Option<String> {
+        let mut spec = match (self.when.as_deref(), self.duration) {
+            (Some(when), _) if when.starts_with('+') => when.to_string(),
+            (_, Some(duration)) if duration > 0 => format!("+{}s", duration),
+            _ => return None,
+        };
+        if let Some(name) = &self.name {
+            spec.push_str(&format!(",name={}", name));
+        }
+        Some(spec)
+    }
+}
+
+/// File formats of `endzeit history export`.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// A header line and one row per timer, for spreadsheets
+    Csv,
+    /// An array of objects with the fields of the `--machine v1` history entries
+    Json,
+}
+
+/// Which entries to show or export.
+#[derive(Args, Default)]
+pub struct HistoryFilter {
+    /// Only timers whose name contains this, ignoring case
+    #[clap(long)]
+    name: Option<String>,
+
+    /// Only timers that ended on or after this day, YYYY-MM-DD
+    #[clap(long, value_parser = parse_date)]
+    since: Option<NaiveDate>,
+
+    /// Only timers that ended on or before this day, YYYY-MM-DD
+    #[clap(long, value_parser = parse_date)]
+    until: Option<NaiveDate>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let day = entry.finished.date();
+        let name_matches = self.name.as_deref().is_none_or(|name| {
+            entry.name.as_deref().is_some_and(|entry_name| entry_name.to_lowercase().contains(&name.to_lowercase()))
+        });
+        name_matches && self.since.is_none_or(|since| day >= since) && self.until.is_none_or(|until| day <= until)
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        let name = name.trim();
+        self.name = (!name.is_empty()).then(|| name.to_string());
+    }
+
+    /// The date range as `FROM..TO`, the way `set_dates` reads it.
+    pub fn dates(&self) -> String {
+        let format = |date: Option<NaiveDate>| date.map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default();
+        if self.since.is_none() && self.until.is_none() {
+            return String::new();
+        }
+        format!("{}..{}", format(self.since), format(self.until))
+    }
+
+    /// Reads `FROM..TO` where either side may be empty, or a single day.
+    pub fn set_dates(&mut self, input: &str) -> Result<(), String> {
+        let input = input.trim();
+        let (since, until) = input.split_once("..").unwrap_or((input, input));
+        let parse = |date: &str| (!date.trim().is_empty()).then(|| parse_date(date.trim())).transpose();
+        self.since = parse(since)?;
+        self.until = parse(until)?;
+        Ok(())
+    }
+
+    /// The active filters for a title, empty without any.
+    pub fn describe(&self) -> String {
+        let mut description = String::new();
+        if let Some(name) = &self.name {
+            description.push_str(&format!(", name contains '{}'", name));
+        }
+        let dates = self.dates();
+        if !dates.is_empty() {
+            description.push_str(&format!(", ended {}", dates));
+        }
+        description
+    }
+}
+
+fn path() -> Result<PathBuf, String> {
+    Ok(state_dir("history")?.join("history.jsonl"))
+}
+
+/// Appends a finished timer to the history.
+pub fn append(entry: &Entry) -> Result<(), String> {
+    let path = path()?;
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Reads the whole history, oldest first. Lines that don't parse are skipped.
+pub fn load() -> Result<Vec<Entry>, String> {
+    let path = path()?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    Ok(contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+/// `endzeit history`: lists the completed timers, or shows them as a heatmap of the last year.
+pub fn run(heatmap: bool, machine: Option<Machine>) -> Result<()> {
+    let entries = load().unwrap_or_else(|err| crate::exit_with(&err));
+    if heatmap {
+        print_heatmap(&entries);
+        return Ok(());
+    }
+    if let Some(machine) = machine {
+        let entries: Vec<HistoryEntry> = entries.iter().map(HistoryEntry::from).collect();
+        machine.print("history", serde_json::json!({ "entries": entries }));
+        return Ok(());
+    }
+    for entry in &entries {
+        let outcome = if entry.aborted {
+            "aborted".to_string()
+        } else if entry.overshoot > 0 {
+            format!("{}s late", entry.overshoot)
+        } else {
+            "on time".to_string()
+        };
+        let hook = if entry.hook_ran { ", command ran" } else { "" };
+        println!("{}  {}  ({}{})", entry.finished.format("%Y-%m-%d %H:%M:%S"), entry.name.as_deref().unwrap_or("-"), outcome, hook);
+    }
+    Ok(())
+}
+
+/// `endzeit history export`: prints the entries passing `filter`, oldest first, with the columns
+/// in a fixed order so scripts and spreadsheets can rely on them.
+pub fn export(filter: &HistoryFilter, format: ExportFormat) -> Result<()> {
+    let entries = load().unwrap_or_else(|err| crate::exit_with(&err));
+    let entries: Vec<HistoryEntry> = entries.iter().filter(|entry| filter.matches(entry)).map(HistoryEntry::from).collect();
+    match format {
+        ExportFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+        ExportFormat::Csv => {
+            println!("{}", HistoryEntry::CSV_HEADER.join(","));
+            for entry in &entries {
+                let record: Vec<String> = entry.csv_record().iter().map(|field| csv_field(field)).collect();
+                println!("{}", record.join(","));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quotes a field that contains a separator, quote or line break, as RFC 4180 has it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Prints a GitHub style grid with a column per week and a row per weekday, darker to brighter
+/// green by the number of timers completed that day.
+fn print_heatmap(entries: &[Entry]) {
+    let mut counts: HashMap<NaiveDate, usize> = HashMap::new();
+    for entry in entries.iter().filter(|entry| !entry.aborted) {
+        *counts.entry(entry.finished.date()).or_default() += 1;
+    }
+
+    let width = terminal::size().map_or(80, |(columns, _)| columns as usize);
+    let weeks = ((width.saturating_sub(LABEL_WIDTH)) / 2).clamp(1, HEATMAP_WEEKS);
+    let today = clock::now().date();
+    // Columns start on Mondays, the last one holds today
+    let first_monday = today - TimeDelta::days(today.weekday().num_days_from_monday() as i64) - TimeDelta::weeks(weeks as i64 - 1);
+    let max = (0..weeks * 7)
+        .map(|offset| counts.get(&(first_monday + TimeDelta::days(offset as i64))).copied().unwrap_or(0))
+        .max()
+        .unwrap_or(0);
+
+    // Month names above the week their first day falls into
+    let mut months = " ".repeat(LABEL_WIDTH);
+    for week in 0..weeks {
+        let monday = first_monday + TimeDelta::weeks(week as i64);
+        let starts_month = (0..7).any(|day| (monday + TimeDelta::days(day)).day() == 1);
+        if starts_month && months.chars().count() <= LABEL_WIDTH + week * 2 {
+            months.push_str(&" ".repeat(LABEL_WIDTH + week * 2 - months.chars().count()));
+            months.push_str(&(monday + TimeDelta::days(6)).format("%b").to_string());
+        }
+    }
+    println!("{}", months);
+
+    for weekday in 0..7 {
+        let label = match weekday {
+            0 => "Mon",
+            2 => "Wed",
+            4 => "Fri",
+            _ => "",
+        };
+        let mut row = format!("{:<width$}", label, width = LABEL_WIDTH);
+        for week in 0..weeks {
+            let day = first_monday + TimeDelta::weeks(week as i64) + TimeDelta::days(weekday);
+            if day > today {
+                break;
+            }
+            let level = level(counts.get(&day).copied().unwrap_or(0), max);
+            row.push_str(&format!("{} ", "■".with(Color::AnsiValue(LEVEL_COLORS[level]))));
+        }
+        println!("{}", row);
+    }
+
+    let total: usize = counts.iter().filter(|(day, _)| **day >= first_monday).map(|(_, count)| count).sum();
+    let mut legend = format!("\n{} timers completed since {}   less ", total, first_monday.format("%Y-%m-%d"));
+    for color in LEVEL_COLORS {
+        legend.push_str(&format!("{} ", "■".with(Color::AnsiValue(color))));
+    }
+    println!("{}more", legend);
+}
+
+/// Buckets a day's count into 0 for none and 1 to 4 relative to the busiest day.
+fn level(count: usize, max: usize) -> usize {
+    if count == 0 || max == 0 {
+        return 0;
+    }
+    (count * 4).div_ceil(max).clamp(1, 4)
+}
@@ -0,0 +1,111 @@
+use std::env;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Language of notification messages.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// English
+    En,
+    /// German
+    De,
+}
+
+impl Language {
+    /// Picks the language from LC_ALL, LC_MESSAGES or LANG, falling back to English.
+    pub fn detect() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        if locale.starts_with("de") {
+            Language::De
+        } else {
+            Language::En
+        }
+    }
+
+    pub fn finished(self, name: &str) -> String {
+        match self {
+            Language::En => format!("{} finished", name),
+            Language::De => format!("{} ist abgelaufen", name),
+        }
+    }
+
+    pub fn halfway(self, name: &str, remaining_seconds: u64) -> String {
+        let remaining = self.duration(remaining_seconds);
+        match self {
+            Language::En => format!("{} is halfway, {} left", name, remaining),
+            Language::De => format!("{} ist zur Hälfte um, {} verbleibend", name, remaining),
+        }
+    }
+
+    /// For `--notify-at`, with the threshold that was reached.
+    pub fn left(self, name: &str, remaining_seconds: u64) -> String {
+        let remaining = self.duration(remaining_seconds);
+        match self {
+            Language::En => format!("{}: {} left", name, remaining),
+            Language::De => format!("{}: noch {}", name, remaining),
+        }
+    }
+
+    pub fn late(self, overshoot_seconds: u64) -> String {
+        let overshoot = self.duration(overshoot_seconds);
+        match self {
+            Language::En => format!("{} late", overshoot),
+            Language::De => format!("{} zu spät", overshoot),
+        }
+    }
+
+    /// The snooze button of desktop notifications.
+    pub fn snooze(self, seconds: u64) -> String {
+        let duration = self.duration(seconds);
+        match self {
+            Language::En => format!("Snooze {}", duration),
+            Language::De => format!("{} schlummern", duration),
+        }
+    }
+
+    /// The dismiss button of desktop notifications.
+    pub fn dismiss(self) -> &'static str {
+        match self {
+            Language::En => "Dismiss",
+            Language::De => "Schließen",
+        }
+    }
+
+    /// Spells out a duration in its two largest units, like "1 hour and 5 minutes" or "2 Minuten".
+    pub fn duration(self, total_seconds: u64) -> String {
+        let parts: Vec<String> = [86_400, 3600, 60, 1].iter().enumerate()
+            .scan(total_seconds, |remaining, (unit, unit_seconds)| {
+                let value = *remaining / unit_seconds;
+                *remaining %= unit_seconds;
+                Some((unit, value))
+            })
+            .filter(|(_, value)| *value > 0)
+            .take(2)
+            .map(|(unit, value)| format!("{} {}", value, self.unit(unit, value)))
+            .collect();
+        if parts.is_empty() {
+            return format!("0 {}", self.unit(3, 0));
+        }
+        let joiner = match self {
+            Language::En => " and ",
+            Language::De => " und ",
+        };
+        parts.join(joiner)
+    }
+
+    /// Unit names by index (day, hour, minute, second) in the grammatical number for `count`.
+    fn unit(self, unit: usize, count: u64) -> &'static str {
+        const EN: [(&str, &str); 4] = [("day", "days"), ("hour", "hours"), ("minute", "minutes"), ("second", "seconds")];
+        const DE: [(&str, &str); 4] = [("Tag", "Tage"), ("Stunde", "Stunden"), ("Minute", "Minuten"), ("Sekunde", "Sekunden")];
+        let (singular, plural) = match self {
+            Language::En => EN[unit],
+            Language::De => DE[unit],
+        };
+        // English and German both use the singular for exactly one only
+        if count == 1 { singular } else { plural }
+    }
+}
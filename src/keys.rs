@@ -0,0 +1,191 @@
+use ratatui::crossterm::event::KeyCode;
+use serde::Deserialize;
+
+/// Keys for the TUI actions. The arrow keys always work for switching timers in addition to these.
+pub struct KeyBindings {
+    pub quit: KeyCode,
+    pub next: KeyCode,
+    pub previous: KeyCode,
+    pub new_timer: KeyCode,
+    pub edit_target: KeyCode,
+    pub edit_name: KeyCode,
+    pub pause: KeyCode,
+    pub restart: KeyCode,
+    pub skip: KeyCode,
+    pub help: KeyCode,
+    pub add_time: KeyCode,
+    pub subtract_time: KeyCode,
+    pub add_time_big: KeyCode,
+    pub subtract_time_big: KeyCode,
+    pub detach: KeyCode,
+    pub tick: KeyCode,
+    pub snooze: KeyCode,
+}
+
+/// The `[keys]` table of the config file, keys are given like `q`, `Tab` or `Esc`.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyConfig {
+    quit: Option<String>,
+    next: Option<String>,
+    previous: Option<String>,
+    new_timer: Option<String>,
+    edit_target: Option<String>,
+    edit_name: Option<String>,
+    pause: Option<String>,
+    restart: Option<String>,
+    skip: Option<String>,
+    help: Option<String>,
+    add_time: Option<String>,
+    subtract_time: Option<String>,
+    add_time_big: Option<String>,
+    subtract_time_big: Option<String>,
+    detach: Option<String>,
+    tick: Option<String>,
+    snooze: Option<String>,
+}
+
+impl KeyConfig {
+    /// Takes every key set in `other`, keeping the own value for the rest.
+    pub fn overlay(&mut self, other: KeyConfig) {
+        let fields = [
+            (&mut self.quit, other.quit),
+            (&mut self.next, other.next),
+            (&mut self.previous, other.previous),
+            (&mut self.new_timer, other.new_timer),
+            (&mut self.edit_target, other.edit_target),
+            (&mut self.edit_name, other.edit_name),
+            (&mut self.pause, other.pause),
+            (&mut self.restart, other.restart),
+            (&mut self.skip, other.skip),
+            (&mut self.help, other.help),
+            (&mut self.add_time, other.add_time),
+            (&mut self.subtract_time, other.subtract_time),
+            (&mut self.add_time_big, other.add_time_big),
+            (&mut self.subtract_time_big, other.subtract_time_big),
+            (&mut self.detach, other.detach),
+            (&mut self.tick, other.tick),
+            (&mut self.snooze, other.snooze),
+        ];
+        for (own, other) in fields {
+            if other.is_some() {
+                *own = other;
+            }
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: KeyCode::Char('q'),
+            next: KeyCode::Tab,
+            previous: KeyCode::BackTab,
+            new_timer: KeyCode::Char('n'),
+            edit_target: KeyCode::Char('e'),
+            edit_name: KeyCode::Char('t'),
+            pause: KeyCode::Char('p'),
+            restart: KeyCode::Char('r'),
+            skip: KeyCode::Char('s'),
+            help: KeyCode::Char('?'),
+            add_time: KeyCode::Char('+'),
+            subtract_time: KeyCode::Char('-'),
+            // Shift + and - on German layouts
+            add_time_big: KeyCode::Char('*'),
+            subtract_time_big: KeyCode::Char('_'),
+            detach: KeyCode::Char('d'),
+            tick: KeyCode::Char('k'),
+            snooze: KeyCode::Char('z'),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn from_config(config: &KeyConfig) -> Result<Self, String> {
+        let defaults = Self::default();
+        let key = |configured: &Option<String>, default: KeyCode| match configured {
+            Some(name) => parse_key(name).ok_or_else(|| format!("Invalid key: {}", name)),
+            None => Ok(default),
+        };
+
+        Ok(Self {
+            quit: key(&config.quit, defaults.quit)?,
+            next: key(&config.next, defaults.next)?,
+            previous: key(&config.previous, defaults.previous)?,
+            new_timer: key(&config.new_timer, defaults.new_timer)?,
+            edit_target: key(&config.edit_target, defaults.edit_target)?,
+            edit_name: key(&config.edit_name, defaults.edit_name)?,
+            pause: key(&config.pause, defaults.pause)?,
+            restart: key(&config.restart, defaults.restart)?,
+            skip: key(&config.skip, defaults.skip)?,
+            help: key(&config.help, defaults.help)?,
+            add_time: key(&config.add_time, defaults.add_time)?,
+            subtract_time: key(&config.subtract_time, defaults.subtract_time)?,
+            add_time_big: key(&config.add_time_big, defaults.add_time_big)?,
+            subtract_time_big: key(&config.subtract_time_big, defaults.subtract_time_big)?,
+            detach: key(&config.detach, defaults.detach)?,
+            tick: key(&config.tick, defaults.tick)?,
+            snooze: key(&config.snooze, defaults.snooze)?,
+        })
+    }
+
+    /// Every binding with what it does, for the help overlay.
+    pub fn describe(&self) -> [(KeyCode, &'static str); 17] {
+        [
+            (self.quit, "Quit"),
+            (self.next, "Next timer"),
+            (self.previous, "Previous timer"),
+            (self.new_timer, "New timer"),
+            (self.edit_target, "Edit target"),
+            (self.edit_name, "Edit name"),
+            (self.pause, "Pause / resume"),
+            (self.restart, "Restart"),
+            (self.skip, "Skip to the end"),
+            (self.add_time, "Add 1 minute"),
+            (self.subtract_time, "Subtract 1 minute"),
+            (self.add_time_big, "Add 5 minutes"),
+            (self.subtract_time_big, "Subtract 5 minutes"),
+            (self.detach, "Detach, keep running in the background"),
+            (self.tick, "Ticking sound on / off"),
+            (self.snooze, "Snooze a finished timer"),
+            (self.help, "Toggle this help"),
+        ]
+    }
+}
+
+pub fn key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Char(c) => return Some(c.to_string()),
+        KeyCode::Enter => "Enter",
+        KeyCode::Esc => "Esc",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Tab => "Tab",
+        KeyCode::BackTab => "BackTab",
+        KeyCode::Left => "Left",
+        KeyCode::Right => "Right",
+        KeyCode::Up => "Up",
+        KeyCode::Down => "Down",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match name {
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Backspace" => KeyCode::Backspace,
+        "Tab" => KeyCode::Tab,
+        "BackTab" => KeyCode::BackTab,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        _ => return None,
+    };
+    Some(code)
+}
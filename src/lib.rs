@@ -0,0 +1,28 @@
+//! The countdowns of endzeit for other ratatui applications. [`Endzeit`] owns no terminal: the host
+//! feeds it events from its own loop and renders it into any part of its screen.
+
+// What the endzeit binary shares with the library, not meant for other crates
+#[doc(hidden)]
+pub mod clock;
+#[doc(hidden)]
+pub mod control;
+#[doc(hidden)]
+pub mod countdown;
+mod embed;
+#[doc(hidden)]
+pub mod exec;
+#[doc(hidden)]
+pub mod keys;
+#[doc(hidden)]
+pub mod milestone;
+#[doc(hidden)]
+pub mod source;
+#[doc(hidden)]
+pub mod spec;
+#[doc(hidden)]
+pub mod theme;
+#[doc(hidden)]
+pub mod units;
+
+pub use embed::Endzeit;
+pub use keys::KeyBindings;
@@ -0,0 +1,189 @@
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{
+    json,
+    Value
+};
+
+use crate::control::TimerStatus;
+use crate::history::Entry;
+
+/// The JSON schema version `--machine` promises. Fields are only ever added within a version,
+/// anything else gets a new one.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Machine {
+    V1,
+}
+
+impl Machine {
+    fn id(self) -> &'static str {
+        match self {
+            Machine::V1 => "endzeit/v1",
+        }
+    }
+
+    /// Prints one JSON document of `kind`, `fields` go next to `schema` and `kind`.
+    pub fn print(self, kind: &str, fields: Value) {
+        println!("{}", self.document(kind, fields));
+    }
+
+    pub fn document(self, kind: &str, fields: Value) -> Value {
+        let mut document = json!({ "schema": self.id(), "kind": kind });
+        if let (Some(document), Value::Object(fields)) = (document.as_object_mut(), fields) {
+            document.extend(fields);
+        }
+        document
+    }
+}
+
+/// Local times in v1 documents, ISO 8601 without offset.
+fn timestamp(time: NaiveDateTime) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/// A timer of a running instance in v1.
+#[derive(Serialize)]
+pub struct Timer {
+    name: String,
+    project: Option<String>,
+    target: String,
+    remaining_seconds: u64,
+    paused: bool,
+    finished: bool,
+    selected: bool,
+}
+
+impl From<&TimerStatus> for Timer {
+    fn from(status: &TimerStatus) -> Self {
+        Self {
+            name: status.name.clone(),
+            project: status.project.clone(),
+            target: timestamp(status.target),
+            remaining_seconds: status.remaining,
+            paused: status.paused,
+            finished: status.finished,
+            selected: status.selected,
+        }
+    }
+}
+
+/// A history entry in v1.
+#[derive(Serialize)]
+pub struct HistoryEntry {
+    name: Option<String>,
+    target: String,
+    ended: String,
+    duration_seconds: Option<u64>,
+    overshoot_seconds: i64,
+    aborted: bool,
+    hook_ran: bool,
+}
+
+impl From<&Entry> for HistoryEntry {
+    fn from(entry: &Entry) -> Self {
+        Self {
+            name: entry.name.clone(),
+            target: timestamp(entry.target),
+            ended: timestamp(entry.finished),
+            duration_seconds: entry.duration,
+            overshoot_seconds: entry.overshoot,
+            aborted: entry.aborted,
+            hook_ran: entry.hook_ran,
+        }
+    }
+}
+
+impl HistoryEntry {
+    /// Columns of `endzeit history export --format csv`, in the order of the JSON fields.
+    pub const CSV_HEADER: [&'static str; 7] = ["name", "target", "ended", "duration_seconds", "overshoot_seconds", "aborted", "hook_ran"];
+
+    /// The values for `CSV_HEADER`, empty for missing ones.
+    pub fn csv_record(&self) -> [String; 7] {
+        [
+            self.name.clone().unwrap_or_default(),
+            self.target.clone(),
+            self.ended.clone(),
+            self.duration_seconds.map(|duration| duration.to_string()).unwrap_or_default(),
+            self.overshoot_seconds.to_string(),
+            self.aborted.to_string(),
+            self.hook_ran.to_string(),
+        ]
+    }
+}
+
+/// `endzeit schema`: the JSON Schema of every document `--machine` prints or posts.
+pub fn schema(machine: Machine) -> Value {
+    let timestamp = json!({ "type": "string", "description": "Local time, YYYY-MM-DDTHH:MM:SS" });
+    let timer = json!({
+        "type": "object",
+        "required": ["name", "target", "remaining_seconds", "paused", "finished", "selected"],
+        "properties": {
+            "name": { "type": "string" },
+            "project": { "type": ["string", "null"], "description": "Set with project= of --target" },
+            "target": timestamp,
+            "remaining_seconds": { "type": "integer", "minimum": 0 },
+            "paused": { "type": "boolean" },
+            "finished": { "type": "boolean" },
+            "selected": { "type": "boolean", "description": "The timer commands act on" }
+        }
+    });
+    let document = |kind: &str, required: &[&str], properties: Value| {
+        let mut properties = properties;
+        properties["schema"] = json!({ "const": machine.id() });
+        properties["kind"] = json!({ "const": kind });
+        let required: Vec<&str> = ["schema", "kind"].iter().chain(required).copied().collect();
+        json!({ "type": "object", "required": required, "properties": properties })
+    };
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": machine.id(),
+        "title": "endzeit --machine output",
+        "oneOf": [
+            document("status", &["instances"], json!({
+                "instances": { "type": "array", "items": {
+                    "type": "object",
+                    "required": ["pid", "timers"],
+                    "properties": { "pid": { "type": "integer" }, "timers": { "type": "array", "items": timer } }
+                }}
+            })),
+            document("list", &["instances"], json!({
+                "instances": { "type": "array", "items": {
+                    "type": "object",
+                    "required": ["pid", "timers", "next"],
+                    "properties": {
+                        "pid": { "type": "integer" },
+                        "timers": { "type": "integer", "minimum": 0 },
+                        "next": { "description": "The unfinished timer closest to its target", "oneOf": [timer, { "type": "null" }] }
+                    }
+                }}
+            })),
+            document("control", &["pid", "command", "ok"], json!({
+                "pid": { "type": "integer" },
+                "command": { "type": "string" },
+                "ok": { "type": "boolean" },
+                "error": { "type": "string" }
+            })),
+            document("history", &["entries"], json!({
+                "entries": { "type": "array", "items": {
+                    "type": "object",
+                    "required": ["name", "target", "ended", "duration_seconds", "overshoot_seconds", "aborted", "hook_ran"],
+                    "properties": {
+                        "name": { "type": ["string", "null"] },
+                        "target": timestamp,
+                        "ended": timestamp,
+                        "duration_seconds": { "type": ["integer", "null"] },
+                        "overshoot_seconds": { "type": "integer", "description": "Negative for timers aborted early" },
+                        "aborted": { "type": "boolean" },
+                        "hook_ran": { "type": "boolean" }
+                    }
+                }}
+            })),
+            document("milestone", &["title", "message"], json!({
+                "title": { "type": "string" },
+                "message": { "type": "string", "description": "Halfway or finish message, in the --lang language" }
+            }))
+        ]
+    })
+}
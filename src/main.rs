@@ -1,40 +1,136 @@
-use clap::Parser;
-use chrono::{
-    Local,
-    NaiveDate,
-    NaiveDateTime
+mod app;
+mod ask;
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(unix)]
+mod attach;
+mod awake;
+mod browse;
+mod calendar;
+mod clipboard;
+mod config;
+mod contrast;
+mod cooldown;
+#[cfg(unix)]
+mod daemon;
+#[cfg(all(feature = "dbus", target_os = "linux"))]
+mod dbus;
+mod demo;
+mod desktop;
+mod dnd;
+mod fmt;
+mod guard;
+mod history;
+mod i18n;
+mod machine;
+mod media;
+mod metrics;
+mod mqtt;
+mod notify;
+mod output;
+mod pane;
+#[cfg(unix)]
+mod popup;
+mod preset;
+mod prompt;
+mod push;
+mod record;
+mod replay;
+mod retry;
+mod rpc;
+mod script;
+mod serve;
+mod session;
+mod settings;
+mod signals;
+#[cfg(unix)]
+mod socket;
+mod state;
+mod suggest;
+mod template;
+mod term;
+#[cfg(unix)]
+mod tree;
+#[cfg(feature = "self-update")]
+mod update;
+mod verify;
+mod watchdog;
+
+use std::path::PathBuf;
+use std::sync::mpsc;
+use clap::{
+    Args,
+    Parser,
+    Subcommand
 };
-use std::str::FromStr;
-use std::thread;
-use std::time::Duration;
-use std::process::Command;
-use std::io;
 use color_eyre::Result;
-use ratatui::{
-    buffer::Buffer,
-    crossterm::event::{
-        self,
-        Event,
-        KeyCode
-    },
-    layout::{
-        Rect
-    },
-    style::{
-        Color,
-        Style
-    },
-    widgets::{
-        Gauge,
-        // Block,
-        // Borders,
-        Widget
-    },
-    DefaultTerminal,
+use endzeit::{
+    clock,
+    control,
+    countdown,
+    exec,
+    keys,
+    milestone,
+    source,
+    spec,
+    theme,
+    units
+};
+
+use app::App;
+use ask::Answer;
+use config::Config;
+use contrast::Contrast;
+use exec::set_default_launcher;
+#[cfg(unix)]
+use control::Fifo;
+#[cfg(unix)]
+use socket::StatusSocket;
+use countdown::Countdown;
+use history::{
+    ExportFormat,
+    HistoryFilter
+};
+use machine::Machine;
+use output::Output;
+use preset::PresetOverrides;
+use record::Recorder;
+use replay::parse_speed;
+use serve::Server;
+use session::Session;
+use settings::{
+    Options,
+    Settings
+};
+use spec::{
+    parse_date,
+    parse_duration,
+    parse_target_spec,
+    set_date_formats,
+    validate_datetime
 };
+use suggest::Choice;
+use units::set_unit_format;
 
 #[derive(Parser)]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(flatten)]
+    start: StartArgs,
+
+    #[clap(flatten)]
+    options: Options,
+}
+
+/// The countdowns to run, given to `endzeit start` or to plain `endzeit`.
+#[derive(Args)]
+struct StartArgs {
+    /// Countdowns in the format of --target, e.g. 17:00 or +25m
+    #[clap(value_name = "TARGET")]
+    positional_targets: Vec<String>,
+
     /// Date in the format YYYY-MM-DD (optional, defaults to today)
     #[clap(short, long)]
     date: Option<String>,
@@ -43,250 +139,596 @@ struct Cli {
     #[clap(short, long)]
     time: Option<String>,
 
-    /// Command to execute when endzeit finishes
+    /// Name of the countdown, shown above the gauge and in notifications
     #[clap(long)]
-    execute: Option<String>,
-}
+    name: Option<String>,
 
-struct App {
-    start_instant: std::time::Instant,
-    total_seconds: f64,
-    execute_command: Option<String>,
-}
+    /// Additional countdown, can be repeated: WHEN[,name=TEXT][,project=NAME][,color=COLOR][,cwd=DIR][,shell=SHELL][,execute=COMMAND]...
+    /// (WHEN may be file:PATH to follow a date/time stored in a file)
+    #[clap(long = "target", value_name = "SPEC")]
+    targets: Vec<String>,
 
-#[derive(Debug)]
-struct TimeRemaining {
-    years: u64,
-    months: u64,
-    weeks: u64,
-    days: u64,
-    hours: u64,
-    minutes: u64,
-    seconds: u64,
-}
+    /// Additional countdown from the clipboard, in the same format as --target
+    #[clap(long)]
+    from_clipboard: bool,
 
-fn main() -> Result<()> {
-    color_eyre::install()?;
-    let args = Cli::parse();
+    /// Named pipe read for commands like `pause`, `resume`, `add 5m` or `cancel`, created if it doesn't exist
+    #[cfg(unix)]
+    #[clap(long, value_name = "PATH")]
+    control_fifo: Option<PathBuf>,
 
-    // Get today's date if no date is provided
-    let date = match args.date {
-        Some(ref d) => NaiveDate::parse_from_str(d, "%Y-%m-%d").expect("Invalid date format, use YYYY-MM-DD"),
-        None => Local::now().naive_local().into(),
-    };
+    /// Take JSON-RPC requests on stdin and send the ticks and finished timers as notifications to
+    /// stdout instead of showing them, for editors and programs that run endzeit as a timer backend
+    #[clap(long)]
+    rpc: bool,
 
-    // Validate and combine date and time
-    let target_datetime = validate_datetime(date, args.time.as_deref());
+    /// Record the session to a file for `endzeit replay`
+    #[clap(long, value_name = "FILE")]
+    record: Option<String>,
 
-    let now = Local::now().naive_local();
-    if target_datetime <= now {
-        eprintln!("Target date/time must be in the future");
-        std::process::exit(1);
-    }
+    /// Keep the countdowns and their commands running in the background without a terminal,
+    /// manage them with `endzeit list`, `status`, `pause` and `cancel`
+    #[cfg(unix)]
+    #[clap(long)]
+    detach: bool,
 
-    let terminal = ratatui::init();
-    let app_result = App::new(target_datetime, args.execute).run(terminal);
-    ratatui::restore();
-    app_result
-}
+    /// Start the countdowns once the timer NAME of a running instance has finished, times like +25m count from then
+    #[cfg(unix)]
+    #[clap(long, value_name = "NAME")]
+    after_timer: Option<String>,
 
-impl App {
-    fn new(target_datetime: NaiveDateTime, execute_command: Option<String>) -> Self {
-        let now = Local::now().naive_local();
-        let total_duration = target_datetime - now;
+    /// Wait this long after the --after-timer timer has finished, e.g. 5m
+    #[cfg(unix)]
+    #[clap(long, value_parser = parse_duration, requires = "after_timer", value_name = "DURATION")]
+    after_gap: Option<chrono::TimeDelta>,
+}
 
-        Self {
-            start_instant: std::time::Instant::now(),
-            total_seconds: total_duration.num_seconds() as f64,
-            execute_command,
-        }
-    }
+#[cfg(unix)]
+#[derive(Subcommand)]
+enum CtlCommand {
+    /// Show the timers grouped by their project= with the earliest deadline of each group
+    Tree {
+        /// Show the tree in a live, foldable view
+        #[clap(long)]
+        tui: bool,
+    },
+}
 
-    fn run(self, mut terminal: DefaultTerminal) -> Result<()> {
-        loop {
-            terminal.draw(|frame| frame.render_widget(&self, frame.area()))?;
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Browse past timers in a scrollable table and start one of them again
+    Browse(HistoryFilter),
+    /// Print past timers as CSV or JSON, oldest first
+    Export {
+        #[clap(long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+
+        #[clap(flatten)]
+        filter: HistoryFilter,
+    },
+}
 
-            if self.is_finished() {
-                break;
-            }
+#[derive(Subcommand)]
+enum Command {
+    /// Start countdowns, the same as plain `endzeit` (options like --execute go before `start`)
+    Start(StartArgs),
+    /// Read durations from stdin (seconds or ISO 8601 like PT2H35M) and print them humanized
+    Fmt {
+        /// Template with the placeholders {y} {mo} {w} {d} {h} {m} {s}, e.g. "{h}h {m}m"
+        #[clap(long)]
+        format: Option<String>,
+    },
+    /// Count down while watching a process, running an action if it is still alive at zero
+    Watchdog {
+        /// Process to watch, the countdown ends early when it exits
+        #[clap(long)]
+        pid: u32,
+
+        /// How long the process may run, e.g. 10m or 1h30m
+        #[clap(long, value_parser = parse_duration)]
+        deadline: chrono::TimeDelta,
+
+        /// Command to execute if the process is still alive at the deadline
+        #[clap(long)]
+        on_timeout: Option<String>,
+    },
+    /// Succeed if the named cooldown has expired (and start it again), otherwise print the remaining time and fail
+    Cooldown {
+        /// Name of the cooldown
+        name: String,
+
+        /// Length of the cooldown, e.g. 1h or 30m
+        #[clap(value_parser = parse_duration)]
+        duration: chrono::TimeDelta,
+    },
+    /// Fail while a code freeze is in effect, for use in pre-commit and pre-push hooks
+    Guard {
+        /// When the freeze ends, or starts with --deny-after; same format as the WHEN part of --target
+        #[clap(long)]
+        until: String,
+
+        /// Deny once the given point has passed instead of until then
+        #[clap(long)]
+        deny_after: bool,
+    },
+    /// Replace this executable with the latest release
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Only report whether a newer release is available
+        #[clap(long)]
+        check: bool,
+    },
+    /// Re-render a session recorded with --record
+    Replay {
+        /// The recording
+        file: String,
+
+        /// Playback speed, e.g. 20x
+        #[clap(long, default_value = "1x", value_parser = parse_speed)]
+        speed: f64,
+    },
+    /// Ask a yes/no question with a time limit. Exits with 0 for yes, 1 for no and 2 if the time ran out without a default
+    Ask {
+        /// The question, e.g. "Proceed with deploy?"
+        question: String,
+
+        /// Time to answer, e.g. 60s or 2m
+        #[clap(long, value_parser = parse_duration, default_value = "60s")]
+        timeout: chrono::TimeDelta,
+
+        /// Answer taken when the time runs out or on Enter
+        #[clap(long, value_enum)]
+        default: Option<Answer>,
+    },
+    /// List the running instances with their next deadline
+    #[cfg(unix)]
+    List,
+    /// Show the timers of the running instances
+    #[cfg(unix)]
+    Status {
+        /// Only the instance with this pid
+        #[clap(long)]
+        pid: Option<u32>,
+    },
+    /// Pause the selected timer of a running instance
+    #[cfg(unix)]
+    Pause {
+        /// The instance, may be left out while only one is running
+        #[clap(long)]
+        pid: Option<u32>,
+    },
+    /// Resume the selected timer of a running instance, or with none running, the timers of
+    /// instances that crashed or were running at a reboot
+    Resume {
+        /// The instance, may be left out while only one is running
+        #[clap(long)]
+        pid: Option<u32>,
+    },
+    /// Cancel the selected timer of a running instance without running its command
+    #[cfg(unix)]
+    Cancel {
+        /// The instance, may be left out while only one is running
+        #[clap(long)]
+        pid: Option<u32>,
+    },
+    /// Stop the alerts of a running instance started with --alarm
+    #[cfg(unix)]
+    Dismiss {
+        /// The instance, may be left out while only one is running
+        #[clap(long)]
+        pid: Option<u32>,
+    },
+    /// Inspect the timers of all running instances
+    #[cfg(unix)]
+    Ctl {
+        #[clap(subcommand)]
+        command: CtlCommand,
+    },
+    /// Show the timers of an instance running in the background, e.g. after pressing d
+    #[cfg(unix)]
+    Attach {
+        /// Name of one of its timers, or its pid
+        name: String,
+    },
+    /// Run endzeit with the rest of the command line in a tmux popup over the current pane, e.g. `endzeit popup +5m,name=Tea`
+    #[cfg(unix)]
+    Popup {
+        /// Arguments for endzeit, like countdowns, options or another subcommand
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Print the JSON Schema of the --machine output
+    Schema {
+        #[clap(long, value_enum, default_value = "v1")]
+        version: Machine,
+    },
+    /// Show all display modes, themes, notifications and the finish in 30 seconds on a fast clock
+    Demo,
+    /// List the completed timers
+    History {
+        #[clap(subcommand)]
+        command: Option<HistoryCommand>,
+
+        /// Show a heatmap of completed timers per day over the last year instead
+        #[clap(long)]
+        heatmap: bool,
+    },
+    /// Suggest timers you usually start around this time, and start one with a key press
+    Suggest,
+    /// Print a shell script that waits for the countdowns and runs their commands, for machines without endzeit
+    ExportScript {
+        /// Countdowns in the format of --target
+        #[clap(value_name = "TARGET")]
+        targets: Vec<String>,
+
+        /// Countdown from the [presets] table of the config file, can be repeated
+        #[clap(long)]
+        preset: Vec<String>,
+    },
+    /// Start a countdown defined in the [presets] table of the config file
+    Preset {
+        /// Name of the preset
+        preset: String,
 
-            if self.should_quit()? {
-                break;
-            }
+        #[clap(flatten)]
+        overrides: PresetOverrides,
+    },
+}
 
-            thread::sleep(Duration::from_millis(333));
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let mut args = Cli::parse();
+
+    // These don't use the config
+    let command = match args.command.take() {
+        Some(Command::Fmt { format }) => return fmt::run(format),
+        Some(Command::Cooldown { name, duration }) => return cooldown::run(&name, duration),
+        Some(Command::History { command: None, heatmap }) => return history::run(heatmap, args.options.machine),
+        Some(Command::History { command: Some(HistoryCommand::Export { format, filter }), .. }) => return history::export(&filter, format),
+        Some(Command::Schema { version }) => {
+            println!("{}", serde_json::to_string_pretty(&machine::schema(version))?);
+            return Ok(());
+        }
+        #[cfg(feature = "self-update")]
+        Some(Command::SelfUpdate { check }) => return update::run(check),
+        #[cfg(unix)]
+        Some(Command::Ctl { command: CtlCommand::Tree { tui } }) => return tree::run(tui),
+        #[cfg(unix)]
+        Some(Command::List) => return socket::run_list(args.options.machine),
+        #[cfg(unix)]
+        Some(Command::Status { pid }) => return socket::run_status(pid, args.options.machine),
+        #[cfg(unix)]
+        Some(Command::Pause { pid }) => return socket::run_control(pid, "pause", args.options.machine),
+        #[cfg(unix)]
+        Some(Command::Resume { pid }) if pid.is_some() || !socket::instances().is_empty() => return socket::run_control(pid, "resume", args.options.machine),
+        #[cfg(unix)]
+        Some(Command::Cancel { pid }) => return socket::run_control(pid, "cancel", args.options.machine),
+        #[cfg(unix)]
+        Some(Command::Dismiss { pid }) => return socket::run_control(pid, "dismiss", args.options.machine),
+        #[cfg(unix)]
+        Some(Command::Popup { args }) => {
+            let timers = popup_timers(&args);
+            return popup::run(&args, timers);
         }
+        command => command,
+    };
 
-        self.handle_completion();
-        Ok(())
+    let config = Config::load(args.options.config.as_deref())
+        .and_then(|config| config.with_profile(args.options.profile.as_deref()))
+        .unwrap_or_else(|err| exit_with(&err));
+    if !args.options.minimal.or(config.minimal).unwrap_or(false) && args.options.contrast.or(config.contrast) != Some(Contrast::Off) {
+        contrast::detect_background();
     }
-
-    fn is_finished(&self) -> bool {
-        let elapsed = self.start_instant.elapsed().as_secs_f64();
-        elapsed >= self.total_seconds
+    let mut settings = Settings::resolve(&args.options, &config).unwrap_or_else(|err| exit_with(&err));
+    set_date_formats(settings.date_formats.clone());
+    set_unit_format(settings.unit_format);
+    set_default_launcher(settings.launcher.clone());
+
+    let mut start = args.start;
+    let mut resumed = Vec::new();
+    // Started by the detach key: the countdowns handed over replace whatever the command line starts
+    #[cfg(unix)]
+    let mut command = command;
+    #[cfg(unix)]
+    if let Some(handed_off) = daemon::handed_off(&settings.theme).unwrap_or_else(|err| exit_with(&err)) {
+        if let Some(Command::Start(start_args)) = command.take() {
+            start = start_args;
+        }
+        resumed = handed_off;
+        start.detach = true;
+        start.after_timer = None;
+        start.record = None;
     }
+    let preset = match command {
+        Some(Command::Watchdog { pid, deadline, on_timeout }) => return watchdog::run(pid, deadline, on_timeout, settings),
+        Some(Command::Replay { file, speed }) => return replay::run(&file, speed, settings),
+        Some(Command::Demo) => return demo::run(settings),
+        Some(Command::Resume { .. }) => {
+            resumed = session::restore(&settings.theme).unwrap_or_else(|err| exit_with(&err));
+            if resumed.is_empty() {
+                exit_with("Nothing to resume");
+            }
+            None
+        }
+        Some(Command::Guard { until, deny_after }) => return guard::run(&until, deny_after),
+        Some(Command::Ask { question, timeout, default }) => return ask::run(&question, timeout, default, settings),
+        #[cfg(unix)]
+        Some(Command::Attach { name }) => return attach::run(&name, settings),
+        Some(Command::ExportScript { targets, preset }) => {
+            let mut countdowns = Vec::new();
+            for spec in &targets {
+                let target_spec = parse_target_spec(spec).unwrap_or_else(|err| exit_with(&format!("{}: {}", spec, err)));
+                countdowns.push(Countdown::from_spec(target_spec, settings.theme.color(countdowns.len())));
+            }
+            for preset in &preset {
+                countdowns.push(preset_countdown(&config, &settings, preset, PresetOverrides::default()));
+            }
+            if countdowns.is_empty() {
+                exit_with("Nothing to export, give countdowns like +25m,execute=CMD or --preset NAME");
+            }
+            print!("{}", script::export(&countdowns));
+            return Ok(());
+        }
+        Some(Command::Preset { preset, overrides }) => Some((preset, overrides)),
+        Some(Command::Start(start_args)) => {
+            start = start_args;
+            None
+        }
+        Some(Command::History { command: Some(HistoryCommand::Browse(filter)), .. }) => match browse::run(filter)? {
+            Some(spec) => {
+                start.targets.push(spec);
+                None
+            }
+            None => return Ok(()),
+        },
+        Some(Command::Suggest) => match suggest::choose(&config) {
+            Some(Choice::Target(spec)) => {
+                start.targets.push(spec);
+                None
+            }
+            Some(Choice::Preset(preset)) => Some((preset, PresetOverrides::default())),
+            None => return Ok(()),
+        },
+        _ => None,
+    };
 
-    fn should_quit(&self) -> Result<bool> {
-        if event::poll(Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
-                return Ok(key.code == KeyCode::Char('q'));
+    let mut countdowns = match preset {
+        Some((preset, overrides)) => vec![preset_countdown(&config, &settings, &preset, overrides)],
+        None if !resumed.is_empty() => resumed,
+        None => countdowns_from_args(&mut start, &mut settings),
+    };
+    settings.validate_hooks.check(&mut countdowns, &settings.shared_hooks()).unwrap_or_else(|err| exit_with(&err));
+    if let Some(path) = &settings.calendar {
+        let events = calendar::load(path).unwrap_or_else(|err| exit_with(&err));
+        for countdown in &mut countdowns {
+            if let Some(warning) = calendar::conflict_warning(&events, countdown.target()) {
+                countdown.set_note(warning);
             }
         }
-        Ok(false)
     }
 
-    fn get_remaining_time(&self) -> TimeRemaining {
-        let remaining_seconds = (self.total_seconds as u64).saturating_sub(
-            self.start_instant.elapsed().as_secs()
-        );
-
-        const SECONDS_IN_MINUTE: u64 = 60;
-        const SECONDS_IN_HOUR: u64 = 3600;
-        const SECONDS_IN_DAY: u64 = 86_400;
-        const SECONDS_IN_WEEK: u64 = 604_800;
-
-        let years = remaining_seconds / (SECONDS_IN_DAY * 365);
-        let remaining_after_years = remaining_seconds % (SECONDS_IN_DAY * 365);
-
-        let months = remaining_after_years / (SECONDS_IN_DAY * 30); // Approximating a month as 30 days
-        let remaining_after_months = remaining_after_years % (SECONDS_IN_DAY * 30);
-
-        let weeks = remaining_after_months / SECONDS_IN_WEEK;
-        let remaining_after_weeks = remaining_after_months % SECONDS_IN_WEEK;
-
-        let days = remaining_after_weeks / SECONDS_IN_DAY;
-        let remaining_after_days = remaining_after_weeks % SECONDS_IN_DAY;
-
-        let hours = remaining_after_days / SECONDS_IN_HOUR;
-        let remaining_after_hours = remaining_after_days % SECONDS_IN_HOUR;
-
-        let minutes = remaining_after_hours / SECONDS_IN_MINUTE;
-        let seconds = remaining_after_hours % SECONDS_IN_MINUTE;
-
-        TimeRemaining {
-            years,
-            months,
-            weeks,
-            days,
-            hours,
-            minutes,
-            seconds
+    // Before any thread is spawned, see `daemon::detach`
+    #[cfg(unix)]
+    let detached = start.detach;
+    #[cfg(not(unix))]
+    let detached = false;
+    #[cfg(unix)]
+    if detached {
+        daemon::detach().unwrap_or_else(|err| exit_with(&err));
+    }
+    #[cfg(unix)]
+    if let Some(name) = &start.after_timer {
+        if !detached {
+            println!("Waiting for {} to finish", name);
+        }
+        socket::wait_for_timer(name).unwrap_or_else(|err| exit_with(&err));
+        if let Some(gap) = start.after_gap.and_then(|gap| gap.to_std().ok()) {
+            std::thread::sleep(gap);
+        }
+        // The countdowns were resolved at start, now they begin for real
+        for countdown in &mut countdowns {
+            countdown.restart();
         }
     }
 
-    fn get_progress_percentage(&self) -> f64 {
-        let elapsed = self.start_instant.elapsed().as_secs_f64();
-        if self.total_seconds > 0.0 {
-            (elapsed / self.total_seconds).min(1.0) * 100.0
-        } else {
-            100.0
+    // Without a terminal the frames go to /dev/null, the countdowns keep ticking
+    let output = if detached { Output::Ansi } else { settings.output };
+    // Only the TUI reads the answer, anywhere else the commands would be left out every time
+    if settings.confirm_execute && (start.rpc || output != Output::Tui) {
+        exit_with("--confirm-execute needs the TUI to ask, it can't be combined with --output, --detach, detaching with d or --rpc");
+    }
+    let mouse = settings.mouse;
+    let height = if settings.ticker { 1 } else { countdowns.len() as u16 * 3 };
+    let (controls, control_receiver) = mpsc::channel();
+    // Before the settings watcher spawns its thread, see `signals::listen`
+    #[cfg(unix)]
+    signals::listen(controls.clone());
+    #[cfg(unix)]
+    signals::catch_termination();
+    #[cfg(unix)]
+    let _fifo = start.control_fifo.map(|path| Fifo::open(path, controls.clone()).unwrap_or_else(|err| exit_with(&err)));
+    let (status_requests, status_receiver) = mpsc::channel();
+    let (timer_waits, wait_receiver) = mpsc::channel();
+    if let Some(port) = settings.metrics_port {
+        metrics::serve(port, status_requests.clone()).unwrap_or_else(|err| exit_with(&err));
+    }
+    if let Some(address) = &settings.serve {
+        let server = Server { status_requests: status_requests.clone(), controls: controls.clone(), token: settings.serve_token.clone() };
+        serve::serve(address, server).unwrap_or_else(|err| exit_with(&err));
+    }
+    if start.rpc {
+        rpc::listen(controls.clone(), status_requests.clone());
+    }
+    #[cfg(unix)]
+    let _socket = StatusSocket::bind(controls.clone(), status_requests, timer_waits).unwrap_or_else(|err| exit_with(&err));
+    let mut app = App::new(countdowns, settings)
+        .watch_settings(settings::watch(args.options.clone()))
+        .control_with(control_receiver)
+        .answer_status(status_receiver)
+        .answer_waits(wait_receiver)
+        .keep_history();
+    match Session::create() {
+        Ok(session) => app = app.persist_to(session),
+        Err(err) => eprintln!("{}", err),
+    }
+    if let Some(path) = &start.record {
+        match Recorder::create(path, app.countdowns()) {
+            Ok(recorder) => app = app.record_to(recorder),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
         }
     }
 
-    fn handle_completion(&self) {
-        if let Some(exec_command) = &self.execute_command {
-            match execute_file(exec_command) {
-                Ok(_) => {},
-                Err(e) => eprintln!("Failed to execute file: {}", e),
-            }
+    let (verdicts, verdict_receiver) = mpsc::channel();
+    app = app.report_verdicts_to(verdicts);
+    let app_result = match output {
+        _ if start.rpc => {
+            exec::capture_output(true);
+            let app_result = app.without_input().run_rpc();
+            exec::capture_output(false);
+            app_result
         }
+        Output::Plain => app.without_input().run_plain(),
+        Output::Waybar => app.without_input().run_waybar(),
+        Output::Line => app.without_input().run_line(),
+        Output::Ansi => {
+            let terminal = output::ansi_terminal(height)?;
+            let app_result = app.without_input().run(terminal);
+            output::restore_ansi_terminal()?;
+            app_result
+        }
+        Output::Tui => run_tui(app, mouse),
+    };
+    app_result?;
+    verify::report(&verdict_receiver);
+    // Exit the way the shell expects of a process killed by the signal
+    if let Some(signal) = signals::terminated_by() {
+        std::process::exit(128 + signal);
     }
+    Ok(())
 }
 
-impl Widget for &App {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        self.render_gauge(area, buf);
+fn run_tui(mut app: App, mouse: bool) -> Result<()> {
+    let (detached, detached_receiver) = mpsc::channel();
+    app = app.detach_to(detached);
+    let terminal = ratatui::init();
+    output::report_focus()?;
+    if mouse {
+        output::capture_mouse()?;
+        app = app.with_mouse();
+    }
+    exec::capture_output(true);
+    let app_result = app.run(terminal);
+    exec::capture_output(false);
+    if mouse {
+        output::release_mouse()?;
+    }
+    output::stop_reporting_focus()?;
+    ratatui::restore();
+    if let Ok(pid) = detached_receiver.try_recv() {
+        println!("Detached, the timers keep running in endzeit {}, see them again with `endzeit attach {}`", pid, pid);
     }
+    app_result
 }
 
-impl App {
-    fn render_gauge(&self, area: Rect, buf: &mut Buffer) {
-        let TimeRemaining { years, months, weeks, days, hours, minutes, seconds } = self.get_remaining_time();
+/// How many timers `endzeit popup` starts with `args`, None for subcommands that show something else.
+#[cfg(unix)]
+fn popup_timers(args: &[String]) -> Option<usize> {
+    let cli = Cli::try_parse_from(std::iter::once("endzeit").chain(args.iter().map(String::as_str))).ok()?;
+    let start = match cli.command {
+        None => cli.start,
+        Some(Command::Start(start)) => start,
+        Some(Command::Preset { .. }) => return Some(1),
+        Some(_) => return None,
+    };
+    let single = start.time.is_some() || start.date.is_some();
+    Some(start.positional_targets.len() + start.targets.len() + usize::from(single) + usize::from(start.from_clipboard))
+}
 
-        // Format the time string
-        let mut time_string = String::new();
+fn exit_with(err: &str) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
 
-        if years > 0 {
-            time_string.push_str(&format!("{}y ", years));
-        }
-        // Only append months and weeks if they are non-zero or there are no other units
-        if (months > 0 && time_string.is_empty()) || (!time_string.is_empty() && months > 0) {
-            time_string.push_str(&format!("{}m ", months));
-        }
-        if (weeks > 0 && time_string.is_empty()) || (!time_string.is_empty() && weeks > 0) {
-            time_string.push_str(&format!("{}w ", weeks));
-        }
-        if (days > 0 && time_string.is_empty()) || (!time_string.is_empty() && days > 0) {
-            time_string.push_str(&format!("{}d ", days));
-        }
-        // Only append hours, minutes, and seconds if they are non-zero or there are no other units
-        if (hours > 0 && time_string.is_empty()) || (!time_string.is_empty() && hours > 0) {
-            time_string.push_str(&format!("{}h ", hours));
-        }
-        if (minutes > 0 && time_string.is_empty()) || (!time_string.is_empty() && minutes > 0) {
-            time_string.push_str(&format!("{}m ", minutes));
-        }
-        if seconds > 0 || !time_string.is_empty() {
-            time_string.push_str(&format!("{}s", seconds));
-        }
+fn countdowns_from_args(args: &mut StartArgs, settings: &mut Settings) -> Vec<Countdown> {
+    let now = clock::now();
+    let mut countdowns = Vec::new();
 
-        let gauge = Gauge::default()
-            .gauge_style(Style::default().fg(Color::Green).bg(Color::Black))
-            .percent(self.get_progress_percentage() as u16)
-            .label(time_string);
+    let mut positional_targets = std::mem::take(&mut args.positional_targets);
+    positional_targets.append(&mut args.targets);
+    args.targets = positional_targets;
 
-        gauge.render(area, buf);
+    if args.from_clipboard {
+        args.targets.push(clipboard::target_spec().unwrap_or_else(|err| exit_with(&err)));
     }
-}
 
-fn parse_time(time: &str) -> Result<(u32, u32, u32), String> {
-    let parts: Vec<&str> = time.split(':').collect();
-    match parts.len() {
-        3 => {
-            let hours = u32::from_str(parts[0]).map_err(|_| "Invalid hour")?;
-            let minutes = u32::from_str(parts[1]).map_err(|_| "Invalid minute")?;
-            let seconds = u32::from_str(parts[2]).map_err(|_| "Invalid second")?;
-            Ok((hours, minutes, seconds))
+    // The -d/-t timer is only implied when no --target is given
+    if args.date.is_some() || args.time.is_some() || args.targets.is_empty() {
+        // Get today's date if no date is provided
+        let date = match args.date {
+            Some(ref d) => parse_date(d).unwrap_or_else(|err| exit_with(&err)),
+            None => now.into(),
+        };
+
+        // Validate and combine date and time
+        let target_datetime = validate_datetime(date, args.time.as_deref()).unwrap_or_else(|err| exit_with(&err));
+        if target_datetime <= now {
+            eprintln!("Target date/time must be in the future");
+            std::process::exit(1);
         }
-        2 => {
-            let hours = u32::from_str(parts[0]).map_err(|_| "Invalid hour")?;
-            let minutes = u32::from_str(parts[1]).map_err(|_| "Invalid minute")?;
-            Ok((hours, minutes, 0))
+
+        let mut countdown = Countdown::new(target_datetime, args.name.take(), settings.theme.color(0), Vec::new());
+        give_commands(&mut countdown, settings);
+        countdowns.push(countdown);
+    }
+
+    for spec in &args.targets {
+        let target_spec = match parse_target_spec(spec) {
+            Ok(target_spec) => target_spec,
+            Err(err) => {
+                eprintln!("{}: {}", spec, err);
+                std::process::exit(1);
+            }
+        };
+        if target_spec.target <= now {
+            eprintln!("{}: Target date/time must be in the future", spec);
+            std::process::exit(1);
         }
-        1 => {
-            let hours = u32::from_str(parts[0]).map_err(|_| "Invalid hour")?;
-            Ok((hours, 0, 0))
+
+        let mut countdown = Countdown::from_spec(target_spec, settings.theme.color(countdowns.len()));
+        if countdown.execute_commands.is_empty() {
+            give_commands(&mut countdown, settings);
         }
-        _ => Err("Invalid time format, use HH[:MM[:SS]]".to_string()),
+        countdowns.push(countdown);
     }
+
+    countdowns
 }
 
-fn validate_datetime(date: NaiveDate, time: Option<&str>) -> NaiveDateTime {
-    let now = Local::now().naive_local();
-    match time {
-        Some(t) => {
-            match parse_time(t) {
-                Ok((hours, minutes, seconds)) => date.and_hms_opt(hours, minutes, seconds).expect("Invalid time"),
-                Err(err) => {
-                    eprintln!("{}", err);
-                    std::process::exit(1);
-                }
-            }
-        },
-        None => now,
-    }
+/// Hands `--execute`, `--exec-direct` and `--then` to a countdown without commands of its own.
+fn give_commands(countdown: &mut Countdown, settings: &Settings) {
+    countdown.execute_commands = settings.execute.clone();
+    countdown.exec_direct = settings.exec_direct.clone();
+    countdown.then = settings.then;
 }
 
-fn execute_file(command_with_args: &str) -> io::Result<()> {
-    if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", command_with_args])
-            .status()?;
-    } else {
-        Command::new("sh")
-            .args(["-c", command_with_args])
-            .status()?;
+fn preset_countdown(config: &Config, settings: &Settings, key: &str, overrides: PresetOverrides) -> Countdown {
+    let Some(preset) = config.presets.get(key) else {
+        eprintln!("Unknown preset: {}", key);
+        std::process::exit(1);
     };
 
-    Ok(())
-}
\ No newline at end of file
+    match preset.clone().merge(overrides).resolve(key) {
+        Ok(target_spec) if target_spec.target > clock::now() => Countdown::from_spec(target_spec, settings.theme.color(0)),
+        Ok(_) => {
+            eprintln!("{}: Target date/time must be in the future", key);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{}: {}", key, err);
+            std::process::exit(1);
+        }
+    }
+}
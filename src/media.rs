@@ -0,0 +1,85 @@
+use std::process::Command;
+use clap::ValueEnum;
+
+use crate::exec::find_program;
+use crate::notify::spawn;
+
+/// What `--media` tells the media players.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum MediaAction {
+    Play,
+    Pause,
+    /// Play what is paused and pause what plays
+    Toggle,
+    Stop,
+}
+
+/// `--media [PERCENT:]ACTION`: an action for the media players once a timer's progress reaches
+/// PERCENT, or once it finished without one.
+#[derive(Clone, Copy, PartialEq)]
+pub struct MediaCue {
+    pub percent: Option<f64>,
+    pub action: MediaAction,
+}
+
+/// Reads `pause` or `50%:pause`, the percentages like `--exec-at` takes them.
+pub fn parse_media_cue(input: &str) -> Result<MediaCue, String> {
+    let (percent, action) = match input.split_once(':') {
+        Some((percent, action)) => {
+            let percent: f64 = percent.trim().trim_end_matches('%').parse().map_err(|_| format!("invalid percentage: {}", percent))?;
+            if !(percent > 0.0 && percent < 100.0) {
+                return Err(format!("{}% is not between 0% and 100%, leave it out for the end", percent));
+            }
+            (Some(percent), action)
+        }
+        None => (None, input),
+    };
+    let action = MediaAction::from_str(action.trim(), true)
+        .map_err(|_| format!("unknown action {:?}, expected play, pause, toggle or stop", action.trim()))?;
+    Ok(MediaCue { percent, action })
+}
+
+impl MediaCue {
+    /// Whether the cue is due, given the stretch of progress `passed` since the last check and
+    /// whether the timer just `finished`.
+    pub fn due(&self, passed: Option<(f64, f64)>, finished: bool) -> bool {
+        match (self.percent, passed) {
+            (Some(percent), Some((from, to))) => from < percent && percent <= to,
+            (Some(_), None) => false,
+            (None, _) => finished,
+        }
+    }
+}
+
+impl MediaAction {
+    /// The method of the MPRIS player interface.
+    #[cfg(all(feature = "dbus", target_os = "linux"))]
+    fn method(self) -> &'static str {
+        match self {
+            MediaAction::Play => "Play",
+            MediaAction::Pause => "Pause",
+            MediaAction::Toggle => "PlayPause",
+            MediaAction::Stop => "Stop",
+        }
+    }
+
+    /// The same as a command of playerctl.
+    fn playerctl(self) -> &'static str {
+        match self {
+            MediaAction::Play => "play",
+            MediaAction::Pause => "pause",
+            MediaAction::Toggle => "play-pause",
+            MediaAction::Stop => "stop",
+        }
+    }
+
+    /// Tells every MPRIS player about it, directly over the session bus with the dbus feature and
+    /// through playerctl otherwise. Returns false if neither is there.
+    pub fn send(self) -> bool {
+        #[cfg(all(feature = "dbus", target_os = "linux"))]
+        if crate::dbus::control_players(self.method()) {
+            return true;
+        }
+        find_program("playerctl") && spawn(Command::new("playerctl").args(["--all-players", self.playerctl()]))
+    }
+}
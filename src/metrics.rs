@@ -0,0 +1,108 @@
+use std::fmt::Write as _;
+use std::io::{
+    BufRead,
+    BufReader,
+    Write
+};
+use std::net::{
+    TcpListener,
+    TcpStream
+};
+use std::sync::atomic::{
+    AtomicU64,
+    Ordering
+};
+use std::sync::mpsc::{
+    self,
+    Sender
+};
+use std::thread;
+use std::time::Duration;
+
+use crate::control::TimerStatus;
+
+/// The name of a metric, its help and how to read it off a timer.
+type Gauge = (&'static str, &'static str, fn(&TimerStatus) -> f64);
+
+/// How long a scrape waits for the timers.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Timers that ran to their end and that were given up, since the start.
+static COMPLETIONS: AtomicU64 = AtomicU64::new(0);
+static CANCELLATIONS: AtomicU64 = AtomicU64::new(0);
+
+pub fn count_completion() {
+    COMPLETIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn count_cancellation() {
+    CANCELLATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// `--metrics-port`: serves the timers in the text format of Prometheus on `/metrics` of every
+/// interface, asking the app for them with a sender on `status_requests` like `endzeit status` does.
+pub fn serve(port: u16, status_requests: Sender<Sender<Vec<TimerStatus>>>) -> Result<(), String> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| format!("Failed to listen on port {}: {}", port, e))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            let status_requests = status_requests.clone();
+            thread::spawn(move || answer(stream, &status_requests));
+        }
+    });
+    Ok(())
+}
+
+fn answer(stream: TcpStream, status_requests: &Sender<Sender<Vec<TimerStatus>>>) {
+    let mut request = String::new();
+    if BufReader::new(&stream).read_line(&mut request).is_err() {
+        return;
+    }
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = if path != "/metrics" && path != "/" {
+        ("404 Not Found", "Not found, the metrics are on /metrics\n".to_string())
+    } else {
+        let (sender, receiver) = mpsc::channel();
+        let _ = status_requests.send(sender);
+        match receiver.recv_timeout(ANSWER_TIMEOUT) {
+            Ok(timers) => ("200 OK", render(&timers)),
+            Err(_) => ("503 Service Unavailable", "No answer from the timers\n".to_string()),
+        }
+    };
+    let _ = write!(
+        &stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body,
+    );
+}
+
+/// The timers as gauges labelled by name, and the counters.
+fn render(timers: &[TimerStatus]) -> String {
+    let mut text = String::new();
+    let gauges: [Gauge; 4] = [
+        ("endzeit_remaining_seconds", "Seconds left on the timer.", |timer| timer.remaining as f64),
+        ("endzeit_progress_ratio", "Share of the timer that has passed, from 0 to 1.", |timer| {
+            if timer.duration == 0 { 1.0 } else { 1.0 - timer.remaining as f64 / timer.duration as f64 }
+        }),
+        ("endzeit_paused", "1 while the timer is paused.", |timer| timer.paused as u8 as f64),
+        ("endzeit_finished", "1 once the timer has finished.", |timer| timer.finished as u8 as f64),
+    ];
+    for (metric, help, value) in gauges {
+        let _ = writeln!(text, "# HELP {} {}\n# TYPE {} gauge", metric, help, metric);
+        for timer in timers {
+            let _ = writeln!(text, "{}{{name=\"{}\"}} {}", metric, escape(&timer.name), value(timer));
+        }
+    }
+    let counters = [
+        ("endzeit_completions_total", "Timers that ran to their end.", &COMPLETIONS),
+        ("endzeit_cancellations_total", "Timers given up before their end.", &CANCELLATIONS),
+    ];
+    for (metric, help, count) in counters {
+        let _ = writeln!(text, "# HELP {} {}\n# TYPE {} counter\n{} {}", metric, help, metric, metric, count.load(Ordering::Relaxed));
+    }
+    text
+}
+
+/// Escapes a label value the way the text format wants.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,53 @@
+use crate::spec::parse_duration;
+
+/// `--exec-at PERCENT:COMMAND`: a command that runs once a timer's progress reaches PERCENT.
+/// The command of a timer itself is the milestone at 100%.
+#[derive(Clone, PartialEq)]
+pub struct Milestone {
+    pub percent: f64,
+    pub command: String,
+}
+
+/// `--execute-before DURATION:COMMAND`: a command that runs once only DURATION is left, it shares
+/// the thresholds of `--notify-at`.
+#[derive(Clone, PartialEq)]
+pub struct BeforeEnd {
+    /// Seconds left
+    pub seconds: u64,
+    pub command: String,
+}
+
+impl Milestone {
+    /// Whether progress moving from `from` to `to` percent passes this milestone.
+    pub fn crossed(&self, from: f64, to: f64) -> bool {
+        from < self.percent && self.percent <= to
+    }
+}
+
+/// Reads `50%:COMMAND`, the percent sign is optional and fractions like `12.5` are fine.
+pub fn parse_milestone(input: &str) -> Result<Milestone, String> {
+    let (percent, command) = input.split_once(':').ok_or("expected PERCENT:COMMAND, like 50%:'notify-send half'")?;
+    let percent: f64 = percent.trim().trim_end_matches('%').parse().map_err(|_| format!("invalid percentage: {}", percent))?;
+    if !(percent > 0.0 && percent < 100.0) {
+        return Err(format!("{}% is not between 0% and 100%, use --execute for the end", percent));
+    }
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("missing command after the percentage".to_string());
+    }
+    Ok(Milestone { percent, command: command.to_string() })
+}
+
+/// Reads `5m:COMMAND`, the duration in any form `--target` takes after the `+`.
+pub fn parse_before_end(input: &str) -> Result<BeforeEnd, String> {
+    let (duration, command) = input.split_once(':').ok_or("expected DURATION:COMMAND, like 5m:'notify-send \"wrap up\"'")?;
+    let seconds = parse_duration(duration)?.num_seconds();
+    if seconds <= 0 {
+        return Err(format!("{} is not a duration before the end", duration));
+    }
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("missing command after the duration".to_string());
+    }
+    Ok(BeforeEnd { seconds: seconds as u64, command: command.to_string() })
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::io::{
+    self,
+    Read,
+    Write
+};
+use std::net::{
+    TcpStream,
+    ToSocketAddrs
+};
+use std::sync::mpsc::{
+    self,
+    Receiver,
+    Sender
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use crate::countdown::Countdown;
+
+const DEFAULT_PORT: u16 = 1883;
+pub const DEFAULT_TOPIC: &str = "endzeit";
+/// How long connecting to the broker may take, and how long to wait before trying again.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// `--mqtt tcp://[USER:PASSWORD@]HOST[:PORT]`: the broker the selected timer is published to.
+#[derive(Clone, PartialEq)]
+pub struct Broker {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Reads `tcp://` and `mqtt://` URLs or a bare `HOST[:PORT]`. There is no TLS, so `mqtts://` is refused.
+pub fn parse_broker(url: &str) -> Result<Broker, String> {
+    let address = match url.split_once("://") {
+        Some(("tcp" | "mqtt", address)) => address,
+        Some((scheme, _)) => return Err(format!("unsupported scheme {}://, expected tcp:// or mqtt://", scheme)),
+        None => url,
+    };
+    let address = address.trim_end_matches('/');
+    let (credentials, address) = match address.rsplit_once('@') {
+        Some((credentials, address)) => (Some(credentials), address),
+        None => (None, address),
+    };
+    let (username, password) = match credentials.map(|credentials| credentials.split_once(':')) {
+        Some(Some((username, password))) => (Some(username.to_string()), Some(password.to_string())),
+        Some(None) => (credentials.map(str::to_string), None),
+        None => (None, None),
+    };
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| format!("invalid port {}", port))?),
+        None => (address, DEFAULT_PORT),
+    };
+    if host.is_empty() {
+        return Err(format!("missing host in {}", url));
+    }
+    Ok(Broker { host: host.to_string(), port, username, password })
+}
+
+/// Publishes the name, state and seconds left of a timer under `<topic>/name`, `<topic>/state`
+/// and `<topic>/remaining`, retained so a dashboard shows them right away. Only changes go out.
+/// A background thread talks to the broker, and connects again when it loses it.
+pub struct Mqtt {
+    topic: String,
+    sender: Sender<(String, String)>,
+    /// The last payload of every subtopic
+    published: HashMap<&'static str, String>,
+}
+
+impl Mqtt {
+    pub fn connect(broker: Broker, topic: String) -> Self {
+        let (sender, messages) = mpsc::channel();
+        let will = format!("{}/state", topic);
+        thread::spawn(move || run(broker, will, messages));
+        Self { topic, sender, published: HashMap::new() }
+    }
+
+    pub fn publish(&mut self, countdown: &Countdown, name: &str) {
+        let state = if countdown.is_cancelled() {
+            "cancelled"
+        } else if countdown.is_finished() {
+            "finished"
+        } else if countdown.is_paused() {
+            "paused"
+        } else {
+            "running"
+        };
+        let values = [
+            ("name", name.to_string()),
+            ("state", state.to_string()),
+            ("remaining", countdown.get_remaining_time().seconds().to_string()),
+        ];
+        for (subtopic, value) in values {
+            if self.published.get(subtopic) != Some(&value) {
+                let _ = self.sender.send((format!("{}/{}", self.topic, subtopic), value.clone()));
+                self.published.insert(subtopic, value);
+            }
+        }
+    }
+}
+
+/// The thread of `Mqtt`. Keeps the latest payload of every topic, so after connecting again the
+/// broker gets what changed meanwhile. The broker marks the state `offline` when the connection drops.
+fn run(broker: Broker, will: String, messages: Receiver<(String, String)>) {
+    let mut latest: HashMap<String, String> = HashMap::new();
+    let mut stream: Option<TcpStream> = None;
+    let mut last_attempt: Option<Instant> = None;
+    while let Ok((topic, payload)) = messages.recv() {
+        let mut changed = vec![(topic, payload)];
+        changed.extend(messages.try_iter());
+        for (topic, payload) in &changed {
+            latest.insert(topic.clone(), payload.clone());
+        }
+        let sent = match &mut stream {
+            Some(stream) => changed.iter().try_for_each(|(topic, payload)| stream.write_all(&publish_packet(topic, payload))),
+            None if last_attempt.is_none_or(|last| last.elapsed() >= RECONNECT_INTERVAL) => {
+                last_attempt = Some(Instant::now());
+                stream = open(&broker, &will).ok();
+                match &mut stream {
+                    Some(stream) => latest.iter().try_for_each(|(topic, payload)| stream.write_all(&publish_packet(topic, payload))),
+                    None => continue,
+                }
+            }
+            None => continue,
+        };
+        if sent.is_err() {
+            stream = None;
+        }
+    }
+}
+
+/// Connects and waits for the broker to accept, with a will that sets the state to `offline`.
+fn open(broker: &Broker, will: &str) -> io::Result<TcpStream> {
+    let address = (broker.host.as_str(), broker.port).to_socket_addrs()?.next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address for the broker"))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.write_all(&connect_packet(broker, will))?;
+    let mut connack = [0; 4];
+    stream.read_exact(&mut connack)?;
+    if connack[0] != 0x20 || connack[3] != 0 {
+        return Err(io::Error::new(io::ErrorKind::ConnectionRefused, format!("broker refused with code {}", connack[3])));
+    }
+    Ok(stream)
+}
+
+/// CONNECT of MQTT 3.1.1 with a clean session, no keep alive and a retained will.
+fn connect_packet(broker: &Broker, will: &str) -> Vec<u8> {
+    // Clean session, will flag and will retain
+    let mut flags = 0x02 | 0x04 | 0x20;
+    let mut body = Vec::new();
+    push_string(&mut body, b"MQTT");
+    body.push(4);
+    let flags_at = body.len();
+    body.push(0);
+    body.extend_from_slice(&0u16.to_be_bytes());
+    push_string(&mut body, format!("endzeit-{}", std::process::id()).as_bytes());
+    push_string(&mut body, will.as_bytes());
+    push_string(&mut body, b"offline");
+    if let Some(username) = &broker.username {
+        flags |= 0x80;
+        push_string(&mut body, username.as_bytes());
+    }
+    if let Some(password) = &broker.password {
+        flags |= 0x40;
+        push_string(&mut body, password.as_bytes());
+    }
+    body[flags_at] = flags;
+    packet(0x10, body)
+}
+
+/// PUBLISH at QoS 0, retained.
+fn publish_packet(topic: &str, payload: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_string(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload.as_bytes());
+    packet(0x31, body)
+}
+
+/// The fixed header, with the remaining length in 7 bit groups, then `body`.
+fn packet(kind: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet = vec![kind];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend(body);
+    packet
+}
+
+fn push_string(buffer: &mut Vec<u8>, string: &[u8]) {
+    buffer.extend_from_slice(&(string.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(string);
+}
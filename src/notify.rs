@@ -0,0 +1,246 @@
+use std::io::{
+    self,
+    Write
+};
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf
+};
+use std::process::{
+    Command,
+    Stdio
+};
+use std::thread;
+use std::time::Duration;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::exec::find_program;
+use crate::machine::Machine;
+
+/// How long the terminal stays inverted for a flash.
+const FLASH_DURATION: Duration = Duration::from_millis(150);
+
+/// Sounds of the system played by the `sound` channel by default, the first one that exists is used.
+const SYSTEM_SOUNDS: [(&str, &str); 3] = [
+    ("paplay", "/usr/share/sounds/freedesktop/stereo/complete.oga"),
+    ("pw-play", "/usr/share/sounds/freedesktop/stereo/complete.oga"),
+    ("afplay", "/System/Library/Sounds/Glass.aiff"),
+];
+/// Sounds built into the binary, selected with `--sound builtin:NAME`. Played where there are no system sounds.
+const BUILTIN_SOUNDS: [(&str, &[u8]); 4] = [
+    ("chime", include_bytes!("../assets/sounds/chime.wav")),
+    ("bell", include_bytes!("../assets/sounds/bell.wav")),
+    ("ding", include_bytes!("../assets/sounds/ding.wav")),
+    ("tick", include_bytes!("../assets/sounds/tick.wav")),
+];
+/// The built-in sound played where there is no system sound, and by `--sound` without a file.
+pub const DEFAULT_SOUND: &str = "chime";
+/// Players for WAV files, the first one installed is used.
+const WAV_PLAYERS: [&str; 4] = ["paplay", "pw-play", "afplay", "aplay"];
+
+/// A way of telling the user about a milestone, one step of the escalation ladder.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// Flash the terminal, only counts while it is focused
+    Flash,
+    /// Desktop notification through notify-send or osascript
+    Desktop,
+    /// Play --sound, or the system's completion sound, through rodio or paplay, pw-play, afplay or aplay
+    Sound,
+    /// POST the message as JSON to the --webhook URL with curl
+    Webhook,
+}
+
+/// What the `sound` channel plays.
+#[derive(Clone, Default)]
+pub enum Sound {
+    /// The system's completion sound, or the built-in chime without one
+    #[default]
+    System,
+    Builtin(&'static str),
+    File(PathBuf),
+}
+
+/// Reads `builtin:NAME` or the path of a sound file.
+pub fn parse_sound(sound: &str) -> Result<Sound, String> {
+    let Some(name) = sound.strip_prefix("builtin:") else {
+        return Ok(Sound::File(PathBuf::from(sound)));
+    };
+    match BUILTIN_SOUNDS.iter().find(|(builtin, _)| *builtin == name) {
+        Some((name, _)) => Ok(Sound::Builtin(name)),
+        None => {
+            let names: Vec<&str> = BUILTIN_SOUNDS.iter().map(|(name, _)| *name).collect();
+            Err(format!("Unknown sound builtin:{}, built in are {}", name, names.join(", ")))
+        }
+    }
+}
+
+/// How often `--tick` ticks.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tick {
+    Second,
+    Minute,
+}
+
+impl Tick {
+    pub const SOUND: Sound = Sound::Builtin("tick");
+
+    pub fn seconds(self) -> u64 {
+        match self {
+            Tick::Second => 1,
+            Tick::Minute => 60,
+        }
+    }
+}
+
+/// Milestones go to the first channel of the ladder that reaches the user instead of all at once.
+#[derive(Clone, Default)]
+pub struct Escalation {
+    ladder: Vec<Channel>,
+    webhook: Option<String>,
+    /// Schema of the webhook payload, a plain `{title, text}` without
+    machine: Option<Machine>,
+    sound: Sound,
+    /// Percent
+    volume: u8,
+}
+
+impl Escalation {
+    pub fn new(ladder: Vec<Channel>, webhook: Option<String>, machine: Option<Machine>, sound: Sound, volume: u8) -> Self {
+        Self { ladder, webhook, machine, sound, volume }
+    }
+
+    /// Walks the ladder until a channel delivers `message`. Channels that aren't available here
+    /// are skipped, and so is the flash while the terminal is not focused.
+    pub fn notify(&self, title: &str, message: &str, focused: bool) {
+        for channel in &self.ladder {
+            let delivered = match channel {
+                Channel::Flash => focused && flash().is_ok(),
+                Channel::Desktop => desktop(title, message),
+                Channel::Sound => play(&self.sound, self.volume),
+                Channel::Webhook => self.webhook.as_deref().is_some_and(|url| webhook(url, self.machine, title, message)),
+            };
+            if delivered {
+                return;
+            }
+        }
+    }
+}
+
+/// Inverts the terminal for a moment.
+fn flash() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(b"\x1b[?5h")?;
+    stdout.flush()?;
+    thread::sleep(FLASH_DURATION);
+    stdout.write_all(b"\x1b[?5l")?;
+    stdout.flush()
+}
+
+fn desktop(title: &str, message: &str) -> bool {
+    #[cfg(all(feature = "dbus", target_os = "linux"))]
+    if crate::dbus::notify(title, message) {
+        return true;
+    }
+    if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title {:?}", message, title);
+        return spawn(Command::new("osascript").args(["-e", &script]));
+    }
+    find_program("notify-send") && spawn(Command::new("notify-send").args([title, message]))
+}
+
+/// Plays `sound` at `volume` percent without waiting for it, returns false if nothing could play it.
+/// With the `audio` feature rodio plays it, and the players are the fallback without an audio device.
+pub fn play(sound: &Sound, volume: u8) -> bool {
+    #[cfg(feature = "audio")]
+    if crate::audio::play(sound, volume).is_ok() {
+        return true;
+    }
+    match sound {
+        Sound::System => match SYSTEM_SOUNDS.iter().find(|(player, file)| Path::new(file).is_file() && find_program(player)) {
+            Some((player, file)) => spawn(Command::new(player).args(volume_args(player, volume)).arg(file)),
+            None => play_builtin(DEFAULT_SOUND, volume),
+        },
+        Sound::Builtin(name) => play_builtin(name, volume),
+        Sound::File(file) => play_file(file, volume),
+    }
+}
+
+fn play_builtin(builtin: &str, volume: u8) -> bool {
+    builtin_file(builtin).is_some_and(|file| play_file(&file, volume))
+}
+
+fn play_file(file: &Path, volume: u8) -> bool {
+    if cfg!(target_os = "macos") {
+        return spawn(Command::new("afplay").args(volume_args("afplay", volume)).arg(file));
+    }
+    WAV_PLAYERS.iter().find(|player| find_program(player)).is_some_and(|player| {
+        spawn(Command::new(player).args(volume_args(player, volume)).arg(file))
+    })
+}
+
+/// The arguments that set the volume of `player`, none at full volume. aplay has no volume.
+fn volume_args(player: &str, volume: u8) -> Vec<String> {
+    if volume >= 100 {
+        return Vec::new();
+    }
+    match player {
+        // 65536 is the normal volume of PulseAudio
+        "paplay" => vec![format!("--volume={}", volume as u32 * 65536 / 100)],
+        "pw-play" => vec![format!("--volume={}", volume as f32 / 100.0)],
+        "afplay" => vec!["-v".to_string(), format!("{}", volume as f32 / 100.0)],
+        _ => Vec::new(),
+    }
+}
+
+/// The system sounds that exist on this machine.
+#[cfg(feature = "audio")]
+pub fn system_sounds() -> impl Iterator<Item = &'static Path> {
+    SYSTEM_SOUNDS.iter().map(|(_, file)| Path::new(*file)).filter(|file| file.is_file())
+}
+
+/// The bytes of the built-in sound `name`, a WAV file.
+pub fn builtin_sound(name: &str) -> Option<&'static [u8]> {
+    BUILTIN_SOUNDS.iter().find(|(builtin, _)| *builtin == name).map(|(_, bytes)| *bytes)
+}
+
+/// Players need a file, so built-in sounds are written to `<cache dir>/endzeit/sounds` the first time they play.
+fn builtin_file(name: &str) -> Option<PathBuf> {
+    let bytes = builtin_sound(name)?;
+    let dir = dirs::cache_dir().unwrap_or_else(std::env::temp_dir).join("endzeit").join("sounds");
+    let file = dir.join(format!("{}.wav", name));
+    if fs::metadata(&file).is_ok_and(|metadata| metadata.len() == bytes.len() as u64) {
+        return Some(file);
+    }
+    fs::create_dir_all(&dir).ok()?;
+    fs::write(&file, bytes).ok()?;
+    Some(file)
+}
+
+fn webhook(url: &str, machine: Option<Machine>, title: &str, message: &str) -> bool {
+    if !find_program("curl") {
+        return false;
+    }
+    let body = match machine {
+        Some(machine) => machine.document("milestone", serde_json::json!({ "title": title, "message": message })),
+        None => serde_json::json!({ "title": title, "text": message }),
+    }.to_string();
+    spawn(Command::new("curl").args(["-fsS", "-m", "10", "-H", "Content-Type: application/json", "-d", &body, url]))
+}
+
+/// Starts `command` without waiting for it, so slow channels don't hold up the countdown.
+pub fn spawn(command: &mut Command) -> bool {
+    let child = command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+    match child {
+        Ok(mut child) => {
+            // Reap it in the background
+            thread::spawn(move || child.wait());
+            true
+        }
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,79 @@
+use std::io;
+use clap::ValueEnum;
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        cursor,
+        event::{
+            DisableFocusChange,
+            DisableMouseCapture,
+            EnableFocusChange,
+            EnableMouseCapture
+        },
+        execute,
+        terminal
+    },
+    layout::Rect,
+    DefaultTerminal,
+    Terminal,
+    TerminalOptions,
+    Viewport,
+};
+use serde::Deserialize;
+
+/// How the countdowns are shown.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Output {
+    /// Interactive full screen terminal UI
+    Tui,
+    /// Plain ANSI frames without alternate screen, raw mode or input, for hosts that pass output
+    /// through like Zellij panes and plugins
+    Ansi,
+    /// A line of plain ASCII text whenever the time left changes, without colors or escape sequences,
+    /// for dumb terminals, serial consoles and logs
+    Plain,
+    /// A line of JSON with text, tooltip, class and percentage whenever it changes, for the custom
+    /// modules of waybar and i3status-rust
+    Waybar,
+    /// A line of plain text after `--format` whenever it changes, for polybar, tmux and other
+    /// status lines fed by a pipe
+    Line,
+}
+
+/// Creates a terminal that draws ANSI frames straight into stdout. When stdout is not a terminal
+/// there is no size to follow, then `height` rows of 80 columns are drawn.
+pub fn ansi_terminal(height: u16) -> io::Result<DefaultTerminal> {
+    let viewport = match terminal::size() {
+        Ok(_) => Viewport::Fullscreen,
+        Err(_) => Viewport::Fixed(Rect::new(0, 0, 80, height)),
+    };
+    let mut terminal = Terminal::with_options(CrosstermBackend::new(io::stdout()), TerminalOptions { viewport })?;
+    terminal.clear()?;
+    execute!(io::stdout(), cursor::Hide)?;
+    Ok(terminal)
+}
+
+pub fn restore_ansi_terminal() -> io::Result<()> {
+    execute!(io::stdout(), cursor::Show)?;
+    println!();
+    Ok(())
+}
+
+/// Reports clicks and scrolling as events, the terminal's own selection stops working meanwhile.
+pub fn capture_mouse() -> io::Result<()> {
+    execute!(io::stdout(), EnableMouseCapture)
+}
+
+pub fn release_mouse() -> io::Result<()> {
+    execute!(io::stdout(), DisableMouseCapture)
+}
+
+/// Reports when the terminal gains or loses focus, for notifications that are only seen in it.
+pub fn report_focus() -> io::Result<()> {
+    execute!(io::stdout(), EnableFocusChange)
+}
+
+pub fn stop_reporting_focus() -> io::Result<()> {
+    execute!(io::stdout(), DisableFocusChange)
+}
@@ -0,0 +1,75 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::KeyCode,
+    layout::Rect,
+    style::{
+        Modifier,
+        Style
+    },
+    text::Line,
+    widgets::{
+        Block,
+        Borders,
+        Clear,
+        Paragraph,
+        Widget
+    },
+};
+
+/// The captured output of the commands a timer ran, in a scrollable popup over the gauges.
+pub struct OutputPane {
+    lines: Vec<Line<'static>>,
+    scroll: u16,
+}
+
+impl OutputPane {
+    pub fn new() -> Self {
+        Self { lines: Vec::new(), scroll: 0 }
+    }
+
+    /// Adds the output of one command under a heading naming the timer and the command.
+    pub fn push(&mut self, name: &str, command: &str, output: &str) {
+        if !self.lines.is_empty() {
+            self.lines.push(Line::default());
+        }
+        self.lines.push(Line::styled(format!("{}: {}", name, command), Style::default().add_modifier(Modifier::BOLD)));
+        self.lines.extend(output.lines().map(|line| Line::from(line.replace('\t', "    "))));
+    }
+
+    /// Scrolls with the arrow keys, page up/down, home and end. Returns false for any other key,
+    /// which closes the pane.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        let last = self.lines.len().saturating_sub(1) as u16;
+        self.scroll = match code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll.saturating_add(1).min(last),
+            KeyCode::PageUp => self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll.saturating_add(10).min(last),
+            KeyCode::Home => 0,
+            KeyCode::End => last,
+            _ => return false,
+        };
+        true
+    }
+}
+
+impl Widget for &OutputPane {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Leaves a frame of the gauges visible around it
+        let popup = Rect::new(
+            area.x + area.width / 10,
+            area.y + area.height / 10,
+            area.width - area.width / 5,
+            area.height - area.height / 5,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Command output")
+            .title_bottom("↑/↓ scroll, any other key closes");
+        Clear.render(popup, buf);
+        Paragraph::new(self.lines.clone())
+            .block(block)
+            .scroll((self.scroll, 0))
+            .render(popup, buf);
+    }
+}
@@ -0,0 +1,49 @@
+use std::env;
+use std::process::Command;
+use color_eyre::Result;
+
+use crate::exec::{
+    find_program,
+    shell_quote
+};
+use crate::exit_with;
+
+/// Columns of the popup, tmux shrinks it to fit the client.
+const POPUP_WIDTH: u16 = 60;
+/// Rows of each timer, like the stacked gauges of the TUI.
+const TIMER_ROWS: u16 = 3;
+/// The border tmux draws around the popup.
+const BORDER_ROWS: u16 = 2;
+
+/// `endzeit popup`: runs endzeit with `args` in a `tmux display-popup` over the current pane and
+/// waits until it's closed. The popup is as high as the `timers` need, tmux's default size when
+/// they aren't known, and gets the directory and the `ENDZEIT_*` variables of this shell.
+pub fn run(args: &[String], timers: Option<usize>) -> Result<()> {
+    if env::var_os("TMUX").is_none() {
+        exit_with("endzeit popup needs to run inside tmux");
+    }
+    if !find_program("tmux") {
+        exit_with("endzeit popup needs tmux");
+    }
+    let exe = env::current_exe().unwrap_or_else(|e| exit_with(&format!("Failed to find the endzeit binary: {}", e)));
+    let command_line: Vec<String> = std::iter::once(exe.to_string_lossy().to_string()).chain(args.iter().cloned())
+        .map(|word| shell_quote(&word))
+        .collect();
+
+    let mut tmux = Command::new("tmux");
+    tmux.arg("display-popup").arg("-E");
+    if let Some(timers) = timers {
+        let height = timers.max(1) as u16 * TIMER_ROWS + BORDER_ROWS;
+        tmux.args(["-w", &POPUP_WIDTH.to_string(), "-h", &height.to_string()]);
+    }
+    if let Ok(cwd) = env::current_dir() {
+        tmux.arg("-d").arg(cwd);
+    }
+    // The popup starts from the environment of the tmux server, not of this shell
+    for (name, value) in env::vars().filter(|(name, _)| name.starts_with("ENDZEIT_")) {
+        tmux.args(["-e", &format!("{}={}", name, value)]);
+    }
+    let status = tmux.arg(command_line.join(" ")).status()
+        .unwrap_or_else(|e| exit_with(&format!("Failed to run tmux: {}", e)));
+    std::process::exit(status.code().unwrap_or(1));
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+use clap::Args;
+use serde::Deserialize;
+
+use crate::exec::{
+    Launcher,
+    Shell
+};
+use crate::spec::{
+    parse_color,
+    parse_when,
+    TargetSpec
+};
+
+/// A named countdown from the `[presets]` table of the config file.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    /// Same format as the WHEN part of `--target`, e.g. `09:30` or `+25m`
+    pub target: String,
+    pub name: Option<String>,
+    /// Groups it with other timers in `endzeit ctl tree`
+    pub project: Option<String>,
+    pub color: Option<String>,
+    pub execute: Option<String>,
+    /// Directory `execute` runs in
+    pub cwd: Option<PathBuf>,
+    pub shell: Option<Shell>,
+}
+
+/// Flags given to `endzeit preset`, these take precedence over the preset's own values.
+#[derive(Args, Default)]
+pub struct PresetOverrides {
+    /// Override the target, same format as the WHEN part of --target
+    #[clap(long)]
+    target: Option<String>,
+
+    /// Override the name of the countdown
+    #[clap(long)]
+    name: Option<String>,
+
+    /// Override the gauge color
+    #[clap(long)]
+    color: Option<String>,
+
+    /// Override the command to execute when the countdown finishes
+    #[clap(long)]
+    execute: Option<String>,
+}
+
+impl Preset {
+    pub fn merge(self, overrides: PresetOverrides) -> Self {
+        Self {
+            target: overrides.target.unwrap_or(self.target),
+            name: overrides.name.or(self.name),
+            project: self.project,
+            color: overrides.color.or(self.color),
+            execute: overrides.execute.or(self.execute),
+            cwd: self.cwd,
+            shell: self.shell,
+        }
+    }
+
+    /// Resolves the preset into a countdown, `key` names it if the preset has no name of its own.
+    pub fn resolve(self, key: &str) -> Result<TargetSpec, String> {
+        let (source, target) = parse_when(&self.target)?;
+        Ok(TargetSpec {
+            when: self.target,
+            target,
+            source,
+            name: Some(self.name.unwrap_or_else(|| key.to_string())),
+            project: self.project,
+            color: self.color.as_deref().map(parse_color).transpose()?,
+            execute: self.execute.into_iter().collect(),
+            launcher: Launcher { cwd: self.cwd, shell: self.shell, detach: false, timeout: None },
+        })
+    }
+}
@@ -0,0 +1,104 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::KeyCode,
+    layout::{
+        Alignment,
+        Constraint,
+        Flex,
+        Layout,
+        Rect
+    },
+    style::{
+        Color,
+        Style
+    },
+    text::Line,
+    widgets::{
+        Block,
+        Borders,
+        Clear,
+        Paragraph,
+        Widget
+    },
+};
+
+/// A single-line text input shown as a popup over the gauges.
+pub struct Prompt {
+    title: String,
+    value: String,
+    error: Option<String>,
+}
+
+pub enum PromptAction {
+    None,
+    Submit(String),
+    Cancel,
+}
+
+impl Prompt {
+    pub fn new(title: &str, value: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            value: value.to_string(),
+            error: None,
+        }
+    }
+
+    /// Keeps the prompt open and shows why the submitted value was rejected.
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> PromptAction {
+        match code {
+            KeyCode::Enter => return PromptAction::Submit(self.value.clone()),
+            KeyCode::Esc => return PromptAction::Cancel,
+            KeyCode::Backspace => {
+                self.value.pop();
+            }
+            KeyCode::Char(c) => self.value.push(c),
+            _ => return PromptAction::None,
+        }
+        self.error = None;
+        PromptAction::None
+    }
+}
+
+impl Widget for &Prompt {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = centered(area, 60, 3);
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.as_str());
+        if let Some(error) = &self.error {
+            block = block.title_bottom(Line::styled(error.as_str(), Style::default().fg(Color::Red)));
+        }
+
+        Clear.render(area, buf);
+        Paragraph::new(format!("{}_", self.value))
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+/// A yes/no question shown as a small centered modal, the text names the answers.
+pub struct Confirm<'a>(pub &'a str);
+
+impl Widget for Confirm<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let text = self.0;
+        let area = centered(area, text.chars().count() as u16 + 4, 3);
+        Clear.render(area, buf);
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL))
+            .render(area, buf);
+    }
+}
+
+/// Returns a rect of at most the given size centered in `area`.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center).areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    area
+}
@@ -0,0 +1,263 @@
+use std::fs::{
+    self,
+    OpenOptions
+};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::process::{
+    Command,
+    Stdio
+};
+use std::thread;
+use chrono::Local;
+use serde::Deserialize;
+
+use crate::clock;
+use crate::countdown::Countdown;
+use crate::exec::find_program;
+use crate::notify::spawn;
+use crate::units::format_seconds;
+
+/// Where topics without a server go.
+const NTFY_SERVER: &str = "https://ntfy.sh";
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+const TELEGRAM_API: &str = "https://api.telegram.org";
+/// The side bar of Discord embeds, endzeit's cyan.
+const DISCORD_COLOR: u32 = 0x00afaf;
+
+/// A service that gets the news of a timer to a phone or chat, posted with curl: that it finished,
+/// and that it fell to a threshold of `--notify-at`.
+#[derive(Clone)]
+pub enum Push {
+    /// `--ntfy TOPIC`, the URL of the topic
+    Ntfy(String),
+    /// `--pushover TOKEN/USER`, the token of the application and the key of the user
+    Pushover { token: String, user: String },
+    /// `--notify=slack:URL`, an incoming webhook
+    Slack(String),
+    /// `--notify=discord:URL`, a channel webhook
+    Discord(String),
+    /// `--telegram TOKEN:CHAT`, the token of the bot and the chat it writes to
+    Telegram { token: String, chat: String },
+    /// `[notify.email]`, only told about finished timers
+    Email(Email),
+}
+
+/// `[notify.email]`: an SMTP server that mails a summary of every finished timer.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Email {
+    /// `smtp.example.com:587`, upgraded with STARTTLS, or an `smtps://` URL
+    pub server: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: String,
+}
+
+/// A value of `--notify`.
+#[derive(Clone)]
+pub enum NotifyTarget {
+    Desktop,
+    /// `false` and the like, for `ENDZEIT_NOTIFY`
+    Off,
+    Push(Push),
+}
+
+/// Reads `desktop`, `slack:URL` or `discord:URL`. Yes and no in the words of `BoolishValueParser`
+/// turn the desktop notification on and off, as before there were targets.
+pub fn parse_notify(value: &str) -> Result<NotifyTarget, String> {
+    if let Some(url) = value.strip_prefix("slack:") {
+        return Push::parse_slack(url).map(NotifyTarget::Push);
+    }
+    if let Some(url) = value.strip_prefix("discord:") {
+        return Push::parse_discord(url).map(NotifyTarget::Push);
+    }
+    match value.trim().to_ascii_lowercase().as_str() {
+        "desktop" | "true" | "t" | "yes" | "y" | "on" | "1" => Ok(NotifyTarget::Desktop),
+        "false" | "f" | "no" | "n" | "off" | "0" | "" => Ok(NotifyTarget::Off),
+        _ => Err(format!("unknown target {:?}, expected desktop, slack:URL or discord:URL", value)),
+    }
+}
+
+fn parse_webhook(service: &str, url: &str) -> Result<String, String> {
+    let url = url.trim();
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("expected the URL of a {} webhook, got {:?}", service, url));
+    }
+    Ok(url.to_string())
+}
+
+impl Push {
+    /// Reads a topic on ntfy.sh, or the URL of one on another server.
+    pub fn parse_ntfy(topic: &str) -> Result<Self, String> {
+        let topic = topic.trim().trim_end_matches('/');
+        if topic.contains("://") {
+            return Ok(Push::Ntfy(topic.to_string()));
+        }
+        if topic.is_empty() || topic.contains('/') {
+            return Err(format!("invalid ntfy topic {:?}, expected a name like endzeit-alerts or its URL", topic));
+        }
+        Ok(Push::Ntfy(format!("{}/{}", NTFY_SERVER, topic)))
+    }
+
+    pub fn parse_slack(url: &str) -> Result<Self, String> {
+        parse_webhook("Slack", url).map(Push::Slack)
+    }
+
+    pub fn parse_discord(url: &str) -> Result<Self, String> {
+        parse_webhook("Discord", url).map(Push::Discord)
+    }
+
+    /// Reads `TOKEN:CHAT`. The token has a colon of its own, so the chat is what follows the last one.
+    pub fn parse_telegram(keys: &str) -> Result<Self, String> {
+        match keys.trim().rsplit_once(':') {
+            Some((token, chat)) if token.contains(':') && !chat.is_empty() => {
+                Ok(Push::Telegram { token: token.to_string(), chat: chat.to_string() })
+            }
+            _ => Err("expected the bot token and the chat id as TOKEN:CHAT, like 123456:ABC-DEF:-1001234".to_string()),
+        }
+    }
+
+    /// Reads `TOKEN/USER`.
+    pub fn parse_pushover(keys: &str) -> Result<Self, String> {
+        match keys.trim().split_once('/') {
+            Some((token, user)) if !token.is_empty() && !user.is_empty() => {
+                Ok(Push::Pushover { token: token.to_string(), user: user.to_string() })
+            }
+            _ => Err("expected the application token and the user key as TOKEN/USER".to_string()),
+        }
+    }
+
+    /// Posts `message` about `countdown`, called `name`, without waiting for the answer, returns false
+    /// without curl. Chats get it laid out with the name and the target time in fields of their own.
+    /// Mails only go out once the timer `finished`.
+    pub fn send(&self, countdown: &Countdown, name: &str, message: &str, finished: bool) -> bool {
+        if !find_program("curl") {
+            return false;
+        }
+        let target = countdown.target().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut command = Command::new("curl");
+        command.args(["-fsS", "-m", "10"]);
+        match self {
+            Push::Ntfy(url) => command.args(["-H", "Title: endzeit", "--data-raw", message, url]),
+            Push::Pushover { token, user } => {
+                command.args(["--form-string", "title=endzeit", "--form-string", &format!("message={}", message), PUSHOVER_URL]);
+                let config = format!("form-string = {:?}\nform-string = {:?}\n", format!("token={}", token), format!("user={}", user));
+                return spawn_with_config(&mut command, &config);
+            }
+            Push::Slack(url) => {
+                let body = serde_json::json!({
+                    // The text of the notification, and what shows where blocks can't
+                    "text": message,
+                    "blocks": [
+                        { "type": "header", "text": { "type": "plain_text", "text": message } },
+                        { "type": "section", "fields": [
+                            { "type": "mrkdwn", "text": format!("*Timer*\n{}", name) },
+                            { "type": "mrkdwn", "text": format!("*Target*\n{}", target) },
+                        ] },
+                    ],
+                });
+                command.args(["-H", "Content-Type: application/json", "-d", &body.to_string(), url])
+            }
+            Push::Discord(url) => {
+                let body = serde_json::json!({
+                    "username": "endzeit",
+                    "embeds": [{
+                        "title": message,
+                        "color": DISCORD_COLOR,
+                        "fields": [
+                            { "name": "Timer", "value": name, "inline": true },
+                            { "name": "Target", "value": target, "inline": true },
+                        ],
+                    }],
+                });
+                command.args(["-H", "Content-Type: application/json", "-d", &body.to_string(), url])
+            }
+            Push::Telegram { token, chat } => {
+                command.args(["--data-urlencode", &format!("chat_id={}", chat), "--data-urlencode", &format!("text={}", message)]);
+                // The token is part of the URL
+                let config = format!("url = {:?}\n", format!("{}/bot{}/sendMessage", TELEGRAM_API, token));
+                return spawn_with_config(&mut command, &config);
+            }
+            Push::Email(email) if finished => return email.send(countdown, name, message),
+            Push::Email(_) => return false,
+        };
+        spawn(&mut command)
+    }
+}
+
+impl Email {
+    /// Mails the summary through curl. The mail goes through a new file in the temp dir only the
+    /// user can read and the credentials through curl's config on stdin, so neither shows up in
+    /// the process list.
+    fn send(&self, countdown: &Countdown, name: &str, subject: &str) -> bool {
+        let summary = [
+            ("Timer", name.to_string()),
+            ("Target", countdown.target().format("%Y-%m-%d %H:%M:%S").to_string()),
+            ("Finished", clock::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+            ("Duration", format_seconds(countdown.total_duration())),
+        ];
+        let mut mail = format!(
+            "From: endzeit <{}>\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from, self.to, subject, Local::now().to_rfc2822(),
+        );
+        for (label, value) in summary {
+            mail.push_str(&format!("{:<10}{}\r\n", format!("{}:", label), value));
+        }
+        let file = std::env::temp_dir().join(format!("endzeit-mail-{}-{}.eml", std::process::id(), clock::seconds()));
+        // A file planted under the name, or a link to another one, is never written to
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let Ok(mut opened) = options.open(&file) else {
+            return false;
+        };
+        if opened.write_all(mail.as_bytes()).is_err() {
+            let _ = fs::remove_file(&file);
+            return false;
+        }
+
+        let url = if self.server.contains("://") { self.server.clone() } else { format!("smtp://{}", self.server) };
+        let mut config = format!("url = {:?}\nmail-from = {:?}\nmail-rcpt = {:?}\nupload-file = {:?}\n", url, self.from, self.to, file.to_string_lossy());
+        if !url.starts_with("smtps://") {
+            config.push_str("ssl-reqd\n");
+        }
+        if let Some(username) = &self.username {
+            config.push_str(&format!("user = {:?}\n", format!("{}:{}", username, self.password.as_deref().unwrap_or_default())));
+        }
+        let child = Command::new("curl").args(["-fsS", "-m", "30", "-K", "-"])
+            .stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            let _ = fs::remove_file(&file);
+            return false;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(config.as_bytes());
+        }
+        // Reaped in the background, the mail is gone once curl is done with it
+        thread::spawn(move || {
+            let _ = child.wait();
+            let _ = fs::remove_file(&file);
+        });
+        true
+    }
+}
+
+/// Starts curl like `spawn` with `config` on its stdin, for secrets that must not show up in the
+/// process list.
+fn spawn_with_config(command: &mut Command, config: &str) -> bool {
+    let child = command.args(["-K", "-"]).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(config.as_bytes());
+    }
+    // Reap it in the background
+    thread::spawn(move || child.wait());
+    true
+}
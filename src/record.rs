@@ -0,0 +1,142 @@
+use std::fs::{
+    self,
+    File
+};
+use std::io::Write;
+use chrono::NaiveDateTime;
+use ratatui::crossterm::event::KeyCode;
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::clock;
+use crate::control::Control;
+use crate::countdown::Countdown;
+use crate::keys::key_name;
+use crate::spec::parse_color;
+
+/// One line of a session recording. The first line is always `Start`, all later
+/// events carry their time in seconds since the start.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Recorded {
+    Start {
+        at: NaiveDateTime,
+        countdowns: Vec<RecordedCountdown>,
+    },
+    Key {
+        t: f64,
+        key: String,
+    },
+    Retarget {
+        t: f64,
+        index: usize,
+        target: NaiveDateTime,
+    },
+    /// A command from a signal or the control FIFO, in the format of `Control::parse`
+    Control {
+        t: f64,
+        command: String,
+    },
+    /// A timer selected with the mouse, keys pressed afterwards act on it
+    Select {
+        t: f64,
+        index: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecordedCountdown {
+    target: NaiveDateTime,
+    name: Option<String>,
+    color: String,
+}
+
+/// Writes a session to a file as JSON lines, flushing every event so a crash still leaves a usable recording.
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    pub fn create(path: &str, countdowns: &[Countdown]) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        let mut recorder = Self { file };
+        recorder.write(&Recorded::Start {
+            at: clock::now(),
+            countdowns: countdowns.iter().map(|countdown| RecordedCountdown {
+                target: countdown.target(),
+                name: countdown.name.clone(),
+                color: countdown.color.to_string(),
+            }).collect(),
+        });
+        Ok(recorder)
+    }
+
+    pub fn key(&mut self, code: KeyCode) {
+        if let Some(key) = key_name(code) {
+            self.write(&Recorded::Key { t: clock::seconds(), key });
+        }
+    }
+
+    pub fn retarget(&mut self, index: usize, target: NaiveDateTime) {
+        self.write(&Recorded::Retarget { t: clock::seconds(), index, target });
+    }
+
+    pub fn control(&mut self, control: Control) {
+        self.write(&Recorded::Control { t: clock::seconds(), command: control.to_string() });
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.write(&Recorded::Select { t: clock::seconds(), index });
+    }
+
+    fn write(&mut self, event: &Recorded) {
+        // Recording is best effort, a full disk must not take down the countdown
+        if let Ok(line) = serde_json::to_string(event) {
+            let _ = writeln!(self.file, "{}", line);
+        }
+    }
+}
+
+/// A loaded recording: the start time, the countdowns as they were at the start and all later events.
+pub struct Recording {
+    pub start: NaiveDateTime,
+    pub countdowns: Vec<RecordedCountdown>,
+    pub events: Vec<Recorded>,
+}
+
+impl Recording {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let mut events = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let event = serde_json::from_str(line).map_err(|e| format!("{}:{}: {}", path, number + 1, e))?;
+            events.push(event);
+        }
+
+        if events.is_empty() {
+            return Err(format!("{}: Empty recording", path));
+        }
+        let Recorded::Start { at, countdowns } = events.remove(0) else {
+            return Err(format!("{}: Recording does not begin with a start event", path));
+        };
+        Ok(Self { start: at, countdowns, events })
+    }
+}
+
+impl RecordedCountdown {
+    /// Creates the countdown on the current clock. Hooks are not recorded, so replaying never runs commands.
+    pub fn into_countdown(self) -> Result<Countdown, String> {
+        Ok(Countdown::new(self.target, self.name, parse_color(&self.color)?, Vec::new()))
+    }
+}
+
+impl Recorded {
+    pub fn time(&self) -> f64 {
+        match self {
+            Recorded::Start { .. } => 0.0,
+            Recorded::Key { t, .. } | Recorded::Retarget { t, .. } | Recorded::Control { t, .. } | Recorded::Select { t, .. } => *t,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use color_eyre::Result;
+
+use crate::app::App;
+use crate::clock;
+use crate::notify::Escalation;
+use crate::record::Recording;
+use crate::settings::Settings;
+
+/// Re-renders a session recorded with `--record`, `speed` times as fast as it happened.
+/// Only the look and keys of `settings` apply, no hooks or notifications fire during a replay.
+pub fn run(path: &str, speed: f64, settings: Settings) -> Result<()> {
+    let recording = match Recording::load(path) {
+        Ok(recording) => recording,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    clock::simulate(recording.start, speed);
+
+    let countdowns = match recording.countdowns.into_iter().map(|recorded| recorded.into_countdown()).collect() {
+        Ok(countdowns) => countdowns,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+
+    let terminal = ratatui::init();
+    let settings = Settings {
+        on_retarget: None,
+        term_notify: None,
+        escalation: Escalation::default(),
+        term_badge: false,
+        ..settings
+    };
+    let app = App::new(countdowns, settings).replay(recording.events);
+    let app_result = app.run(terminal);
+    ratatui::restore();
+    app_result
+}
+
+/// Parses a replay speed like `20x` or `2.5`.
+pub fn parse_speed(input: &str) -> Result<f64, String> {
+    let speed: f64 = input.trim_end_matches('x').parse().map_err(|_| format!("Invalid speed: {}", input))?;
+    if speed <= 0.0 {
+        return Err("Speed must be greater than zero".to_string());
+    }
+    Ok(speed)
+}
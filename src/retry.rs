@@ -0,0 +1,90 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::mpsc::{
+    self,
+    Receiver
+};
+use std::thread;
+use std::time::Duration;
+
+use crate::clock;
+use crate::countdown::FailedHook;
+use crate::state::state_dir;
+use crate::units::format_seconds;
+
+/// `--execute-retries` and `--execute-backoff`: how often and how soon a failed completion command
+/// is tried again.
+#[derive(Clone, Copy)]
+pub struct Retry {
+    /// Tries after the first one
+    pub retries: u32,
+    /// The wait before the first retry, it doubles for every further one
+    pub backoff: Duration,
+}
+
+/// How one try of a failed command went.
+pub struct Attempt {
+    pub command: String,
+    /// Counting the first try that failed as 1
+    pub number: u32,
+    pub of: u32,
+    pub outcome: Result<(), String>,
+    /// The wait until the next try, None after the last
+    pub next: Option<Duration>,
+}
+
+impl Retry {
+    /// Runs `hook` again on a background thread until it succeeds or the retries are used up.
+    /// Every try, the first failed one included, is logged and sent to the receiver.
+    pub fn start(&self, name: String, hook: FailedHook) -> Receiver<Attempt> {
+        let (sender, receiver) = mpsc::channel();
+        let FailedHook { label, mut command, launcher, outcome } = hook;
+        let of = self.retries + 1;
+        let mut wait = self.backoff;
+        thread::spawn(move || {
+            let mut outcome = Err(outcome);
+            for number in 1..=of {
+                if number > 1 {
+                    // Output captured for the TUI is dropped, there is no pane for retries
+                    outcome = match launcher.start(&mut command) {
+                        Ok(Some(ran)) => ran.failure().map_or(Ok(()), Err),
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(format!("didn't start: {}", e)),
+                    };
+                }
+                let next = (outcome.is_err() && number < of).then_some(wait);
+                let attempt = Attempt { command: label.clone(), number, of, outcome: outcome.clone(), next };
+                log(&name, &attempt);
+                if sender.send(attempt).is_err() || next.is_none() {
+                    return;
+                }
+                thread::sleep(wait);
+                wait *= 2;
+            }
+        });
+        receiver
+    }
+}
+
+impl Attempt {
+    /// The line for the gauge and the log.
+    pub fn note(&self) -> String {
+        match (&self.outcome, self.next) {
+            (Ok(()), _) => format!("`{}` ok on attempt {} of {}", self.command, self.number, self.of),
+            (Err(err), Some(next)) => {
+                format!("`{}` attempt {} of {} {}, next in {}", self.command, self.number, self.of, err, format_seconds(next.as_secs()))
+            }
+            (Err(err), None) => format!("`{}` {} on the last of {} attempts", self.command, err, self.of),
+        }
+    }
+}
+
+/// Appends `attempt` to `<state dir>/endzeit/logs/hooks.log`, best effort.
+fn log(name: &str, attempt: &Attempt) {
+    let Ok(dir) = state_dir("logs") else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(dir.join("hooks.log")) {
+        let _ = writeln!(file, "{}  {}: {}", clock::now().format("%Y-%m-%d %H:%M:%S"), name, attempt.note());
+    }
+}
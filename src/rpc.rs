@@ -0,0 +1,115 @@
+use std::io::{
+    self,
+    BufRead
+};
+use std::sync::mpsc::{
+    self,
+    Sender
+};
+use std::thread;
+use std::time::Duration;
+use chrono::TimeDelta;
+use serde_json::{
+    json,
+    Value
+};
+
+use crate::control::{
+    Control,
+    TimerStatus
+};
+use crate::spec::parse_duration;
+
+/// How long `status` waits for the timers.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(2);
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// `--rpc`: reads JSON-RPC 2.0 requests from stdin, one per line, on a background thread and
+/// answers them on stdout. Controls act on the selected timer like the control FIFO, `status`
+/// asks the app for the timers like `endzeit status` does.
+pub fn listen(controls: Sender<Result<Control, String>>, status_requests: Sender<Sender<Vec<TimerStatus>>>) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = answer(&line, &controls, &status_requests) {
+                println!("{}", response);
+            }
+        }
+    });
+}
+
+/// Writes a notification to stdout, how the app tells about ticks and finished timers.
+pub fn notify(method: &str, params: Value) {
+    println!("{}", json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+}
+
+/// The response to a request, None for notifications, which have no id.
+fn answer(line: &str, controls: &Sender<Result<Control, String>>, status_requests: &Sender<Sender<Vec<TimerStatus>>>) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+    };
+    let id = request.get("id").cloned();
+    let (Some("2.0"), Some(method)) = (request["jsonrpc"].as_str(), request["method"].as_str()) else {
+        return Some(error(id.unwrap_or_default(), INVALID_REQUEST, "expected a JSON-RPC 2.0 request with a method"));
+    };
+    let result = match method {
+        "status" => {
+            let (sender, receiver) = mpsc::channel();
+            let _ = status_requests.send(sender);
+            receiver.recv_timeout(ANSWER_TIMEOUT)
+                .map(|timers| json!(timers))
+                .map_err(|_| (INVALID_REQUEST, "no answer from the timers".to_string()))
+        }
+        "add" | "subtract" => parse_delta(&request["params"]).map_err(|err| (INVALID_PARAMS, err)).map(|delta| {
+            let _ = controls.send(Ok(Control::Adjust(if method == "subtract" { -delta } else { delta })));
+            json!("ok")
+        }),
+        "pause" | "resume" | "toggle" | "cancel" | "dismiss" => Control::parse(method)
+            .map_err(|err| (METHOD_NOT_FOUND, err))
+            .map(|control| {
+                let _ = controls.send(Ok(control));
+                json!("ok")
+            }),
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method {}", method))),
+    };
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, &message),
+    })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Reads `{"seconds": 90}`, `{"minutes": 5}` or `{"duration": "1h30m"}`, several add up.
+fn parse_delta(params: &Value) -> Result<TimeDelta, String> {
+    let out_of_range = || "time out of range".to_string();
+    let mut delta = TimeDelta::zero();
+    for (name, milliseconds_per_unit) in [("seconds", 1000.0), ("minutes", 60_000.0)] {
+        if let Some(amount) = params[name].as_f64() {
+            // Casting saturates, so anything beyond the range of i64 is turned away before
+            let milliseconds = amount * milliseconds_per_unit;
+            let time = Some(milliseconds)
+                .filter(|milliseconds| milliseconds.is_finite() && milliseconds.abs() < i64::MAX as f64)
+                .and_then(|milliseconds| TimeDelta::try_milliseconds(milliseconds as i64))
+                .ok_or_else(out_of_range)?;
+            delta = delta.checked_add(&time).ok_or_else(out_of_range)?;
+        }
+    }
+    if let Some(duration) = params["duration"].as_str() {
+        delta = delta.checked_add(&parse_duration(duration)?).ok_or_else(out_of_range)?;
+    }
+    if delta <= TimeDelta::zero() {
+        return Err("expected seconds, minutes or a duration like {\"minutes\": 5}".to_string());
+    }
+    Ok(delta)
+}
@@ -0,0 +1,55 @@
+use chrono::{
+    Local,
+    NaiveDateTime,
+    TimeZone
+};
+
+use crate::clock;
+use crate::countdown::Countdown;
+use crate::exec::shell_quote;
+use crate::source::TargetSource;
+
+/// Sleeps until a time given in seconds since the epoch, not at all once it has passed.
+const WAIT_UNTIL: &str = r#"wait_until() {
+    now=$(date +%s)
+    if [ "$1" -gt "$now" ]; then
+        sleep $(($1 - now))
+    fi
+}
+"#;
+
+/// `endzeit export-script`: a POSIX shell script that waits for the countdowns in the order they
+/// are due and runs their commands, for machines without endzeit. The targets are written as
+/// absolute timestamps as resolved now, so a script started late runs the ones already past right away.
+pub fn export(countdowns: &[Countdown]) -> String {
+    let mut due: Vec<(usize, &Countdown)> = countdowns.iter().enumerate().collect();
+    due.sort_by_key(|(_, countdown)| countdown.target());
+
+    let mut script = String::from("#!/bin/sh\n");
+    script.push_str(&format!("# Exported by endzeit export-script on {}\n\n", clock::now().format("%Y-%m-%d %H:%M:%S")));
+    script.push_str(WAIT_UNTIL);
+    for (index, countdown) in due {
+        let name = countdown.display_name(index);
+        script.push_str(&format!("\n# {}, due {}\n", name, countdown.target().format("%Y-%m-%d %H:%M:%S")));
+        if let TargetSource::File { path, .. } = &countdown.source {
+            script.push_str(&format!("# The target followed {}, this is where it pointed at the export\n", path.display()));
+        }
+        script.push_str(&format!("wait_until {}\n", timestamp(countdown.target())));
+        script.push_str(&format!("echo {}\n", shell_quote(&format!("{} finished", name))));
+        for command in &countdown.execute_commands {
+            script.push_str(&countdown.launcher.script_line(command));
+            script.push('\n');
+        }
+        if let Some(argv) = &countdown.exec_direct {
+            script.push_str(&countdown.launcher.direct_script_line(argv));
+            script.push('\n');
+        }
+    }
+    script
+}
+
+/// Seconds since the epoch of a local time. Times skipped by a DST change are read as UTC,
+/// repeated ones as their first occurrence.
+fn timestamp(time: NaiveDateTime) -> i64 {
+    Local.from_local_datetime(&time).earliest().map_or_else(|| time.and_utc().timestamp(), |time| time.timestamp())
+}
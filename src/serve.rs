@@ -0,0 +1,293 @@
+use std::fmt::Write as _;
+use std::io::{
+    BufRead,
+    BufReader,
+    Write
+};
+use std::net::{
+    TcpListener,
+    TcpStream
+};
+use std::sync::mpsc::{
+    self,
+    Sender
+};
+use std::thread;
+use std::time::Duration;
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine
+};
+use chrono::TimeDelta;
+use serde::Serialize;
+use sha1_smol::Sha1;
+
+use crate::control::{
+    Control,
+    TimerStatus
+};
+use crate::spec::parse_duration;
+use crate::units::format_seconds;
+
+/// How long a page waits for the timers.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(2);
+/// Appended to the key of a client to accept its WebSocket, from RFC 6455.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A message of `/ws`.
+#[derive(Serialize)]
+struct Tick {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    timers: Vec<TimerStatus>,
+}
+
+const STYLE: &str = "body{background:#111;color:#ddd;font-family:system-ui,sans-serif;max-width:48rem;margin:3rem auto;padding:0 1rem}\
+h1{font-size:1.2rem;color:#0aa;font-weight:normal}\
+section{margin:2rem 0}\
+.name{font-size:1.1rem}\
+.remaining{font-size:3.5rem;font-variant-numeric:tabular-nums;margin:.3rem 0}\
+.bar{background:#333;height:.6rem;border-radius:.3rem;overflow:hidden}\
+.bar div{background:#0aa;height:100%}\
+.paused .bar div{background:#aa0}\
+.finished .bar div{background:#a00}\
+.target{color:#888;font-size:.9rem;margin-top:.4rem}";
+
+/// Reads `HOST:PORT`, or a bare port for this machine only.
+pub fn parse_address(address: &str) -> Result<String, String> {
+    let address = address.trim();
+    if address.parse::<u16>().is_ok() {
+        return Ok(format!("127.0.0.1:{}", address));
+    }
+    match address.rsplit_once(':') {
+        Some((_, port)) if port.parse::<u16>().is_ok() => Ok(address.to_string()),
+        _ => Err(format!("expected HOST:PORT like 0.0.0.0:8080, got {:?}", address)),
+    }
+}
+
+/// What the threads of `--serve` talk to the app with.
+#[derive(Clone)]
+pub struct Server {
+    pub status_requests: Sender<Sender<Vec<TimerStatus>>>,
+    pub controls: Sender<Result<Control, String>>,
+    /// `--serve-token`, which the control routes want as a bearer token
+    pub token: Option<String>,
+}
+
+/// `--serve`: serves a page on `address` with the remaining time and progress of every timer that
+/// reloads itself every second, asking the app for them like `endzeit status` does. On `/ws` a
+/// WebSocket gets the timers as JSON every second, for dashboards and overlays, and POST requests
+/// like `/pause` control the selected timer like the control FIFO.
+pub fn serve(address: &str, server: Server) -> Result<(), String> {
+    let listener = TcpListener::bind(address).map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            let server = server.clone();
+            thread::spawn(move || answer(stream, &server));
+        }
+    });
+    Ok(())
+}
+
+fn answer(stream: TcpStream, server: &Server) {
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    if reader.read_line(&mut request).is_err() {
+        return;
+    }
+    // The headers, only the key of a WebSocket, the token and where the request comes from are of interest
+    let mut websocket_key = None;
+    let mut authorization = None;
+    let mut origin = None;
+    let mut host = None;
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|read| read > 0) && !line.trim().is_empty() {
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            }
+        }
+        line.clear();
+    }
+    let mut words = request.split_whitespace();
+    let method = words.next().unwrap_or_default();
+    let target = words.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/ws") => match websocket_key {
+            Some(key) => return stream_ticks(stream, &key, &server.status_requests),
+            None => ("400 Bad Request", "text/plain", "/ws only speaks WebSocket\n".to_string()),
+        },
+        ("GET", "/" | "/index.html") => match timers(&server.status_requests) {
+            Some(timers) => ("200 OK", "text/html", render(&timers)),
+            None => ("503 Service Unavailable", "text/plain", "No answer from the timers\n".to_string()),
+        },
+        // Browsers send a form POST to another site without asking first, but always say where it comes from
+        ("POST", _) if origin.is_some_and(|origin| !same_origin(&origin, host.as_deref())) => {
+            ("403 Forbidden", "text/plain", "Other sites can't control the countdown\n".to_string())
+        }
+        ("POST", _) if server.token.as_ref().is_some_and(|token| !authorization.as_deref().is_some_and(|authorization| bearer_matches(authorization, token))) => {
+            ("401 Unauthorized", "text/plain", "Send the token of --serve-token as Authorization: Bearer TOKEN\n".to_string())
+        }
+        ("POST", path) => match parse_control(path, query) {
+            Ok(control) => {
+                let _ = server.controls.send(Ok(control));
+                ("200 OK", "text/plain", format!("{}\n", control))
+            }
+            Err((status, err)) => (status, "text/plain", format!("{}\n", err)),
+        },
+        (_, "/ws" | "/" | "/index.html") => ("405 Method Not Allowed", "text/plain", "Only GET is allowed here\n".to_string()),
+        _ => ("404 Not Found", "text/plain", "Not found, the countdown is on /\n".to_string()),
+    };
+    let _ = write!(
+        &stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nCache-Control: no-store\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body,
+    );
+}
+
+/// Whether the `Origin` of a request is the page itself, served on `host`.
+fn same_origin(origin: &str, host: Option<&str>) -> bool {
+    let origin = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://"));
+    origin.is_some() && origin == host
+}
+
+/// Compares the `Authorization` header with the token in constant time, so the time taken
+/// doesn't tell how much of it was right.
+fn bearer_matches(authorization: &str, token: &str) -> bool {
+    let Some(given) = authorization.strip_prefix("Bearer ") else {
+        return false;
+    };
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Reads the control routes: `/pause`, `/resume`, `/toggle`, `/cancel` and `/dismiss`, and `/add`
+/// and `/subtract` with `minutes=`, `seconds=` or `duration=` in any form of `--target` after the `+`.
+/// Errors come with their status.
+fn parse_control(path: &str, query: &str) -> Result<Control, (&'static str, String)> {
+    let command = path.trim_start_matches('/');
+    if command != "add" && command != "subtract" {
+        return Control::parse(command).map_err(|_| {
+            ("404 Not Found", format!("Unknown route {}, expected /pause, /resume, /toggle, /cancel, /dismiss, /add or /subtract", path))
+        });
+    }
+    parse_delta(query)
+        .and_then(|delta| if delta > TimeDelta::zero() { Ok(delta) } else { Err(format!("{} needs a time, like {}?minutes=5", path, path)) })
+        .map(|delta| Control::Adjust(if command == "subtract" { -delta } else { delta }))
+        .map_err(|err| ("400 Bad Request", err))
+}
+
+/// The sum of the times in a query.
+fn parse_delta(query: &str) -> Result<TimeDelta, String> {
+    let mut delta = TimeDelta::zero();
+    for (name, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        let value = value.replace("%20", " ").replace('+', " ");
+        let time = match name {
+            "minutes" | "seconds" => {
+                let amount: f64 = value.trim().parse().map_err(|_| format!("invalid {}: {}", name, value))?;
+                let milliseconds = if name == "minutes" { amount * 60_000.0 } else { amount * 1000.0 };
+                // Casting saturates, so anything beyond the range of i64 is turned away before
+                Some(milliseconds)
+                    .filter(|milliseconds| milliseconds.is_finite() && milliseconds.abs() < i64::MAX as f64)
+                    .and_then(|milliseconds| TimeDelta::try_milliseconds(milliseconds as i64))
+                    .ok_or_else(|| format!("{} out of range: {}", name, value))?
+            }
+            "duration" => parse_duration(&value)?,
+            _ => return Err(format!("unknown parameter {}, expected minutes, seconds or duration", name)),
+        };
+        delta = delta.checked_add(&time).ok_or_else(|| "time out of range".to_string())?;
+    }
+    Ok(delta)
+}
+
+/// Asks the app for the timers.
+fn timers(status_requests: &Sender<Sender<Vec<TimerStatus>>>) -> Option<Vec<TimerStatus>> {
+    let (sender, receiver) = mpsc::channel();
+    let _ = status_requests.send(sender);
+    receiver.recv_timeout(ANSWER_TIMEOUT).ok()
+}
+
+/// Accepts the WebSocket and sends it a tick with all timers every second, until the client is
+/// gone or endzeit quits. What the client sends is never read.
+fn stream_ticks(mut stream: TcpStream, key: &str, status_requests: &Sender<Sender<Vec<TimerStatus>>>) {
+    let accept = STANDARD.encode(Sha1::from(format!("{}{}", key, WEBSOCKET_GUID)).digest().bytes());
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept,
+    );
+    if stream.write_all(handshake.as_bytes()).is_err() {
+        return;
+    }
+    while let Some(timers) = timers(status_requests) {
+        let tick = serde_json::to_string(&Tick { kind: "tick", timers }).unwrap_or_default();
+        if stream.write_all(&text_frame(&tick)).is_err() {
+            return;
+        }
+        thread::sleep(TICK_INTERVAL);
+    }
+    // Close, the app is gone
+    let _ = stream.write_all(&[0x88, 0]);
+}
+
+/// A single unmasked text frame, as servers send them.
+fn text_frame(text: &str) -> Vec<u8> {
+    let mut frame = vec![0x81];
+    match text.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(text.as_bytes());
+    frame
+}
+
+/// The page, titled after the selected timer so the tab shows what it's counting down to.
+fn render(timers: &[TimerStatus]) -> String {
+    let title = match timers.iter().find(|timer| timer.selected) {
+        Some(timer) if timer.finished => format!("{} finished", timer.name),
+        Some(timer) => format!("{} {}", format_seconds(timer.remaining), timer.name),
+        None => "endzeit".to_string(),
+    };
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><meta http-equiv=\"refresh\" content=\"1\">\
+         <meta name=\"viewport\" content=\"width=device-width\"><title>{}</title><style>{}</style></head><body><h1>endzeit</h1>\n",
+        escape(&title), STYLE,
+    );
+    for timer in timers {
+        let progress = if timer.duration == 0 { 1.0 } else { 1.0 - timer.remaining as f64 / timer.duration as f64 };
+        let (class, remaining) = if timer.finished {
+            ("finished", "finished".to_string())
+        } else if timer.paused {
+            ("paused", format!("{} paused", format_seconds(timer.remaining)))
+        } else {
+            ("running", format_seconds(timer.remaining))
+        };
+        let _ = writeln!(
+            page,
+            "<section class=\"{}\"><div class=\"name\">{}</div><div class=\"remaining\">{}</div>\
+             <div class=\"bar\"><div style=\"width:{:.1}%\"></div></div><div class=\"target\">{}</div></section>",
+            class, escape(&timer.name), remaining, progress.clamp(0.0, 1.0) * 100.0, timer.target.format("%Y-%m-%d %H:%M:%S"),
+        );
+    }
+    page.push_str("</body></html>\n");
+    page
+}
+
+/// Escapes text for HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf
+};
+use chrono::TimeDelta;
+use ratatui::style::Color;
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::clock;
+use crate::countdown::Countdown;
+use crate::spec::parse_target_spec;
+use crate::state::state_dir;
+use crate::theme::Theme;
+
+/// How often at most the state file is rewritten, in seconds.
+const SAVE_INTERVAL: f64 = 1.0;
+
+/// A timer as written to the state file.
+#[derive(Serialize, Deserialize)]
+struct SavedTimer {
+    /// A `--target` spec with the absolute target, so the time left follows the wall clock
+    spec: String,
+    /// Whole seconds left for a paused timer, it stays paused and keeps them
+    #[serde(default)]
+    paused_remaining: Option<u64>,
+}
+
+/// The unfinished timers of this process in `<state dir>/endzeit/sessions/<pid>.json`, kept up to date
+/// while it runs and removed when it ends normally. A file left behind by a crash is picked up by `endzeit resume`.
+pub struct Session {
+    path: PathBuf,
+    saved: String,
+    saved_at: Option<f64>,
+}
+
+fn dir() -> Result<PathBuf, String> {
+    state_dir("sessions")
+}
+
+impl Session {
+    pub fn create() -> Result<Self, String> {
+        let path = dir()?.join(format!("{}.json", std::process::id()));
+        Ok(Self { path, saved: String::new(), saved_at: None })
+    }
+
+    /// Writes the unfinished `countdowns` if they changed, at most once per `SAVE_INTERVAL`.
+    pub fn save(&mut self, countdowns: &[Countdown]) -> Result<(), String> {
+        let now = clock::seconds();
+        if self.saved_at.is_some_and(|saved_at| now - saved_at < SAVE_INTERVAL) {
+            return Ok(());
+        }
+        self.saved_at = Some(now);
+
+        let timers: Vec<SavedTimer> = countdowns.iter()
+            .filter(|countdown| !countdown.is_finished())
+            .map(|countdown| SavedTimer {
+                spec: countdown.to_spec(),
+                paused_remaining: countdown.is_paused().then(|| countdown.get_remaining_time().seconds()),
+            })
+            .collect();
+        let contents = serde_json::to_string(&timers).map_err(|e| e.to_string())?;
+        if contents == self.saved {
+            return Ok(());
+        }
+        fs::write(&self.path, &contents).map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+        self.saved = contents;
+        Ok(())
+    }
+
+    /// Removes the state file, there is nothing to resume after a normal exit.
+    pub fn remove(self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Whether the endzeit that wrote a session is still running. On Unix its socket answers, which
+/// tells it apart from another process that got the same pid after a reboot.
+#[cfg(unix)]
+fn running(pid: u32) -> bool {
+    crate::socket::instances().contains(&pid)
+}
+
+#[cfg(not(unix))]
+fn running(pid: u32) -> bool {
+    crate::exec::is_process_alive(pid)
+}
+
+/// Takes the timers of every session whose process is gone, like after a crash or a reboot, and
+/// removes their state files once all are read. A file that can't be read is left alone with a warning.
+/// Timers that ended meanwhile finish right away and run their command.
+pub fn restore(theme: &Theme) -> Result<Vec<Countdown>, String> {
+    let dir = dir()?;
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut countdowns = Vec::new();
+    let mut restored = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let Some(pid) = path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse().ok()) else {
+            continue;
+        };
+        if running(pid) {
+            continue;
+        }
+        match read(&path, theme, countdowns.len()) {
+            Ok(timers) => {
+                countdowns.extend(timers);
+                restored.push(path);
+            }
+            Err(err) => eprintln!("Skipping {}: {}", path.display(), err),
+        }
+    }
+    for path in restored {
+        let _ = fs::remove_file(&path);
+    }
+    Ok(countdowns)
+}
+
+/// The timers of one state file, colored on from the `first` index.
+fn read(path: &Path, theme: &Theme, first: usize) -> Result<Vec<Countdown>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let timers: Vec<SavedTimer> = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    timers.into_iter()
+        .enumerate()
+        .map(|(index, timer)| timer.restore(theme.color(first + index)))
+        .collect()
+}
+
+impl SavedTimer {
+    fn restore(self, fallback_color: Color) -> Result<Countdown, String> {
+        let mut target_spec = parse_target_spec(&self.spec).map_err(|err| format!("{}: {}", self.spec, err))?;
+        let now = clock::now();
+        if let Some(remaining) = self.paused_remaining {
+            target_spec.target = now + TimeDelta::seconds(remaining as i64);
+        }
+        let missed = target_spec.target <= now;
+        let mut countdown = Countdown::from_spec(target_spec, fallback_color);
+        if self.paused_remaining.is_some() {
+            countdown.toggle_pause();
+        }
+        if missed {
+            countdown.set_note("ended while endzeit wasn't running".to_string());
+        }
+        Ok(countdown)
+    }
+}
@@ -0,0 +1,771 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{
+    self,
+    Receiver
+};
+use std::thread;
+use std::time::Duration;
+use chrono::TimeDelta;
+use clap::{
+    builder::BoolishValueParser,
+    Args
+};
+
+use crate::app::DEFAULT_TICK_RATE;
+use crate::config::{
+    Config,
+    NotifyConfig
+};
+use crate::contrast::{
+    self,
+    Contrast
+};
+use crate::exec::{
+    HookValidation,
+    Launcher,
+    PowerAction,
+    Shell
+};
+use crate::i18n::Language;
+use crate::keys::KeyBindings;
+use crate::machine::Machine;
+use crate::media::{
+    parse_media_cue,
+    MediaCue
+};
+use crate::milestone::{
+    parse_before_end,
+    parse_milestone,
+    BeforeEnd,
+    Milestone
+};
+use crate::notify::{
+    parse_sound,
+    Channel,
+    Escalation,
+    Sound,
+    Tick
+};
+use crate::mqtt::{
+    parse_broker,
+    Broker,
+    DEFAULT_TOPIC
+};
+use crate::output::Output;
+use crate::push::{
+    parse_notify,
+    NotifyTarget,
+    Push
+};
+use crate::retry::Retry;
+use crate::serve::parse_address;
+use crate::spec::parse_duration;
+use crate::template::Template;
+use crate::term::TermNotify;
+use crate::theme::Theme;
+use crate::units::UnitFormat;
+use crate::verify::Verification;
+
+/// Options that apply to all countdowns. Each one is resolved in the order
+/// command line > environment (ENDZEIT_*) > config file > built-in default.
+#[derive(Args, Clone)]
+pub struct Options {
+    /// Config file to use instead of ~/.config/endzeit/config.toml
+    #[clap(long, env = "ENDZEIT_CONFIG", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Use the settings of a [profiles.NAME] table of the config file
+    #[clap(long, env = "ENDZEIT_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Command to execute when endzeit finishes, can be repeated to run several in order
+    #[clap(long, env = "ENDZEIT_EXECUTE")]
+    execute: Vec<String>,
+
+    /// Program to run when endzeit finishes, started directly without a shell so untrusted arguments
+    /// are never interpreted
+    #[clap(long, env = "ENDZEIT_EXEC_DIRECT", value_name = "PROGRAM")]
+    exec_direct: Option<String>,
+
+    /// An argument for --exec-direct, passed on as it is, can be repeated
+    #[clap(long, requires = "exec_direct", allow_hyphen_values = true, value_name = "ARG")]
+    exec_arg: Vec<String>,
+
+    /// Shut down, suspend, hibernate or lock the machine once the countdown finished and its commands ran
+    #[clap(long, env = "ENDZEIT_THEN", value_enum, value_name = "ACTION")]
+    then: Option<PowerAction>,
+
+    /// Directory commands run in, unless their timer sets cwd= [default: the current directory]
+    #[clap(long, env = "ENDZEIT_EXECUTE_CWD", value_name = "DIR")]
+    execute_cwd: Option<PathBuf>,
+
+    /// Shell commands run with, unless their timer sets shell= [default: sh, cmd on Windows]
+    #[clap(long, visible_alias = "shell", env = "ENDZEIT_EXECUTE_SHELL", value_enum)]
+    execute_shell: Option<Shell>,
+
+    /// Command to run when a timer's progress reaches a percentage, like 50%:'notify-send half', can be repeated
+    #[clap(long, env = "ENDZEIT_EXEC_AT", value_parser = parse_milestone, value_name = "PERCENT:COMMAND")]
+    exec_at: Vec<Milestone>,
+
+    /// Command to run when only this much time is left, like 5m:'notify-send "wrap up"', can be repeated
+    #[clap(long, env = "ENDZEIT_EXECUTE_BEFORE", value_parser = parse_before_end, value_name = "DURATION:COMMAND")]
+    execute_before: Vec<BeforeEnd>,
+
+    /// Start the commands in a session of their own and don't wait for them, for GUI apps and
+    /// anything else that keeps running
+    #[clap(long, env = "ENDZEIT_EXECUTE_DETACH", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    execute_detach: Option<bool>,
+
+    /// Kill a command that is still running after this long, with everything it started, e.g. 30s
+    #[clap(long, env = "ENDZEIT_EXECUTE_TIMEOUT", value_parser = parse_duration, value_name = "DURATION")]
+    execute_timeout: Option<TimeDelta>,
+
+    /// Ask before running the commands of a finished timer, they don't run if nobody answers within 30s
+    #[clap(long, env = "ENDZEIT_CONFIRM_EXECUTE", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    confirm_execute: Option<bool>,
+
+    /// Keep the machine from going to sleep while a timer runs, so its commands get to run
+    #[clap(long, env = "ENDZEIT_KEEP_AWAKE", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    keep_awake: Option<bool>,
+
+    /// Turn on Do Not Disturb while a timer runs, and back off once all are finished or cancelled
+    #[clap(long, env = "ENDZEIT_DND", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    dnd: Option<bool>,
+
+    /// Try a failed --execute command again up to this many times, each try is logged to
+    /// <state dir>/endzeit/logs/hooks.log
+    #[clap(long, env = "ENDZEIT_EXECUTE_RETRIES", value_name = "N")]
+    execute_retries: Option<u32>,
+
+    /// Wait before the first retry, it doubles for every further one, e.g. 30s [default: 10s]
+    #[clap(long, env = "ENDZEIT_EXECUTE_BACKOFF", value_parser = parse_duration, requires = "execute_retries", value_name = "DURATION")]
+    execute_backoff: Option<TimeDelta>,
+
+    /// Refuse target moves of dynamic sources larger than this duration, e.g. 2h
+    #[clap(long, env = "ENDZEIT_MAX_RETARGET", value_parser = parse_duration)]
+    max_retarget: Option<TimeDelta>,
+
+    /// Command that checks whether the --execute command worked, like "test -f /tmp/done". It runs after
+    /// the command until it succeeds or --verify-timeout passes; endzeit exits with 3 if it never does
+    #[clap(long, env = "ENDZEIT_VERIFY_CMD", value_name = "COMMAND")]
+    verify_cmd: Option<String>,
+
+    /// How long --verify-cmd gets to succeed, e.g. 30s [default: 1m]
+    #[clap(long, env = "ENDZEIT_VERIFY_TIMEOUT", value_parser = parse_duration, requires = "verify_cmd")]
+    verify_timeout: Option<TimeDelta>,
+
+    /// Keep showing the finished countdowns this long before exiting, e.g. 2m [default: 0s]
+    #[clap(long, env = "ENDZEIT_EXIT_AFTER", value_parser = parse_duration)]
+    exit_after: Option<TimeDelta>,
+
+    /// Check hook commands at startup: off, warn on the gauge, or strict to refuse to start [default: off]
+    #[clap(long, env = "ENDZEIT_VALIDATE_HOOKS", value_enum, value_name = "MODE", num_args = 0..=1, default_missing_value = "strict")]
+    validate_hooks: Option<HookValidation>,
+
+    /// Command to execute when a timer is given up before it finished: cancelled, or endzeit quit
+    /// with the key, SIGINT or SIGTERM
+    #[clap(long, env = "ENDZEIT_ON_CANCEL", value_name = "COMMAND")]
+    on_cancel: Option<String>,
+
+    /// Command to execute whenever a dynamic source moves a target
+    #[clap(long, env = "ENDZEIT_ON_RETARGET")]
+    on_retarget: Option<String>,
+
+    /// Raise a desktop notification when a timer finishes, with a button to snooze it on Linux.
+    /// --notify=slack:URL and --notify=discord:URL post it to a chat webhook instead, can be repeated
+    #[clap(long, env = "ENDZEIT_NOTIFY", value_name = "TARGET", value_parser = parse_notify, num_args = 0..=1, require_equals = true, default_missing_value = "desktop")]
+    notify: Vec<NotifyTarget>,
+
+    /// Also raise a desktop notification when a timer is halfway
+    #[clap(long, env = "ENDZEIT_NOTIFY_HALFWAY", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    notify_halfway: Option<bool>,
+
+    /// Notify when the time left falls to each of these, comma separated like 30m,10m,1m
+    #[clap(long, env = "ENDZEIT_NOTIFY_AT", value_parser = parse_duration, value_delimiter = ',', value_name = "DURATIONS")]
+    notify_at: Vec<TimeDelta>,
+
+    /// Ring the terminal bell when a timer finishes, multiplexers like tmux turn it into an urgency flag
+    #[clap(long, env = "ENDZEIT_BELL", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    bell: Option<bool>,
+
+    /// How often to ring the bell, implies --bell [default: 1]
+    #[clap(long, env = "ENDZEIT_BELL_COUNT", value_name = "N")]
+    bell_count: Option<u32>,
+
+    /// Invert the whole screen a few times when a timer finishes, before exiting or holding
+    #[clap(long, env = "ENDZEIT_FLASH", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    flash: Option<bool>,
+
+    /// Keep alerting when a timer finishes until a key is pressed or `endzeit dismiss` is sent
+    #[clap(long, env = "ENDZEIT_ALARM", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    alarm: Option<bool>,
+
+    /// How long the z key and the snooze button of --notify run a finished timer again, e.g. 9m [default: 5m]
+    #[clap(long, env = "ENDZEIT_SNOOZE", value_parser = parse_duration, value_name = "DURATION")]
+    snooze: Option<TimeDelta>,
+
+    /// Time between the alerts of --alarm, implies --alarm [default: 10s]
+    #[clap(long, env = "ENDZEIT_ALARM_EVERY", value_parser = parse_duration, value_name = "DURATION")]
+    alarm_every: Option<TimeDelta>,
+
+    /// Send terminal notifications at halfway and on completion (works over SSH)
+    #[clap(long, env = "ENDZEIT_TERM_NOTIFY", value_enum, value_name = "KIND", num_args = 0..=1, default_missing_value = "osc9")]
+    term_notify: Option<TermNotify>,
+
+    /// Escalation ladder for halfway and finish notifications, comma separated. Each milestone goes to the
+    /// first channel that reaches you; the flash only counts while the terminal is focused
+    #[clap(long, env = "ENDZEIT_ESCALATE", value_enum, value_delimiter = ',')]
+    escalate: Vec<Channel>,
+
+    /// Sound played when a timer finishes, builtin:chime, builtin:bell, builtin:ding, builtin:tick or a file. With the sound
+    /// channel in --escalate that channel plays it instead [default: the system's completion sound]
+    #[clap(long, env = "ENDZEIT_SOUND", value_parser = parse_sound)]
+    sound: Option<Sound>,
+
+    /// Volume of --sound in percent
+    #[clap(long, env = "ENDZEIT_VOLUME", value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
+    volume: Option<u8>,
+
+    /// Play a soft tick every second or minute while the selected timer runs, the k key turns it on and off
+    #[clap(long, env = "ENDZEIT_TICK", value_enum, value_name = "EVERY", num_args = 0..=1, require_equals = true, default_missing_value = "second")]
+    tick: Option<Tick>,
+
+    /// Play or pause the media players over MPRIS once the timer finished, or reached a percentage like
+    /// 50%:pause, can be repeated. ACTION is play, pause, toggle or stop
+    #[clap(long, env = "ENDZEIT_MEDIA", value_parser = parse_media_cue, value_name = "[PERCENT:]ACTION")]
+    media: Vec<MediaCue>,
+
+    /// URL the webhook channel of --escalate posts to
+    #[clap(long, env = "ENDZEIT_WEBHOOK", value_name = "URL")]
+    webhook: Option<String>,
+
+    /// Push finished timers and the --notify-at thresholds to this ntfy.sh topic, or the URL of a topic on another server
+    #[clap(long, env = "ENDZEIT_NTFY", value_name = "TOPIC", value_parser = Push::parse_ntfy)]
+    ntfy: Option<Push>,
+
+    /// Message finished timers and the --notify-at thresholds to a Telegram chat, with the token of the bot and the id of the chat
+    #[clap(long, env = "ENDZEIT_TELEGRAM", value_name = "TOKEN:CHAT", value_parser = Push::parse_telegram)]
+    telegram: Option<Push>,
+
+    /// Push finished timers and the --notify-at thresholds to Pushover, with the token of the application and the key of the user
+    #[clap(long, env = "ENDZEIT_PUSHOVER", value_name = "TOKEN/USER", value_parser = Push::parse_pushover)]
+    pushover: Option<Push>,
+
+    /// Language of notifications [default: from LANG]
+    #[clap(long, env = "ENDZEIT_LANG", value_enum)]
+    lang: Option<Language>,
+
+    /// Show the remaining time as iTerm2 badge and WezTerm user vars (endzeit_name, endzeit_remaining)
+    #[clap(long, env = "ENDZEIT_TERM_BADGE", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    term_badge: Option<bool>,
+
+    /// Publish the selected timer's name, state and seconds left to this MQTT broker,
+    /// tcp://[USER:PASSWORD@]HOST[:PORT]
+    #[clap(long, env = "ENDZEIT_MQTT", value_name = "URL", value_parser = parse_broker)]
+    mqtt: Option<Broker>,
+
+    /// Topic the --mqtt values go under, as <topic>/name, <topic>/state and <topic>/remaining [default: endzeit]
+    #[clap(long, env = "ENDZEIT_MQTT_TOPIC", value_name = "TOPIC")]
+    mqtt_topic: Option<String>,
+
+    /// Serve the timers as Prometheus metrics on this port, at /metrics
+    #[clap(long, env = "ENDZEIT_METRICS_PORT", value_name = "PORT")]
+    metrics_port: Option<u16>,
+
+    /// Serve a page with the remaining time and progress on this address, like 0.0.0.0:8080, a bare port serves this machine only
+    #[clap(long, env = "ENDZEIT_SERVE", value_parser = parse_address, value_name = "HOST:PORT")]
+    serve: Option<String>,
+
+    /// Token the control routes of --serve want as Authorization: Bearer TOKEN
+    #[clap(long, env = "ENDZEIT_SERVE_TOKEN", value_name = "TOKEN")]
+    serve_token: Option<String>,
+
+    /// How to show the countdowns [default: tui]
+    #[clap(long, env = "ENDZEIT_OUTPUT", value_enum)]
+    output: Option<Output>,
+
+    /// Template of the lines of --output line, with {name}, {remaining}, {percent}, {target} and the units like {hh}:{mm}:{ss}
+    #[clap(long, env = "ENDZEIT_FORMAT", value_name = "TEMPLATE")]
+    format: Option<String>,
+
+    /// Ask before quitting while a countdown is still running
+    #[clap(long, env = "ENDZEIT_CONFIRM_QUIT", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    confirm_quit: Option<bool>,
+
+    /// Don't capture the mouse, so the terminal's own text selection keeps working
+    #[clap(long, env = "ENDZEIT_NO_MOUSE", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    no_mouse: Option<bool>,
+
+    /// Units remaining times are shown in, a selection of ymwdhms in that order, e.g. dhms
+    #[clap(long, env = "ENDZEIT_UNITS")]
+    units: Option<String>,
+
+    /// Show at most this many units at once, e.g. 3 for 2d 3h 15m
+    #[clap(long, env = "ENDZEIT_MAX_UNITS", value_name = "N")]
+    max_units: Option<usize>,
+
+    /// Rescue mode for stripped-down terminals: plain ASCII lines once a second without colors, alternate
+    /// screen or input, and no notifications, sounds, webhooks or terminal queries, whatever else is set
+    #[clap(long, env = "ENDZEIT_MINIMAL", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub minimal: Option<bool>,
+
+    /// Show all running timers on a single scrolling line, for one-row panes and bars
+    #[clap(long, env = "ENDZEIT_TICKER", value_parser = BoolishValueParser::new(), value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    ticker: Option<bool>,
+
+    /// ICS file whose events are checked for overlaps with the countdowns at start
+    #[clap(long, env = "ENDZEIT_CALENDAR", value_name = "FILE")]
+    calendar: Option<PathBuf>,
+
+    /// Milliseconds between redraws [default: 333]
+    #[clap(long, env = "ENDZEIT_REFRESH_RATE", value_name = "MS")]
+    refresh_rate: Option<u64>,
+
+    /// Theme to start from, builtin:ocean, builtin:sunset, builtin:mono, builtin:solarized-light
+    /// or a TOML file with colors and background
+    #[clap(long, env = "ENDZEIT_THEME")]
+    theme: Option<String>,
+
+    /// Gauge colors handed out to timers in order, comma separated
+    #[clap(long, env = "ENDZEIT_COLORS", value_delimiter = ',')]
+    colors: Vec<String>,
+
+    /// Gauge background color [default: black]
+    #[clap(long, env = "ENDZEIT_BACKGROUND")]
+    background: Option<String>,
+
+    /// Adjust the gauge colors to the terminal background: auto fixes up the default colors,
+    /// high brings every color to a WCAG-like contrast ratio [default: auto]
+    #[clap(long, env = "ENDZEIT_CONTRAST", value_enum)]
+    pub contrast: Option<Contrast>,
+
+    /// Print JSON of a stable, versioned schema instead of text for scripts: status, list, pause, resume,
+    /// cancel and history, and post it to webhooks. `endzeit schema` prints the JSON Schema
+    #[clap(long, env = "ENDZEIT_MACHINE", value_enum, value_name = "VERSION")]
+    pub machine: Option<Machine>,
+
+    /// Extra date format (chrono syntax) tried before YYYY-MM-DD, can be repeated
+    #[clap(long = "date-format", env = "ENDZEIT_DATE_FORMATS", value_delimiter = ',', value_name = "FORMAT")]
+    date_formats: Vec<String>,
+}
+
+/// The resolved options.
+pub struct Settings {
+    pub execute: Vec<String>,
+    /// `--exec-direct` and its `--exec-arg`s
+    pub exec_direct: Option<Vec<String>>,
+    /// `--then`
+    pub then: Option<PowerAction>,
+    pub launcher: Launcher,
+    /// Commands run by every timer along the way
+    pub milestones: Vec<Milestone>,
+    /// Commands run by every timer shortly before its end
+    pub before_end: Vec<BeforeEnd>,
+    /// Ask before the completion commands run
+    pub confirm_execute: bool,
+    /// `--keep-awake`
+    pub keep_awake: bool,
+    /// `--dnd`
+    pub dnd: bool,
+    /// How failed completion commands are tried again
+    pub retry: Option<Retry>,
+    pub max_retarget: Option<TimeDelta>,
+    pub exit_after: TimeDelta,
+    pub verification: Option<Verification>,
+    pub on_retarget: Option<String>,
+    pub on_cancel: Option<String>,
+    pub validate_hooks: HookValidation,
+    pub term_notify: Option<TermNotify>,
+    pub notify: bool,
+    pub notify_halfway: bool,
+    /// Seconds left at which to notify
+    pub notify_at: Vec<u64>,
+    /// Bells rung when a timer finishes, 0 for none
+    pub bell_count: u32,
+    /// Invert the screen a few times when a timer finishes
+    pub flash: bool,
+    /// Time between the alerts of `--alarm`, None without
+    pub alarm_every: Option<Duration>,
+    pub snooze: Duration,
+    /// Played when a timer finishes, unless the sound channel of the escalation plays it
+    pub alarm: Option<Sound>,
+    pub volume: u8,
+    pub tick: Option<Tick>,
+    /// `--media`
+    pub media: Vec<MediaCue>,
+    pub escalation: Escalation,
+    /// Services told about finished timers and thresholds
+    pub push: Vec<Push>,
+    pub language: Language,
+    pub term_badge: bool,
+    /// `--mqtt` and the topic
+    pub mqtt: Option<(Broker, String)>,
+    pub metrics_port: Option<u16>,
+    /// `--serve`
+    pub serve: Option<String>,
+    pub serve_token: Option<String>,
+    pub output: Output,
+    /// `--format`
+    pub line_format: Template,
+    pub ticker: bool,
+    pub confirm_quit: bool,
+    pub mouse: bool,
+    pub calendar: Option<PathBuf>,
+    pub tick_rate: Duration,
+    pub theme: Theme,
+    pub date_formats: Vec<String>,
+    pub unit_format: UnitFormat,
+    pub keys: KeyBindings,
+}
+
+/// Time between updates of `--minimal`, as often as the seconds change.
+const MINIMAL_TICK_RATE: Duration = Duration::from_secs(1);
+
+/// Time between the alerts of --alarm without --alarm-every.
+const DEFAULT_ALARM_EVERY: Duration = Duration::from_secs(10);
+
+/// How long a snooze lasts without --snooze.
+const DEFAULT_SNOOZE: Duration = Duration::from_secs(5 * 60);
+
+/// How long --verify-cmd gets without --verify-timeout.
+const DEFAULT_VERIFY_TIMEOUT: TimeDelta = TimeDelta::minutes(1);
+/// Wait before the first retry of a failed command, unless --execute-backoff says otherwise.
+const DEFAULT_EXECUTE_BACKOFF: TimeDelta = TimeDelta::seconds(10);
+
+/// The lines of `--output line` without --format.
+const DEFAULT_LINE_FORMAT: &str = "{name} {remaining}";
+/// What --format has besides the units, filled in by the app.
+const LINE_FIELDS: &[&str] = &["name", "remaining", "percent", "target"];
+
+/// How often the config file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            execute: Vec::new(),
+            exec_direct: None,
+            then: None,
+            launcher: Launcher::default(),
+            milestones: Vec::new(),
+            before_end: Vec::new(),
+            confirm_execute: false,
+            keep_awake: false,
+            dnd: false,
+            retry: None,
+            max_retarget: None,
+            exit_after: TimeDelta::zero(),
+            verification: None,
+            on_retarget: None,
+            on_cancel: None,
+            validate_hooks: HookValidation::Off,
+            term_notify: None,
+            notify: false,
+            notify_halfway: false,
+            notify_at: Vec::new(),
+            bell_count: 0,
+            flash: false,
+            alarm_every: None,
+            snooze: DEFAULT_SNOOZE,
+            alarm: None,
+            volume: 100,
+            tick: None,
+            media: Vec::new(),
+            escalation: Escalation::default(),
+            push: Vec::new(),
+            language: Language::En,
+            term_badge: false,
+            mqtt: None,
+            metrics_port: None,
+            serve: None,
+            serve_token: None,
+            output: Output::Tui,
+            line_format: Template::parse_with(DEFAULT_LINE_FORMAT, LINE_FIELDS).expect("the default format is valid"),
+            ticker: false,
+            confirm_quit: false,
+            mouse: true,
+            calendar: None,
+            tick_rate: DEFAULT_TICK_RATE,
+            theme: Theme::default(),
+            date_formats: Vec::new(),
+            unit_format: UnitFormat::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Fills everything not given on the command line or in the environment from the config.
+    pub fn resolve(options: &Options, config: &Config) -> Result<Self, String> {
+        let max_retarget = match options.max_retarget {
+            Some(max_retarget) => Some(max_retarget),
+            None => config.max_retarget.as_deref().map(parse_duration).transpose().map_err(|err| format!("max_retarget: {}", err))?,
+        };
+        let exit_after = match options.exit_after {
+            Some(exit_after) => Some(exit_after),
+            None => config.exit_after.as_deref().map(parse_duration).transpose().map_err(|err| format!("exit_after: {}", err))?,
+        };
+        let verify_timeout = match options.verify_timeout {
+            Some(verify_timeout) => Some(verify_timeout),
+            None => config.verify_timeout.as_deref().map(parse_duration).transpose().map_err(|err| format!("verify_timeout: {}", err))?,
+        };
+        let verification = options.verify_cmd.clone().or_else(|| config.verify_cmd.clone())
+            .map(|command| Verification { command, timeout: verify_timeout.unwrap_or(DEFAULT_VERIFY_TIMEOUT) });
+        let sound = match options.sound.clone() {
+            Some(sound) => Some(sound),
+            None => config.sound.as_deref().map(parse_sound).transpose().map_err(|err| format!("sound: {}", err))?,
+        };
+        let volume = options.volume.or(config.volume).unwrap_or(100).min(100);
+        let bell_count = match options.bell_count.or(config.bell_count) {
+            Some(count) => count,
+            None if options.bell.or(config.bell).unwrap_or(false) => 1,
+            None => 0,
+        };
+        let alarm_every = match options.alarm_every {
+            Some(alarm_every) => Some(alarm_every),
+            None => config.alarm_every.as_deref().map(parse_duration).transpose().map_err(|err| format!("alarm_every: {}", err))?,
+        };
+        let alarm_every = match alarm_every {
+            Some(alarm_every) => Some(alarm_every.to_std().map_err(|_| "alarm_every: must be positive".to_string())?),
+            None if options.alarm.or(config.alarm).unwrap_or(false) => Some(DEFAULT_ALARM_EVERY),
+            None => None,
+        };
+        let snooze = match options.snooze {
+            Some(snooze) => Some(snooze),
+            None => config.snooze.as_deref().map(parse_duration).transpose().map_err(|err| format!("snooze: {}", err))?,
+        };
+        let snooze = match snooze {
+            Some(snooze) => snooze.to_std().ok().filter(|snooze| !snooze.is_zero()).ok_or_else(|| "snooze: must be positive".to_string())?,
+            None => DEFAULT_SNOOZE,
+        };
+        let notify_at = if options.notify_at.is_empty() {
+            config.notify_at.iter().map(|threshold| parse_duration(threshold)).collect::<Result<_, _>>().map_err(|err| format!("notify_at: {}", err))?
+        } else {
+            options.notify_at.clone()
+        };
+        let notify_at = notify_at.iter().map(|threshold| threshold.num_seconds()).filter(|seconds| *seconds > 0).map(|seconds| seconds as u64).collect();
+        let media = if options.media.is_empty() {
+            config.media.iter().map(|cue| parse_media_cue(cue)).collect::<Result<_, _>>().map_err(|err| format!("media: {}", err))?
+        } else {
+            options.media.clone()
+        };
+        let milestones = if options.exec_at.is_empty() {
+            config.exec_at.iter().map(|milestone| parse_milestone(milestone)).collect::<Result<_, _>>().map_err(|err| format!("exec_at: {}", err))?
+        } else {
+            options.exec_at.clone()
+        };
+        let before_end = if options.execute_before.is_empty() {
+            config.execute_before.iter().map(|hook| parse_before_end(hook)).collect::<Result<_, _>>().map_err(|err| format!("execute_before: {}", err))?
+        } else {
+            options.execute_before.clone()
+        };
+        let execute_backoff = match options.execute_backoff {
+            Some(execute_backoff) => Some(execute_backoff),
+            None => config.execute_backoff.as_deref().map(parse_duration).transpose().map_err(|err| format!("execute_backoff: {}", err))?,
+        };
+        let mqtt = match &options.mqtt {
+            Some(mqtt) => Some(mqtt.clone()),
+            None => config.mqtt.as_deref().map(parse_broker).transpose().map_err(|err| format!("mqtt: {}", err))?,
+        };
+        let ntfy = match &options.ntfy {
+            Some(ntfy) => Some(ntfy.clone()),
+            None => config.ntfy.as_deref().map(Push::parse_ntfy).transpose().map_err(|err| format!("ntfy: {}", err))?,
+        };
+        let pushover = match &options.pushover {
+            Some(pushover) => Some(pushover.clone()),
+            None => config.pushover.as_deref().map(Push::parse_pushover).transpose().map_err(|err| format!("pushover: {}", err))?,
+        };
+        let telegram = match &options.telegram {
+            Some(telegram) => Some(telegram.clone()),
+            None => config.telegram.as_deref().map(Push::parse_telegram).transpose().map_err(|err| format!("telegram: {}", err))?,
+        };
+        let chats: Vec<Push> = options.notify.iter()
+            .filter_map(|target| match target {
+                NotifyTarget::Push(push) => Some(push.clone()),
+                _ => None,
+            })
+            .collect();
+        let chats = if chats.is_empty() {
+            let slack = config.slack.as_deref().map(Push::parse_slack).transpose().map_err(|err| format!("slack: {}", err))?;
+            let discord = config.discord.as_deref().map(Push::parse_discord).transpose().map_err(|err| format!("discord: {}", err))?;
+            slack.into_iter().chain(discord).collect()
+        } else {
+            chats
+        };
+        let execute_timeout = match options.execute_timeout {
+            Some(execute_timeout) => Some(execute_timeout),
+            None => config.execute_timeout.as_deref().map(parse_duration).transpose().map_err(|err| format!("execute_timeout: {}", err))?,
+        };
+        let then = options.then.or(config.then);
+        if then.is_some_and(|action| action.argv().is_none()) {
+            return Err("--then: hibernate isn't supported on macOS".to_string());
+        }
+        let retry = options.execute_retries.or(config.execute_retries).filter(|retries| *retries > 0).map(|retries| Retry {
+            retries,
+            backoff: execute_backoff.unwrap_or(DEFAULT_EXECUTE_BACKOFF).to_std().unwrap_or_default(),
+        });
+        let colors = if options.colors.is_empty() { config.colors.as_deref() } else { Some(&options.colors[..]) };
+        let escalate = if options.escalate.is_empty() { &config.escalate } else { &options.escalate };
+        let date_formats = if options.date_formats.is_empty() { &config.date_formats } else { &options.date_formats };
+        let background = options.background.as_deref().or(config.background.as_deref());
+        let base = options.theme.as_deref().or(config.theme.as_deref());
+        let mut theme = Theme::new(base, colors, background)?;
+        contrast::adapt(&mut theme, options.contrast.or(config.contrast).unwrap_or(Contrast::Auto), base.is_some() || colors.is_some(), background.is_some());
+
+        let settings = Self {
+            execute: if options.execute.is_empty() { config.execute.iter().cloned().collect() } else { options.execute.clone() },
+            exec_direct: match &options.exec_direct {
+                Some(program) => Some(std::iter::once(program.clone()).chain(options.exec_arg.iter().cloned()).collect()),
+                None => config.exec_direct.clone().filter(|argv| !argv.is_empty()),
+            },
+            then,
+            launcher: Launcher {
+                cwd: options.execute_cwd.clone().or_else(|| config.execute_cwd.clone()),
+                shell: options.execute_shell.or(config.execute_shell),
+                detach: options.execute_detach.or(config.execute_detach).unwrap_or(false),
+                timeout: execute_timeout.and_then(|timeout| timeout.to_std().ok()),
+            },
+            milestones,
+            before_end,
+            confirm_execute: options.confirm_execute.or(config.confirm_execute).unwrap_or(false),
+            keep_awake: options.keep_awake.or(config.keep_awake).unwrap_or(false),
+            dnd: options.dnd.or(config.dnd).unwrap_or(false),
+            retry,
+            max_retarget,
+            exit_after: exit_after.unwrap_or_default(),
+            verification,
+            on_retarget: options.on_retarget.clone().or_else(|| config.on_retarget.clone()),
+            on_cancel: options.on_cancel.clone().or_else(|| config.on_cancel.clone()),
+            validate_hooks: options.validate_hooks.or(config.validate_hooks).unwrap_or(HookValidation::Off),
+            term_notify: options.term_notify.or(config.term_notify),
+            notify: options.notify.iter().any(|target| matches!(target, NotifyTarget::Desktop)) || config.notify.as_ref().is_some_and(NotifyConfig::desktop),
+            notify_halfway: options.notify_halfway.or(config.notify_halfway).unwrap_or(false),
+            notify_at,
+            bell_count,
+            flash: options.flash.or(config.flash).unwrap_or(false),
+            alarm_every,
+            snooze,
+            alarm: sound.clone().filter(|_| !escalate.contains(&Channel::Sound)),
+            volume,
+            tick: options.tick.or(config.tick),
+            media,
+            escalation: Escalation::new(escalate.clone(), options.webhook.clone().or_else(|| config.webhook.clone()), options.machine, sound.unwrap_or_default(), volume),
+            push: ntfy.into_iter().chain(pushover).chain(telegram).chain(chats)
+                .chain(config.notify.as_ref().and_then(NotifyConfig::email).cloned().map(Push::Email))
+                .collect(),
+            language: options.lang.or(config.lang).unwrap_or_else(Language::detect),
+            term_badge: options.term_badge.or(config.term_badge).unwrap_or(false),
+            metrics_port: options.metrics_port.or(config.metrics_port),
+            serve: match &options.serve {
+                Some(serve) => Some(serve.clone()),
+                None => config.serve.as_deref().map(parse_address).transpose().map_err(|err| format!("serve: {}", err))?,
+            },
+            serve_token: options.serve_token.clone().or_else(|| config.serve_token.clone()),
+            mqtt: mqtt.map(|broker| {
+                let topic = options.mqtt_topic.clone().or_else(|| config.mqtt_topic.clone());
+                (broker, topic.unwrap_or_else(|| DEFAULT_TOPIC.to_string()))
+            }),
+            output: options.output.or(config.output).unwrap_or(Output::Tui),
+            line_format: Template::parse_with(
+                options.format.as_deref().or(config.format.as_deref()).unwrap_or(DEFAULT_LINE_FORMAT),
+                LINE_FIELDS,
+            ).map_err(|err| format!("format: {}", err))?,
+            ticker: options.ticker.or(config.ticker).unwrap_or(false),
+            confirm_quit: options.confirm_quit.or(config.confirm_quit).unwrap_or(false),
+            mouse: options.no_mouse.map(|no_mouse| !no_mouse).or(config.mouse).unwrap_or(true),
+            calendar: options.calendar.clone().or_else(|| config.calendar.clone()),
+            tick_rate: options.refresh_rate.or(config.refresh_rate).map_or(DEFAULT_TICK_RATE, Duration::from_millis),
+            theme,
+            date_formats: date_formats.clone(),
+            unit_format: UnitFormat::new(
+                options.units.as_deref().or(config.units.as_deref()),
+                options.max_units.or(config.max_units),
+            )?,
+            keys: KeyBindings::from_config(&config.keys)?,
+        };
+        if options.minimal.or(config.minimal).unwrap_or(false) {
+            return Ok(settings.minimal());
+        }
+        Ok(settings)
+    }
+
+    /// The commands that run for every timer, for `--validate-hooks`.
+    pub fn shared_hooks(&self) -> Vec<&str> {
+        self.on_retarget.iter().chain(&self.on_cancel)
+            .chain(self.milestones.iter().map(|milestone| &milestone.command))
+            .chain(self.before_end.iter().map(|hook| &hook.command))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The seconds left at which timers notify or run a command, see `Countdown::crossed_thresholds`.
+    pub fn thresholds(&self) -> Vec<u64> {
+        let mut thresholds: Vec<u64> = self.notify_at.iter().copied().chain(self.before_end.iter().map(|hook| hook.seconds)).collect();
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        thresholds
+    }
+
+    /// Turns off everything optional, see `--minimal`. Commands still run when timers finish.
+    fn minimal(self) -> Self {
+        Self {
+            term_notify: None,
+            notify: false,
+            notify_halfway: false,
+            notify_at: Vec::new(),
+            bell_count: 0,
+            flash: false,
+            alarm_every: None,
+            alarm: None,
+            tick: None,
+            media: Vec::new(),
+            escalation: Escalation::default(),
+            push: Vec::new(),
+            term_badge: false,
+            mqtt: None,
+            metrics_port: None,
+            serve: None,
+            serve_token: None,
+            output: Output::Plain,
+            ticker: false,
+            mouse: false,
+            calendar: None,
+            keep_awake: false,
+            dnd: false,
+            tick_rate: MINIMAL_TICK_RATE,
+            ..self
+        }
+    }
+}
+
+/// Watches the config file on a background thread and sends the settings resolved
+/// from it after every change, or the error if it no longer loads.
+pub fn watch(options: Options) -> Receiver<Result<Settings, String>> {
+    let (sender, receiver) = mpsc::channel();
+    let path = options.config.clone().or_else(Config::path);
+
+    thread::spawn(move || {
+        let Some(path) = path else {
+            return;
+        };
+        let modified = || fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let mut last_modified = modified();
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let current = modified();
+            if current.is_none() || current == last_modified {
+                continue;
+            }
+            last_modified = current;
+
+            let settings = Config::load(Some(&path))
+                .and_then(|config| config.with_profile(options.profile.as_deref()))
+                .and_then(|config| Settings::resolve(&options, &config));
+            if sender.send(settings).is_err() {
+                return;
+            }
+        }
+    });
+    receiver
+}
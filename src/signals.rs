@@ -0,0 +1,68 @@
+use std::sync::atomic::{
+    AtomicI32,
+    Ordering
+};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use crate::control::Control;
+
+/// How many minutes SIGUSR2 adds to the selected timer.
+const SIGUSR2_MINUTES: i64 = 5;
+
+/// The SIGINT or SIGTERM that asked to quit, 0 before one arrived.
+static TERMINATED_BY: AtomicI32 = AtomicI32::new(0);
+
+/// Waits for SIGUSR1 and SIGUSR2 on a background thread and sends what they ask for: SIGUSR1 pauses
+/// or resumes the selected timer, SIGUSR2 adds five minutes to it. Must be called before other threads
+/// are spawned, they inherit the mask that keeps the signals from killing the process.
+#[cfg(unix)]
+pub fn listen(sender: Sender<Result<Control, String>>) {
+    let set = unsafe {
+        let mut set: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGUSR1);
+        libc::sigaddset(&mut set, libc::SIGUSR2);
+        // Blocked signals stay pending until sigwait picks them up
+        libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut());
+        set
+    };
+
+    thread::spawn(move || loop {
+        let mut number = 0;
+        if unsafe { libc::sigwait(&set, &mut number) } != 0 {
+            continue;
+        }
+        let control = if number == libc::SIGUSR1 {
+            Control::TogglePause
+        } else {
+            Control::Adjust(chrono::TimeDelta::minutes(SIGUSR2_MINUTES))
+        };
+        if sender.send(Ok(control)).is_err() {
+            break;
+        }
+    });
+}
+
+/// Turns SIGINT and SIGTERM into a request to quit that the run loop picks up, so the timers
+/// given up are handled like on quitting with the key. A handler rather than a blocked signal
+/// like in `listen`, since commands started later inherit the mask but not the handler.
+#[cfg(unix)]
+pub fn catch_termination() {
+    extern "C" fn handle(signal: libc::c_int) {
+        TERMINATED_BY.store(signal, Ordering::Relaxed);
+    }
+    let handler = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+/// The signal that asked to quit, if one did.
+pub fn terminated_by() -> Option<i32> {
+    match TERMINATED_BY.load(Ordering::Relaxed) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
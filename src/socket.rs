@@ -0,0 +1,300 @@
+use std::fs;
+use std::io::{
+    BufRead,
+    BufReader,
+    Write
+};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{
+    UnixListener,
+    UnixStream
+};
+use std::path::PathBuf;
+use std::sync::mpsc::{
+    self,
+    Sender
+};
+use std::thread;
+use std::time::Duration;
+use crate::control::{
+    Control,
+    TimerStatus,
+    TimerWait
+};
+use crate::exit_with;
+use crate::machine::{
+    Machine,
+    Timer
+};
+use crate::units::format_seconds;
+
+/// How long a client waits for a running instance to answer.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where running instances put their sockets, one per process: `$XDG_RUNTIME_DIR/endzeit`,
+/// or a directory only the user can read in the temp dir.
+pub fn runtime_dir() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("endzeit"),
+        None => std::env::temp_dir().join(format!("endzeit-{}", unsafe { libc::getuid() })),
+    }
+}
+
+/// The socket of a running instance, removed again on drop. Every connection sends one line,
+/// `status`, `wait NAME` or a command like `pause`, and gets one line back. The answer to
+/// `wait NAME` only comes once the timer NAME has finished.
+pub struct StatusSocket {
+    path: PathBuf,
+}
+
+impl StatusSocket {
+    /// Listens on `<runtime dir>/<pid>.sock`. Commands go to `controls`, status requests to `status_requests`
+    /// with a sender for the timers of the instance, and waits for a timer to `timer_waits`.
+    pub fn bind(
+        controls: Sender<Result<Control, String>>,
+        status_requests: Sender<Sender<Vec<TimerStatus>>>,
+        timer_waits: Sender<TimerWait>,
+    ) -> Result<Self, String> {
+        let dir = runtime_dir();
+        fs::DirBuilder::new().recursive(true).mode(0o700).create(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join(format!("{}.sock", std::process::id()));
+        // A leftover of a crashed process that had the same pid
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).map_err(|e| format!("Failed to listen on {}: {}", path.display(), e))?;
+
+        thread::spawn(move || {
+            for stream in listener.incoming().map_while(Result::ok) {
+                let controls = controls.clone();
+                let status_requests = status_requests.clone();
+                let timer_waits = timer_waits.clone();
+                thread::spawn(move || answer(stream, &controls, &status_requests, &timer_waits));
+            }
+        });
+        Ok(Self { path })
+    }
+}
+
+impl Drop for StatusSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn answer(
+    stream: UnixStream,
+    controls: &Sender<Result<Control, String>>,
+    status_requests: &Sender<Sender<Vec<TimerStatus>>>,
+    timer_waits: &Sender<TimerWait>,
+) {
+    let mut request = String::new();
+    if BufReader::new(&stream).read_line(&mut request).is_err() {
+        return;
+    }
+    let reply = match request.trim() {
+        "status" => {
+            let (sender, receiver) = mpsc::channel();
+            let _ = status_requests.send(sender);
+            match receiver.recv_timeout(ANSWER_TIMEOUT) {
+                Ok(timers) => serde_json::to_string(&timers).unwrap_or_default(),
+                Err(_) => "error: no answer".to_string(),
+            }
+        }
+        request if request.starts_with("wait ") => {
+            let (reply, receiver) = mpsc::channel();
+            let name = request["wait ".len()..].trim().to_string();
+            let _ = timer_waits.send(TimerWait { name, reply });
+            // Holds the connection until the timer is done, the instance quitting drops the sender
+            match receiver.recv() {
+                Ok(Ok(())) => "finished".to_string(),
+                Ok(Err(err)) => format!("error: {}", err),
+                Err(_) => return,
+            }
+        }
+        command => match Control::parse(command) {
+            Ok(control) => {
+                let _ = controls.send(Ok(control));
+                "ok".to_string()
+            }
+            Err(err) => format!("error: {}", err),
+        },
+    };
+    let _ = writeln!(&stream, "{}", reply);
+}
+
+/// The pids of the running instances, sockets left behind by crashed ones are removed.
+pub fn instances() -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(runtime_dir()) else {
+        return Vec::new();
+    };
+    let mut pids: Vec<u32> = entries.filter_map(Result::ok)
+        .filter_map(|entry| {
+            let pid = entry.path().file_stem()?.to_str()?.parse().ok()?;
+            if UnixStream::connect(entry.path()).is_err() {
+                let _ = fs::remove_file(entry.path());
+                return None;
+            }
+            Some(pid)
+        })
+        .collect();
+    pids.sort();
+    pids
+}
+
+/// Sends one request line to the instance with `pid` and returns its answer.
+pub fn request(pid: u32, request: &str) -> Result<String, String> {
+    let path = runtime_dir().join(format!("{}.sock", pid));
+    let stream = UnixStream::connect(&path).map_err(|_| format!("No endzeit running with pid {}", pid))?;
+    stream.set_read_timeout(Some(ANSWER_TIMEOUT)).map_err(|e| e.to_string())?;
+    writeln!(&stream, "{}", request).map_err(|e| format!("Failed to talk to {}: {}", pid, e))?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).map_err(|e| format!("No answer from {}: {}", pid, e))?;
+    match reply.trim().strip_prefix("error: ") {
+        Some(err) => Err(err.to_string()),
+        None => Ok(reply.trim().to_string()),
+    }
+}
+
+/// Waits until the timer `name` of the instance with `pid` has finished. Returns false if the
+/// instance quit first, like after handing its timers to a background instance.
+pub fn wait_for(pid: u32, name: &str) -> Result<bool, String> {
+    let path = runtime_dir().join(format!("{}.sock", pid));
+    let stream = UnixStream::connect(&path).map_err(|_| format!("No endzeit running with pid {}", pid))?;
+    writeln!(&stream, "wait {}", name).map_err(|e| format!("Failed to talk to {}: {}", pid, e))?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).map_err(|e| format!("No answer from {}: {}", pid, e))?;
+    match reply.trim() {
+        "" => Ok(false),
+        "finished" => Ok(true),
+        reply => Err(reply.strip_prefix("error: ").unwrap_or(reply).to_string()),
+    }
+}
+
+/// `--after-timer`: blocks until the timer `name` of a running instance has finished. Follows it
+/// when its instance hands it to a background one with the detach key.
+pub fn wait_for_timer(name: &str) -> Result<(), String> {
+    loop {
+        let pid = find_timer(name).ok_or_else(|| format!("No running timer named {}", name))?;
+        if wait_for(pid, name)? {
+            return Ok(());
+        }
+        // Give the instance taking over the timers time to open its socket
+        thread::sleep(ANSWER_TIMEOUT);
+    }
+}
+
+/// The instance with the pid `name`, or the first one running a timer called `name`.
+pub fn find(name: &str) -> Option<u32> {
+    match name.parse().ok().filter(|pid| instances().contains(pid)) {
+        Some(pid) => Some(pid),
+        None => find_timer(name),
+    }
+}
+
+/// The first instance running a timer called `name`.
+pub fn find_timer(name: &str) -> Option<u32> {
+    instances().into_iter().find(|pid| {
+        timers(*pid).is_ok_and(|timers| timers.iter().any(|timer| timer.name == name))
+    })
+}
+
+/// Asks the instance with `pid` for its timers.
+pub fn timers(pid: u32) -> Result<Vec<TimerStatus>, String> {
+    let reply = request(pid, "status")?;
+    serde_json::from_str(&reply).map_err(|e| format!("Invalid answer from {}: {}", pid, e))
+}
+
+/// `endzeit list`: prints one line per running instance with its number of timers and the next one to finish.
+pub fn run_list(machine: Option<Machine>) -> color_eyre::Result<()> {
+    let mut listed = Vec::new();
+    for pid in instances() {
+        let timers = match timers(pid) {
+            Ok(timers) => timers,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+        let next = timers.iter().filter(|timer| !timer.finished).min_by_key(|timer| timer.remaining);
+        if machine.is_some() {
+            listed.push(serde_json::json!({ "pid": pid, "timers": timers.len(), "next": next.map(Timer::from) }));
+            continue;
+        }
+        let next = match next {
+            Some(timer) => format!("next: {} in {}", timer.name, format_seconds(timer.remaining)),
+            None => "all finished".to_string(),
+        };
+        let plural = if timers.len() == 1 { "" } else { "s" };
+        println!("{:>7}  {} timer{}, {}", pid, timers.len(), plural, next);
+    }
+    if let Some(machine) = machine {
+        machine.print("list", serde_json::json!({ "instances": listed }));
+    }
+    Ok(())
+}
+
+/// `endzeit status`: prints the timers of the instance with `pid`, or of all running instances.
+pub fn run_status(pid: Option<u32>, machine: Option<Machine>) -> color_eyre::Result<()> {
+    let pids = match pid {
+        Some(pid) => vec![pid],
+        None => instances(),
+    };
+    if let Some(machine) = machine {
+        let instances: Vec<_> = pids.into_iter()
+            .map(|pid| {
+                let timers = timers(pid).unwrap_or_else(|err| exit_with(&err));
+                serde_json::json!({ "pid": pid, "timers": timers.iter().map(Timer::from).collect::<Vec<_>>() })
+            })
+            .collect();
+        machine.print("status", serde_json::json!({ "instances": instances }));
+        return Ok(());
+    }
+    if pids.is_empty() {
+        exit_with("No endzeit running");
+    }
+
+    for pid in pids {
+        for timer in timers(pid).unwrap_or_else(|err| exit_with(&err)) {
+            let state = if timer.finished {
+                "finished".to_string()
+            } else if timer.paused {
+                format!("{} left, paused", format_seconds(timer.remaining))
+            } else {
+                format!("{} left", format_seconds(timer.remaining))
+            };
+            let marker = if timer.selected { "*" } else { " " };
+            println!("{:>7} {} {}  {}  (until {})", pid, marker, timer.name, state, timer.target.format("%Y-%m-%d %H:%M:%S"));
+        }
+    }
+    Ok(())
+}
+
+/// `endzeit pause`, `resume` and `cancel`: sends `command` to the instance with `pid`, which may only be
+/// left out while a single one is running. It acts on that instance's selected timer.
+pub fn run_control(pid: Option<u32>, command: &str, machine: Option<Machine>) -> color_eyre::Result<()> {
+    let pid = match (pid, instances().as_slice()) {
+        (Some(pid), _) => pid,
+        (None, []) => exit_with("No endzeit running"),
+        (None, [pid]) => *pid,
+        (None, pids) => {
+            let pids: Vec<String> = pids.iter().map(u32::to_string).collect();
+            exit_with(&format!("Several endzeit running, choose one with --pid: {}", pids.join(", ")));
+        }
+    };
+    let result = request(pid, command);
+    if let Some(machine) = machine {
+        let mut fields = serde_json::json!({ "pid": pid, "command": command, "ok": result.is_ok() });
+        if let Err(err) = &result {
+            fields["error"] = err.as_str().into();
+        }
+        machine.print("control", fields);
+        if result.is_err() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    result.unwrap_or_else(|err| exit_with(&err));
+    Ok(())
+}
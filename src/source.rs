@@ -0,0 +1,47 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use chrono::NaiveDateTime;
+
+use crate::spec::parse_datetime;
+
+/// Where a countdown gets its target from. Dynamic sources are polled every tick
+/// and may move the target while the countdown is running.
+pub enum TargetSource {
+    Fixed,
+    File {
+        path: PathBuf,
+        modified: Option<SystemTime>,
+    },
+}
+
+impl TargetSource {
+    /// Opens a file containing a single date/time and returns the source together with its current target.
+    pub fn open_file(path: &str) -> Result<(Self, NaiveDateTime), String> {
+        let path = PathBuf::from(path);
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let target = read_target_file(&path)?;
+        Ok((TargetSource::File { path, modified }, target))
+    }
+
+    /// Returns the new target if the source changed since the last poll.
+    pub fn poll(&mut self) -> Option<NaiveDateTime> {
+        match self {
+            TargetSource::Fixed => None,
+            TargetSource::File { path, modified } => {
+                let current = fs::metadata(&*path).and_then(|m| m.modified()).ok();
+                if current.is_none() || current == *modified {
+                    return None;
+                }
+                *modified = current;
+                // Editors may leave the file half-written, it will be picked up on the next write
+                read_target_file(path).ok()
+            }
+        }
+    }
+}
+
+fn read_target_file(path: &PathBuf) -> Result<NaiveDateTime, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_datetime(contents.trim())
+}
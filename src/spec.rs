@@ -0,0 +1,259 @@
+use chrono::{
+    NaiveDate,
+    NaiveDateTime,
+    TimeDelta
+};
+use clap::ValueEnum;
+use ratatui::style::Color;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::clock;
+use crate::exec::{
+    Launcher,
+    Shell
+};
+use crate::source::TargetSource;
+
+/// Date formats from the config file, tried before YYYY-MM-DD.
+static DATE_FORMATS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// A countdown as given on the command line with `--target`.
+pub struct TargetSpec {
+    /// The WHEN part as given, kept to resolve it again on restart
+    pub when: String,
+    pub target: NaiveDateTime,
+    pub source: TargetSource,
+    pub name: Option<String>,
+    pub project: Option<String>,
+    pub color: Option<Color>,
+    /// Commands to run in order when it completes
+    pub execute: Vec<String>,
+    pub launcher: Launcher,
+}
+
+pub fn parse_time(time: &str) -> Result<(u32, u32, u32), String> {
+    let parts: Vec<&str> = time.split(':').collect();
+    match parts.len() {
+        3 => {
+            let hours = u32::from_str(parts[0]).map_err(|_| "Invalid hour")?;
+            let minutes = u32::from_str(parts[1]).map_err(|_| "Invalid minute")?;
+            let seconds = u32::from_str(parts[2]).map_err(|_| "Invalid second")?;
+            Ok((hours, minutes, seconds))
+        }
+        2 => {
+            let hours = u32::from_str(parts[0]).map_err(|_| "Invalid hour")?;
+            let minutes = u32::from_str(parts[1]).map_err(|_| "Invalid minute")?;
+            Ok((hours, minutes, 0))
+        }
+        1 => {
+            let hours = u32::from_str(parts[0]).map_err(|_| "Invalid hour")?;
+            Ok((hours, 0, 0))
+        }
+        _ => Err("Invalid time format, use HH[:MM[:SS]]".to_string()),
+    }
+}
+
+pub fn validate_datetime(date: NaiveDate, time: Option<&str>) -> Result<NaiveDateTime, String> {
+    match time {
+        Some(t) => {
+            let (hours, minutes, seconds) = parse_time(t)?;
+            date.and_hms_opt(hours, minutes, seconds).ok_or_else(|| format!("Invalid time: {}", t))
+        },
+        None => Ok(clock::now()),
+    }
+}
+
+pub fn set_date_formats(formats: Vec<String>) {
+    let _ = DATE_FORMATS.set(formats);
+}
+
+/// Parses a date in one of the configured formats or as YYYY-MM-DD.
+pub fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    DATE_FORMATS.get().into_iter().flatten()
+        .map(String::as_str)
+        .chain(["%Y-%m-%d"])
+        .find_map(|format| NaiveDate::parse_from_str(input, format).ok())
+        .ok_or_else(|| "Invalid date format, use YYYY-MM-DD".to_string())
+}
+
+/// Parses a point in time: `YYYY-MM-DD[( |T)HH[:MM[:SS]]]`, `+DURATION` relative to now,
+/// or just `HH[:MM[:SS]]` for its next occurrence (today, or tomorrow once it has passed).
+pub fn parse_datetime(input: &str) -> Result<NaiveDateTime, String> {
+    let input = input.trim();
+    let now = clock::now();
+    if let Some(duration) = input.strip_prefix('+') {
+        return now.checked_add_signed(parse_duration(duration)?).ok_or_else(|| "Too far in the future".to_string());
+    }
+
+    let (date_part, time_part) = match input.split_once([' ', 'T']) {
+        Some((date, time)) => (Some(date), Some(time)),
+        None if parse_date(input).is_ok() => (Some(input), None),
+        None => (None, Some(input)),
+    };
+
+    let date = match date_part {
+        Some(d) => parse_date(d)?,
+        None => now.date(),
+    };
+    let (hours, minutes, seconds) = match time_part {
+        Some(t) => parse_time(t)?,
+        None => (0, 0, 0),
+    };
+
+    let datetime = date.and_hms_opt(hours, minutes, seconds).ok_or_else(|| format!("Invalid time: {}", input))?;
+    if date_part.is_none() && datetime <= now {
+        return Ok(datetime + TimeDelta::days(1));
+    }
+    Ok(datetime)
+}
+
+/// Resolves the `<when>` part of a target, `file:PATH` reads it from a file that is watched for changes.
+pub fn parse_when(when: &str) -> Result<(TargetSource, NaiveDateTime), String> {
+    match when.strip_prefix("file:") {
+        Some(path) => TargetSource::open_file(path),
+        None => Ok((TargetSource::Fixed, parse_datetime(when)?)),
+    }
+}
+
+pub fn parse_color(color: &str) -> Result<Color, String> {
+    Color::from_str(color).map_err(|_| format!("Invalid color: {}", color))
+}
+
+/// Parses a duration like `90`, `15m` or `1h30m` (units: d, h, m, s; bare numbers are seconds).
+pub fn parse_duration(input: &str) -> Result<TimeDelta, String> {
+    let input = input.trim();
+    let out_of_range = || "duration out of range".to_string();
+    if let Ok(seconds) = i64::from_str(input) {
+        return TimeDelta::try_seconds(seconds).ok_or_else(out_of_range);
+    }
+
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value = i64::from_str(&number).map_err(|_| format!("Invalid duration: {}", input))?;
+        let delta = match c {
+            'd' => TimeDelta::try_days(value),
+            'h' => TimeDelta::try_hours(value),
+            'm' => TimeDelta::try_minutes(value),
+            's' => TimeDelta::try_seconds(value),
+            _ => return Err(format!("Invalid duration unit '{}', use d, h, m or s", c)),
+        };
+        total = delta.and_then(|delta| total.checked_add(&delta)).ok_or_else(out_of_range)?;
+        number.clear();
+    }
+    if !number.is_empty() || input.is_empty() {
+        return Err(format!("Invalid duration: {}", input));
+    }
+
+    Ok(total)
+}
+
+/// Parses an ISO 8601 duration like `PT2H35M47S` or `P1W` into seconds.
+/// Years and months use the same 365 and 30 day approximations as the gauge.
+pub fn parse_iso_duration(input: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid ISO 8601 duration: {}", input);
+    let rest = input.strip_prefix('P').ok_or_else(invalid)?;
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' if !in_time && number.is_empty() => in_time = true,
+            '0'..='9' => number.push(c),
+            _ => {
+                let value = u64::from_str(&number).map_err(|_| invalid())?;
+                let unit_seconds = match (c, in_time) {
+                    ('Y', false) => 86_400 * 365,
+                    ('M', false) => 86_400 * 30,
+                    ('W', false) => 604_800,
+                    ('D', false) => 86_400,
+                    ('H', true) => 3600,
+                    ('M', true) => 60,
+                    ('S', true) => 1,
+                    _ => return Err(invalid()),
+                };
+                total = value.checked_mul(unit_seconds).and_then(|seconds| total.checked_add(seconds)).ok_or_else(invalid)?;
+                number.clear();
+            }
+        }
+    }
+    if !number.is_empty() || rest.is_empty() || rest.ends_with('T') {
+        return Err(invalid());
+    }
+
+    Ok(total)
+}
+
+pub fn parse_shell(shell: &str) -> Result<Shell, String> {
+    Shell::from_str(shell, true).map_err(|_| format!("Invalid shell: {}", shell))
+}
+
+/// Parses `<when>[,name=...][,color=...][,cwd=...][,shell=...][,execute=...]`, `label=` is accepted as an alias for `name=`.
+/// `cwd=` and `shell=` say where and how `execute=` runs.
+/// `<when>` may be `file:PATH` to read the target from a file that is watched for changes.
+/// `execute=` takes the rest of the spec verbatim, so the command may contain commas.
+pub fn parse_target_spec(spec: &str) -> Result<TargetSpec, String> {
+    let (when, mut rest) = split_option(spec);
+
+    let (source, target) = parse_when(&when)?;
+
+    let mut target_spec = TargetSpec {
+        when,
+        target,
+        source,
+        name: None,
+        project: None,
+        color: None,
+        execute: Vec::new(),
+        launcher: Launcher::default(),
+    };
+
+    while let Some(options) = rest {
+        // Commands take the rest of the spec, since they may contain commas themselves,
+        // further commands follow as ,execute=
+        if let Some(commands) = options.strip_prefix("execute=") {
+            target_spec.execute = commands.split(",execute=").map(str::to_string).collect();
+            break;
+        }
+        let (option, next) = split_option(options);
+        match option.split_once('=') {
+            Some(("name" | "label", name)) => target_spec.name = Some(name.to_string()),
+            Some(("project" | "tag", project)) => target_spec.project = Some(project.to_string()),
+            Some(("color", color)) => target_spec.color = Some(parse_color(color)?),
+            Some(("cwd", cwd)) => target_spec.launcher.cwd = Some(PathBuf::from(cwd)),
+            Some(("shell", shell)) => target_spec.launcher.shell = Some(parse_shell(shell)?),
+            _ => return Err(format!("Unknown target option: {}", option)),
+        }
+        rest = next;
+    }
+
+    Ok(target_spec)
+}
+
+/// Writes a value of a `--target` spec so that `parse_target_spec` reads it back: commas are doubled.
+pub fn escape_option(value: &str) -> String {
+    value.replace(',', ",,")
+}
+
+/// Splits the first part off a spec at the first single comma, a doubled comma is a comma of the value.
+fn split_option(spec: &str) -> (String, Option<&str>) {
+    let mut value = String::new();
+    let mut chars = spec.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c != ',' {
+            value.push(c);
+        } else if chars.next_if(|(_, next)| *next == ',').is_some() {
+            value.push(',');
+        } else {
+            return (value, Some(&spec[index + 1..]));
+        }
+    }
+    (value, None)
+}
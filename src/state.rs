@@ -0,0 +1,20 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// Returns `<state dir>/endzeit/<sub>`, creating it if needed. Uses the XDG state dir
+/// where there is one and the local data dir elsewhere.
+pub fn state_dir(sub: &str) -> Result<PathBuf, String> {
+    let base = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .ok_or("Could not determine a directory for state files")?;
+    let dir = base.join("endzeit").join(sub);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Turns a user given name into a safe file name, so names key their own state file.
+pub fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use chrono::{
+    Datelike,
+    NaiveDateTime,
+    TimeDelta,
+    Timelike
+};
+use ratatui::crossterm::{
+    event::{
+        self,
+        Event,
+        KeyCode
+    },
+    terminal
+};
+
+use crate::clock;
+use crate::config::Config;
+use crate::history::{
+    self,
+    Entry
+};
+
+/// How many suggestions are offered, each started with its number key.
+const MAX_SUGGESTIONS: usize = 9;
+/// Timers started further than this from the current time of day count as much as `OFF_HOURS_WEIGHT`.
+const NEARBY_MINUTES: i64 = 180;
+const OFF_HOURS_WEIGHT: f64 = 0.1;
+/// Extra weight of timers started on the same weekday.
+const SAME_WEEKDAY_BONUS: f64 = 0.5;
+/// Score of presets that never showed up in the history, so they are offered after the habits.
+const PRESET_SCORE: f64 = 0.05;
+
+/// What to start for the chosen suggestion.
+pub enum Choice {
+    /// A `--target` spec
+    Target(String),
+    Preset(String),
+}
+
+struct Suggestion {
+    label: String,
+    reason: String,
+    choice: Choice,
+    score: f64,
+}
+
+/// `endzeit suggest`: ranks the timers of the history by how often they were started around this time
+/// of day and weekday, adds the presets, and returns the one picked with its number key.
+pub fn choose(config: &Config) -> Option<Choice> {
+    let entries = history::load().unwrap_or_else(|err| crate::exit_with(&err));
+    let mut suggestions = from_history(&entries, clock::now());
+    for key in config.presets.keys() {
+        suggestions.push(Suggestion {
+            label: format!("preset {}", key),
+            reason: "from the config".to_string(),
+            choice: Choice::Preset(key.clone()),
+            score: PRESET_SCORE,
+        });
+    }
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.label.cmp(&b.label)));
+    suggestions.truncate(MAX_SUGGESTIONS);
+
+    if suggestions.is_empty() {
+        println!("No suggestions yet, they come from the history of finished timers and from presets");
+        return None;
+    }
+    for (number, suggestion) in suggestions.iter().enumerate() {
+        println!("{}) {:<24} {}", number + 1, suggestion.label, suggestion.reason);
+    }
+    println!("Press a number to start it, any other key to quit");
+
+    let number = read_digit()?;
+    suggestions.into_iter().nth(number.checked_sub(1)?).map(|suggestion| suggestion.choice)
+}
+
+/// Groups the history by name and duration and scores each group by how close to `now` its timers were started.
+fn from_history(entries: &[Entry], now: NaiveDateTime) -> Vec<Suggestion> {
+    let mut groups: HashMap<(Option<&str>, u64), (f64, usize, i64)> = HashMap::new();
+    for entry in entries.iter().filter(|entry| !entry.aborted) {
+        let Some(duration) = entry.duration.filter(|duration| *duration >= 60) else {
+            continue;
+        };
+        // Whole minutes, so 24m59s and 25m count as the same timer
+        let duration = (duration + 30) / 60 * 60;
+        let started = entry.finished - TimeDelta::seconds(duration as i64);
+        let minutes_apart = (minute_of_day(started) - minute_of_day(now)).abs();
+        let minutes_apart = minutes_apart.min(24 * 60 - minutes_apart);
+        let mut weight = if minutes_apart <= NEARBY_MINUTES {
+            1.0 - minutes_apart as f64 / NEARBY_MINUTES as f64 * (1.0 - OFF_HOURS_WEIGHT)
+        } else {
+            OFF_HOURS_WEIGHT
+        };
+        if started.weekday() == now.weekday() {
+            weight += SAME_WEEKDAY_BONUS;
+        }
+
+        let group = groups.entry((entry.name.as_deref(), duration)).or_default();
+        group.0 += weight;
+        group.1 += 1;
+        group.2 += minute_of_day(started);
+    }
+
+    groups.into_iter().map(|((name, duration), (score, count, minutes))| {
+        let duration = duration_spec(duration);
+        let average = minutes / count as i64;
+        let times = if count == 1 { "once".to_string() } else { format!("{} times", count) };
+        let mut target = format!("+{}", duration);
+        if let Some(name) = name {
+            target.push_str(&format!(",name={}", name));
+        }
+        Suggestion {
+            label: format!("{} {}", name.unwrap_or("timer"), duration),
+            reason: format!("started around {:02}:{:02}, {}", average / 60, average % 60, times),
+            choice: Choice::Target(target),
+            score,
+        }
+    }).collect()
+}
+
+fn minute_of_day(time: NaiveDateTime) -> i64 {
+    (time.hour() * 60 + time.minute()) as i64
+}
+
+/// Writes whole minutes the way `--target` reads them, like `1h30m`.
+fn duration_spec(seconds: u64) -> String {
+    let (hours, minutes) = (seconds / 3600, seconds % 3600 / 60);
+    match (hours, minutes) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
+}
+
+/// Waits for a single key press, returns the digit if it was one.
+fn read_digit() -> Option<usize> {
+    terminal::enable_raw_mode().ok()?;
+    let key = loop {
+        match event::read() {
+            Ok(Event::Key(key)) => break Some(key.code),
+            Ok(_) => continue,
+            Err(_) => break None,
+        }
+    };
+    let _ = terminal::disable_raw_mode();
+    match key? {
+        KeyCode::Char(c) => c.to_digit(10).map(|digit| digit as usize),
+        _ => None,
+    }
+}
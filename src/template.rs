@@ -0,0 +1,83 @@
+use crate::units::UNITS;
+
+enum Segment {
+    Text(String),
+    Unit(usize),
+    /// A unit with two digits, like `{mm}`
+    Padded(usize),
+    /// A value the caller fills in, like `{name}`
+    Field(String),
+}
+
+/// A format string like `{h}h {m}m` for rendering durations.
+/// Only the units used in the template are broken out: the largest one absorbs
+/// everything above it and anything below the smallest one is dropped.
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        Self::parse_with(template, &[])
+    }
+
+    /// Like `parse`, and also takes the placeholders `fields`, which `render_with` fills in.
+    /// Besides the units `{days}` is `{d}`, and `{hh}`, `{mm}` and `{ss}` have two digits.
+    pub fn parse_with(template: &str, fields: &[&str]) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        let unit = |name: &str| UNITS.iter().position(|(unit, _)| *unit == name);
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..].find('}').ok_or_else(|| format!("Unclosed placeholder in: {}", template))?;
+            let name = &rest[start + 1..start + end];
+            let segment = match name {
+                "days" => unit("d").map(Segment::Unit),
+                "hh" | "mm" | "ss" => unit(&name[..1]).map(Segment::Padded),
+                _ if fields.contains(&name) => Some(Segment::Field(name.to_string())),
+                _ => unit(name).map(Segment::Unit),
+            };
+            let segment = segment.ok_or_else(|| {
+                let fields: String = fields.iter().map(|field| format!(", {}", field)).collect();
+                format!("Unknown placeholder {{{}}}, use one of y, mo, w, d, h, m, s, days, hh, mm, ss{}", name, fields)
+            })?;
+            segments.push(segment);
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn render(&self, total_seconds: u64) -> String {
+        self.render_with(total_seconds, &[])
+    }
+
+    /// Renders the units of `total_seconds` and the `fields` by name, missing ones stay empty.
+    pub fn render_with(&self, total_seconds: u64, fields: &[(&str, String)]) -> String {
+        let mut values = [0; UNITS.len()];
+        let mut remaining = total_seconds;
+        for (index, (_, unit_seconds)) in UNITS.iter().enumerate() {
+            if self.uses(index) {
+                values[index] = remaining / unit_seconds;
+                remaining %= unit_seconds;
+            }
+        }
+
+        self.segments.iter().map(|segment| match segment {
+            Segment::Text(text) => text.clone(),
+            Segment::Unit(index) => values[*index].to_string(),
+            Segment::Padded(index) => format!("{:02}", values[*index]),
+            Segment::Field(name) => fields.iter().find(|(field, _)| field == name).map(|(_, value)| value.clone()).unwrap_or_default(),
+        }).collect()
+    }
+
+    fn uses(&self, unit: usize) -> bool {
+        self.segments.iter().any(|segment| matches!(segment, Segment::Unit(index) | Segment::Padded(index) if *index == unit))
+    }
+}
@@ -0,0 +1,53 @@
+use std::env;
+use std::io::{
+    self,
+    Write
+};
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine
+};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Terminal-native notification escape sequences, these work over SSH as they travel with the output.
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TermNotify {
+    /// OSC 9, supported by iTerm2, kitty, WezTerm, Windows Terminal
+    Osc9,
+    /// OSC 777, supported by foot, WezTerm, rxvt-unicode and VTE based terminals
+    Osc777,
+}
+
+impl TermNotify {
+    pub fn notify(self, title: &str, body: &str) {
+        let sequence = match self {
+            TermNotify::Osc9 => format!("\x1b]9;{}: {}\x07", title, body),
+            TermNotify::Osc777 => format!("\x1b]777;notify;{};{}\x07", title, body),
+        };
+        write_sequence(&sequence);
+    }
+}
+
+/// Shows `text` as the iTerm2 badge and publishes it as WezTerm user vars, empty text clears both.
+pub fn set_badge(name: &str, text: &str) {
+    let badge = if text.is_empty() { String::new() } else { format!("{} {}", name, text) };
+    write_sequence(&format!("\x1b]1337;SetBadgeFormat={}\x07", STANDARD.encode(badge)));
+    write_sequence(&format!("\x1b]1337;SetUserVar=endzeit_name={}\x07", STANDARD.encode(name)));
+    write_sequence(&format!("\x1b]1337;SetUserVar=endzeit_remaining={}\x07", STANDARD.encode(text)));
+}
+
+/// Writes an escape sequence straight to the terminal, wrapped for tmux so it reaches the outer terminal.
+pub fn write_sequence(sequence: &str) {
+    let sequence = if env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence.to_string()
+    };
+
+    let mut stdout = io::stdout();
+    // Notifications are best effort, a failed write must not take down the countdown
+    let _ = stdout.write_all(sequence.as_bytes());
+    let _ = stdout.flush();
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::spec::parse_color;
+
+/// Themes built into the binary, selected with `--theme builtin:NAME`.
+const BUILTIN_THEMES: [(&str, &str); 4] = [
+    ("ocean", include_str!("../assets/themes/ocean.toml")),
+    ("sunset", include_str!("../assets/themes/sunset.toml")),
+    ("mono", include_str!("../assets/themes/mono.toml")),
+    ("solarized-light", include_str!("../assets/themes/solarized-light.toml")),
+];
+
+/// Colors of the gauges. Timers without a color of their own take the next one from the palette.
+pub struct Theme {
+    pub palette: Vec<Color>,
+    pub background: Color,
+}
+
+/// A theme file, the same keys as in the config.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    colors: Vec<String>,
+    background: Option<String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            palette: vec![
+                Color::Green,
+                Color::Cyan,
+                Color::Yellow,
+                Color::Magenta,
+                Color::Blue,
+                Color::Red,
+            ],
+            background: Color::Black,
+        }
+    }
+}
+
+impl Theme {
+    /// Starts from the theme `base`, `builtin:NAME` or the path of a TOML file, and replaces its
+    /// palette and background with the given ones.
+    pub fn new(base: Option<&str>, palette: Option<&[String]>, background: Option<&str>) -> Result<Self, String> {
+        let mut theme = match base {
+            Some(base) => Self::load(base)?,
+            None => Self::default(),
+        };
+        if let Some(palette) = palette.filter(|palette| !palette.is_empty()) {
+            theme.palette = palette.iter().map(|color| parse_color(color)).collect::<Result<_, _>>()?;
+        }
+        if let Some(background) = background {
+            theme.background = parse_color(background)?;
+        }
+        Ok(theme)
+    }
+
+    /// One of the built-in themes, by name.
+    pub fn builtin(name: &str) -> Result<Self, String> {
+        let Some((_, contents)) = BUILTIN_THEMES.iter().find(|(builtin, _)| *builtin == name) else {
+            let names: Vec<&str> = BUILTIN_THEMES.iter().map(|(name, _)| *name).collect();
+            return Err(format!("Unknown theme builtin:{}, built in are {}", name, names.join(", ")));
+        };
+        Self::parse(contents).map_err(|err| format!("Theme builtin:{}: {}", name, err))
+    }
+
+    fn load(theme: &str) -> Result<Self, String> {
+        if let Some(name) = theme.strip_prefix("builtin:") {
+            return Self::builtin(name);
+        }
+        let contents = fs::read_to_string(theme).map_err(|e| format!("Failed to read theme {}: {}", theme, e))?;
+        Self::parse(&contents).map_err(|err| format!("Invalid theme {}: {}", theme, err))
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let file: ThemeFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if file.colors.is_empty() {
+            return Err("colors is empty".to_string());
+        }
+        Self::new(None, Some(&file.colors), file.background.as_deref())
+    }
+
+    pub fn color(&self, index: usize) -> Color {
+        self.palette[index % self.palette.len()]
+    }
+}
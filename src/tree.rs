@@ -0,0 +1,181 @@
+use std::collections::{
+    BTreeMap,
+    HashSet
+};
+use std::time::Duration;
+use chrono::NaiveDateTime;
+use color_eyre::Result;
+use ratatui::{
+    crossterm::event::{
+        self,
+        Event,
+        KeyCode
+    },
+    style::{
+        Modifier,
+        Style
+    },
+    text::Line,
+    widgets::{
+        Block,
+        List,
+        ListItem,
+        ListState
+    },
+};
+
+use crate::control::TimerStatus;
+use crate::socket;
+use crate::units::format_seconds;
+
+/// How often the tree view asks the instances for their timers.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+/// Shown for timers without `project=`.
+const NO_PROJECT: &str = "(no project)";
+
+/// The timers of one project across all running instances.
+struct Group {
+    project: Option<String>,
+    /// With the pid of the instance running them, by target
+    timers: Vec<(u32, TimerStatus)>,
+}
+
+impl Group {
+    fn title(&self) -> &str {
+        self.project.as_deref().unwrap_or(NO_PROJECT)
+    }
+
+    /// The unfinished timer closest to its target.
+    fn next(&self) -> Option<&TimerStatus> {
+        self.timers.iter().map(|(_, timer)| timer).find(|timer| !timer.finished)
+    }
+
+    fn summary(&self) -> String {
+        let count = self.timers.len();
+        let plural = if count == 1 { "" } else { "s" };
+        let next = match self.next() {
+            Some(timer) => format!("next {} at {} (in {})", timer.name, timer.target.format("%Y-%m-%d %H:%M"), format_seconds(timer.remaining)),
+            None => "all finished".to_string(),
+        };
+        format!("{}  {} timer{}, {}", self.title(), count, plural, next)
+    }
+}
+
+fn timer_line(pid: u32, timer: &TimerStatus) -> String {
+    let state = if timer.finished {
+        "finished".to_string()
+    } else if timer.paused {
+        format!("{} left, paused", format_seconds(timer.remaining))
+    } else {
+        format!("{} left", format_seconds(timer.remaining))
+    };
+    format!("{:<20} {:>7}  {}  {}", timer.name, pid, timer.target.format("%Y-%m-%d %H:%M:%S"), state)
+}
+
+/// The timers of all running instances by project, the group with the earliest deadline first.
+fn groups() -> Vec<Group> {
+    let mut projects: BTreeMap<Option<String>, Vec<(u32, TimerStatus)>> = BTreeMap::new();
+    for pid in socket::instances() {
+        let Ok(timers) = socket::timers(pid) else {
+            continue;
+        };
+        for timer in timers {
+            projects.entry(timer.project.clone()).or_default().push((pid, timer));
+        }
+    }
+
+    let mut groups: Vec<Group> = projects.into_iter()
+        .map(|(project, mut timers)| {
+            timers.sort_by_key(|(_, timer)| (timer.finished, timer.target));
+            Group { project, timers }
+        })
+        .collect();
+    // Finished groups last, timers without a project after the projects with the same deadline
+    let deadline = |group: &Group| group.next().map_or(NaiveDateTime::MAX, |timer| timer.target);
+    groups.sort_by_key(|group| (deadline(group), group.project.is_none()));
+    groups
+}
+
+/// `endzeit ctl tree`: prints the timers of all running instances grouped by project, with the
+/// number of timers and the earliest deadline of each group. `tui` shows the same as a live tree.
+pub fn run(tui: bool) -> Result<()> {
+    if tui {
+        return run_tui();
+    }
+    let groups = groups();
+    if groups.is_empty() {
+        crate::exit_with("No endzeit running");
+    }
+    for group in groups {
+        println!("{}", group.summary());
+        for (index, (pid, timer)) in group.timers.iter().enumerate() {
+            let branch = if index + 1 == group.timers.len() { "└─" } else { "├─" };
+            println!("  {} {}", branch, timer_line(*pid, timer));
+        }
+    }
+    Ok(())
+}
+
+/// A row of the tree view, a group or one of its timers.
+enum Row {
+    Group(Option<String>),
+    Timer,
+}
+
+fn run_tui() -> Result<()> {
+    let mut collapsed: HashSet<Option<String>> = HashSet::new();
+    let mut state = ListState::default().with_selected(Some(0));
+    let mut terminal = ratatui::init();
+    loop {
+        let groups = groups();
+        let mut rows = Vec::new();
+        let mut items = Vec::new();
+        for group in &groups {
+            let folded = collapsed.contains(&group.project);
+            let marker = if folded { "▸" } else { "▾" };
+            rows.push(Row::Group(group.project.clone()));
+            items.push(ListItem::new(Line::styled(format!("{} {}", marker, group.summary()), Style::default().add_modifier(Modifier::BOLD))));
+            if folded {
+                continue;
+            }
+            for (index, (pid, timer)) in group.timers.iter().enumerate() {
+                let branch = if index + 1 == group.timers.len() { "└─" } else { "├─" };
+                rows.push(Row::Timer);
+                items.push(ListItem::new(format!("  {} {}", branch, timer_line(*pid, timer))));
+            }
+        }
+        if items.is_empty() {
+            items.push(ListItem::new("No endzeit running"));
+        }
+        if state.selected().is_some_and(|selected| selected >= items.len()) {
+            state.select(Some(items.len() - 1));
+        }
+
+        let list = List::new(items)
+            .block(Block::bordered().title(" endzeit timers by project ").title_bottom(" ↑↓ move  Enter fold  q quit "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        terminal.draw(|frame| frame.render_stateful_widget(list, frame.area(), &mut state))?;
+
+        if !event::poll(REFRESH_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Down | KeyCode::Char('j') => state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => state.select_previous(),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(Row::Group(project)) = state.selected().and_then(|selected| rows.get(selected)) {
+                    if !collapsed.remove(project) {
+                        collapsed.insert(project.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    ratatui::restore();
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+/// Units durations are broken into, largest first.
+pub const UNITS: [(&str, u64); 7] = [
+    ("y", 86_400 * 365),
+    ("mo", 86_400 * 30), // Approximating a month as 30 days
+    ("w", 604_800),
+    ("d", 86_400),
+    ("h", 3600),
+    ("m", 60),
+    ("s", 1),
+];
+
+static UNIT_FORMAT: OnceLock<UnitFormat> = OnceLock::new();
+
+/// Which units remaining times are shown in and how many of them at once.
+/// The largest chosen unit absorbs everything above it, anything below the smallest one is dropped.
+#[derive(Clone, Copy)]
+pub struct UnitFormat {
+    /// Which entries of `UNITS` are used
+    units: [bool; UNITS.len()],
+    max_units: Option<usize>,
+}
+
+impl Default for UnitFormat {
+    fn default() -> Self {
+        Self {
+            units: [true; UNITS.len()],
+            max_units: None,
+        }
+    }
+}
+
+impl UnitFormat {
+    /// Parses units like `ymwdhms` or `dhm`, in that order. `m` means months if a
+    /// larger unit than minutes follows it, `mo` is always months.
+    pub fn new(units: Option<&str>, max_units: Option<usize>) -> Result<Self, String> {
+        if max_units == Some(0) {
+            return Err("At least one unit must be shown".to_string());
+        }
+        let Some(units) = units else {
+            return Ok(Self { max_units, ..Self::default() });
+        };
+
+        let invalid = || format!("Invalid units: {}, use a selection of ymwdhms in that order", units);
+        let mut selected = [false; UNITS.len()];
+        let mut last: Option<usize> = None;
+        let mut rest = units;
+        while let Some(c) = rest.chars().next() {
+            let (name, next) = match rest.strip_prefix("mo") {
+                Some(next) => ("mo", next),
+                None if c == 'm' && rest[1..].contains(['w', 'd', 'h', 'm']) => ("mo", &rest[1..]),
+                None => rest.split_at(c.len_utf8()),
+            };
+            let index = UNITS.iter().position(|(unit, _)| *unit == name).ok_or_else(invalid)?;
+            if last.is_some_and(|last| last >= index) {
+                return Err(invalid());
+            }
+            selected[index] = true;
+            last = Some(index);
+            rest = next;
+        }
+        if last.is_none() {
+            return Err(invalid());
+        }
+
+        Ok(Self { units: selected, max_units })
+    }
+
+    /// Formats like `1d 2h 5s`, leaving out zero units. The smallest unit is always shown once a larger one is.
+    pub fn format(&self, total_seconds: u64) -> String {
+        let mut out = String::new();
+        self.write_to(total_seconds, &mut out);
+        out
+    }
+
+    /// Like `format`, but reuses `out` so formatting every tick doesn't allocate.
+    pub fn write_to(&self, total_seconds: u64, out: &mut String) {
+        out.clear();
+        let smallest_index = self.units.iter().rposition(|used| *used).unwrap_or(UNITS.len() - 1);
+        let mut shown = 0;
+        let mut remaining = total_seconds;
+        for (index, (unit, unit_seconds)) in UNITS.iter().enumerate().filter(|(index, _)| self.units[*index]) {
+            if self.max_units.is_some_and(|max_units| shown >= max_units) {
+                break;
+            }
+            let value = remaining / unit_seconds;
+            remaining %= unit_seconds;
+
+            let smallest = index == smallest_index;
+            if value > 0 || (smallest && shown > 0) {
+                if shown > 0 {
+                    out.push(' ');
+                }
+                // Minutes and months share the letter m
+                let unit = if *unit == "mo" { "m" } else { unit };
+                let _ = write!(out, "{}{}", value, unit);
+                shown += 1;
+            }
+        }
+    }
+}
+
+/// Sets the unit format used by `format_seconds`, can only be set once.
+pub fn set_unit_format(format: UnitFormat) {
+    let _ = UNIT_FORMAT.set(format);
+}
+
+/// Formats a remaining time with the configured units.
+pub fn format_seconds(total_seconds: u64) -> String {
+    let mut out = String::new();
+    write_seconds(total_seconds, &mut out);
+    out
+}
+
+/// Like `format_seconds`, but into a reusable buffer.
+pub fn write_seconds(total_seconds: u64, out: &mut String) {
+    match UNIT_FORMAT.get() {
+        Some(format) => format.write_to(total_seconds, out),
+        None => UnitFormat::default().write_to(total_seconds, out),
+    }
+}
@@ -0,0 +1,115 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use color_eyre::Result;
+use serde::Deserialize;
+use sha2::{
+    Digest,
+    Sha256
+};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/dahead/endzeit.rust/releases/latest";
+
+/// Release asset listing the SHA-256 checksums of all binaries, one `<hex>  <name>` per line.
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Replaces the running executable with the binary of the latest release for this platform,
+/// after checking it against the release's checksums. With `check_only` it just reports.
+pub fn run(check_only: bool) -> Result<()> {
+    if let Err(err) = update(check_only) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn update(check_only: bool) -> Result<(), String> {
+    let release: Release = ureq::get(RELEASES_URL).call()
+        .map_err(|e| format!("Failed to check for updates: {}", e))?
+        .into_json()
+        .map_err(|e| format!("Invalid release feed: {}", e))?;
+
+    let current = env!("CARGO_PKG_VERSION");
+    let latest = release.tag_name.trim_start_matches('v');
+    if version_parts(latest) <= version_parts(current) {
+        println!("endzeit {} is up to date", current);
+        return Ok(());
+    }
+    if check_only {
+        println!("endzeit {} is available (installed: {})", latest, current);
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let binary = release.assets.iter().find(|asset| asset.name == name)
+        .ok_or_else(|| format!("Release {} has no binary for this platform ({})", latest, name))?;
+    let checksums = release.assets.iter().find(|asset| asset.name == CHECKSUMS_ASSET)
+        .ok_or_else(|| format!("Release {} has no {}, refusing to update", latest, CHECKSUMS_ASSET))?;
+
+    let checksums = String::from_utf8(download(&checksums.browser_download_url)?)
+        .map_err(|_| format!("Invalid {}", CHECKSUMS_ASSET))?;
+    let expected = checksums.lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, file)| file.trim().trim_start_matches('*') == name)
+        .map(|(checksum, _)| checksum.to_lowercase())
+        .ok_or_else(|| format!("{} has no checksum for {}", CHECKSUMS_ASSET, name))?;
+
+    let bytes = download(&binary.browser_download_url)?;
+    let actual: String = Sha256::digest(&bytes).iter().map(|byte| format!("{:02x}", byte)).collect();
+    if actual != expected {
+        return Err(format!("Checksum mismatch for {}, refusing to update", name));
+    }
+
+    let exe = env::current_exe().map_err(|e| format!("Failed to locate the executable: {}", e))?;
+    replace_executable(&exe, &bytes).map_err(|e| format!("Failed to replace {}: {}", exe.display(), e))?;
+    println!("Updated endzeit {} to {}", current, latest);
+    Ok(())
+}
+
+fn download(url: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    ureq::get(url).call()
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    Ok(bytes)
+}
+
+/// Release binaries are named like `endzeit-x86_64-linux` or `endzeit-aarch64-macos`.
+fn asset_name() -> String {
+    format!("endzeit-{}-{}{}", env::consts::ARCH, env::consts::OS, env::consts::EXE_SUFFIX)
+}
+
+fn version_parts(version: &str) -> Vec<u64> {
+    version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+/// Writes the new binary next to the old one and moves it into place, so a failed
+/// download never leaves a half-written executable behind.
+fn replace_executable(exe: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let new = exe.with_extension("new");
+    fs::write(&new, bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&new, fs::Permissions::from_mode(0o755))?;
+    }
+    // Windows can't overwrite a running executable, but it can rename it
+    #[cfg(windows)]
+    fs::rename(exe, exe.with_extension("old"))?;
+    fs::rename(&new, exe)
+}
@@ -0,0 +1,96 @@
+use std::process::Stdio;
+use std::sync::mpsc::{
+    self,
+    Receiver
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+use chrono::TimeDelta;
+
+use crate::exec::Launcher;
+use crate::units::format_seconds;
+
+/// Exit code of a run in which a hook didn't pass its verification.
+pub const FAILED_EXIT_CODE: i32 = 3;
+/// Pause between two runs of the verification command.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `--verify-cmd`: a command that checks whether a hook did its job, like `test -f /tmp/done`.
+/// It runs until it succeeds or `timeout` has passed since the hook ran.
+#[derive(Clone)]
+pub struct Verification {
+    pub command: String,
+    pub timeout: TimeDelta,
+}
+
+/// How the verification of one timer's hook went.
+pub struct Verdict {
+    pub name: String,
+    pub command: String,
+    /// How long until the command succeeded, or why it never did
+    pub outcome: Result<Duration, String>,
+}
+
+impl Verification {
+    /// Runs the command through `launcher` on a background thread until it passes or times out.
+    /// Its output is discarded so it can't disturb the screen.
+    pub fn start(&self, name: String, launcher: Launcher) -> Receiver<Verdict> {
+        let (sender, receiver) = mpsc::channel();
+        let command_line = self.command.clone();
+        let timeout = self.timeout.to_std().unwrap_or_default();
+        thread::spawn(move || {
+            let started = Instant::now();
+            let outcome = loop {
+                let status = launcher.command(&command_line)
+                    .and_then(|mut command| command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).status());
+                match status {
+                    Ok(status) if status.success() => break Ok(started.elapsed()),
+                    Ok(status) if started.elapsed() >= timeout => {
+                        break Err(format!("still failing after {} ({})", format_seconds(timeout.as_secs()), status));
+                    }
+                    Ok(_) => thread::sleep(POLL_INTERVAL),
+                    Err(e) => break Err(format!("failed to run: {}", e)),
+                }
+            };
+            let _ = sender.send(Verdict { name, command: command_line, outcome });
+        });
+        receiver
+    }
+}
+
+impl Verdict {
+    /// The line for the gauge while the timer is still on screen.
+    pub fn note(&self) -> String {
+        match &self.outcome {
+            Ok(_) => "verified".to_string(),
+            Err(err) => format!("verification failed: {}", err),
+        }
+    }
+}
+
+/// Prints the verdicts once the timers are off the screen, and exits with `FAILED_EXIT_CODE`
+/// if a hook failed its verification.
+pub fn report(verdicts: &Receiver<Verdict>) {
+    let mut failed = false;
+    for verdict in verdicts.try_iter() {
+        match verdict.outcome {
+            Ok(took) => {
+                let after = match took.as_secs() {
+                    0 => "right away".to_string(),
+                    seconds => format!("after {}", format_seconds(seconds)),
+                };
+                println!("{}: verified, `{}` passed {}", verdict.name, verdict.command, after);
+            }
+            Err(err) => {
+                eprintln!("{}: verification failed, `{}` {}", verdict.name, verdict.command, err);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(FAILED_EXIT_CODE);
+    }
+}
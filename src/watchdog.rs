@@ -0,0 +1,35 @@
+use chrono::TimeDelta;
+use color_eyre::Result;
+
+use crate::app::App;
+use crate::clock;
+use crate::countdown::Countdown;
+use crate::exec::is_process_alive;
+use crate::settings::Settings;
+
+/// Counts down `deadline` while watching `pid`. If the process is still alive at zero the
+/// timeout action runs, if it exits before that the countdown ends early.
+pub fn run(pid: u32, deadline: TimeDelta, on_timeout: Option<String>, settings: Settings) -> Result<()> {
+    if !is_process_alive(pid) {
+        eprintln!("No process with pid {} is running", pid);
+        std::process::exit(1);
+    }
+
+    let target_datetime = clock::now() + deadline;
+    let mut countdown = Countdown::new(target_datetime, Some(format!("PID {}", pid)), settings.theme.color(0), on_timeout.into_iter().collect())
+        .with_watched_pid(pid);
+    if let Err(err) = settings.validate_hooks.check(std::slice::from_mut(&mut countdown), &settings.shared_hooks()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    let terminal = ratatui::init();
+    let app = App::new(vec![countdown], settings);
+    let app_result = app.run(terminal);
+    ratatui::restore();
+
+    if !is_process_alive(pid) && clock::now() < target_datetime {
+        println!("Process {} exited before the deadline", pid);
+    }
+    app_result
+}